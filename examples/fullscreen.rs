@@ -1,5 +1,5 @@
 use wry::Result;
-use wry::{Application, Attributes};
+use wry::{Application, Attributes, Fullscreen};
 
 fn main() -> Result<()> {
     let mut app = Application::new()?;
@@ -8,14 +8,14 @@ fn main() -> Result<()> {
         url: Some("https://www.wirple.com/".to_string()),
 
         //title: String::from("3D Render Test ^ ^"),
-        fullscreen: true,
+        fullscreen: Fullscreen::Borderless(None),
         //transparent: true, // <- Future
         //decorations: true,
         // maximized: true, and others from (https://docs.rs/wry/0.5.0/wry/struct.Attributes.html)
         ..Default::default()
     };
 
-    app.add_window_with_configs(attributes, None, None)?;
+    app.add_window_with_configs(attributes, None, None, vec![])?;
     app.run();
     Ok(())
 }
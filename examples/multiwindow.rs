@@ -48,7 +48,7 @@ async function openWindow() {
         None
     });
 
-    let _ = app.add_window_with_configs(attributes, Some(handler), None)?;
+    let _ = app.add_window_with_configs(attributes, Some(handler), None, vec![])?;
 
     std::thread::spawn(move || {
         while let Ok(url) = window_rx.recv() {
@@ -63,6 +63,7 @@ async function openWindow() {
                     },
                     None,
                     None,
+                    vec![],
                 )
                 .unwrap();
             println!("ID of new window: {:?}", new_window.id());
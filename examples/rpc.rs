@@ -13,10 +13,8 @@ fn main() -> Result<()> {
 
     let html = r#"
 <script>
-let fullscreen = false;
 async function toggleFullScreen() {
-    await rpc.call('fullscreen', !fullscreen);
-    fullscreen = !fullscreen;
+    await rpc.call('toggle-fullscreen');
 }
 
 async function getAsyncRpcResult() {
@@ -38,17 +36,10 @@ async function getAsyncRpcResult() {
 
     let handler = Box::new(|proxy: WindowProxy, mut req: RpcRequest| {
         let mut response = None;
-        if &req.method == "fullscreen" {
-            if let Some(params) = req.params.take() {
-                if let Some(mut args) = serde_json::from_value::<Vec<bool>>(params).ok() {
-                    if args.len() > 0 {
-                        let flag = args.swap_remove(0);
-                        // NOTE: in the real world we need to reply with an error
-                        let _ = proxy.set_fullscreen(flag);
-                    };
-                    response = Some(RpcResponse::new_result(req.id.take(), None));
-                }
-            }
+        if &req.method == "toggle-fullscreen" {
+            // NOTE: in the real world we need to reply with an error
+            let _ = proxy.toggle_fullscreen();
+            response = Some(RpcResponse::new_result(req.id.take(), None));
         } else if &req.method == "send-parameters" {
             if let Some(params) = req.params.take() {
                 if let Some(mut args) =
@@ -70,7 +61,7 @@ async function getAsyncRpcResult() {
         response
     });
 
-    app.add_window_with_configs(attributes, Some(handler), None)?;
+    app.add_window_with_configs(attributes, Some(handler), None, vec![])?;
 
     app.run();
     Ok(())
@@ -1,12 +1,102 @@
-use crate::{Result, RpcRequest, RpcResponse, WindowProxy};
+use crate::{
+    webview::{ProtocolHandler, ThreadedProtocolHandler},
+    ClipboardContent, ContextMenu, DevtoolsAttachment, Error, ProtocolRequest, ProtocolResponse,
+    Result, RpcRequest, RpcResponse, RpcSerializer, ScriptDialog, ScriptDialogAnswer, WindowProxy,
+};
 
-use std::{fs::read, path::Path};
+use std::{fs::read, path::Path, rc::Rc, sync::Arc, time::Duration};
 
 pub type WindowRpcHandler = Box<dyn Fn(WindowProxy, RpcRequest) -> Option<RpcResponse> + Send>;
 
+/// Decides whether a window may be closed when the user asks for it, e.g. to prompt for unsaved
+/// changes. It's given the window, and returns `true` to keep it open.
+pub type WindowCloseRequestedHandler = Box<dyn Fn(WindowProxy) -> bool + Send>;
+
+/// The handler given to `Application::set_before_unload_handler`.
+pub(crate) type WindowBeforeUnloadHandler = Rc<dyn Fn(WindowProxy, &str) -> bool>;
+
+/// The handler given to `Application::set_script_dialog_handler`.
+pub(crate) type WindowScriptDialogHandler =
+    Rc<dyn Fn(WindowProxy, ScriptDialog) -> ScriptDialogAnswer>;
+
+/// The handler given to `Application::set_paste_handler`.
+pub(crate) type WindowPasteHandler = Rc<dyn Fn(WindowProxy, ClipboardContent) -> bool>;
+
+/// The handler given to `Application::set_context_menu_handler`.
+pub(crate) type WindowContextMenuHandler =
+    Rc<dyn Fn(WindowProxy, ContextMenu) -> Option<ContextMenu>>;
+
 pub struct CustomProtocol {
     pub name: String,
-    pub handler: Box<dyn Fn(&str) -> Result<Vec<u8>> + Send>,
+    handler: CustomProtocolHandler,
+}
+
+// Local handlers are sent to the thread running the application, where they're called.
+type SendProtocolHandler = Box<dyn Fn(&ProtocolRequest) -> Result<ProtocolResponse> + Send>;
+
+enum CustomProtocolHandler {
+    Local(SendProtocolHandler),
+    Threaded(ThreadedProtocolHandler),
+}
+
+impl CustomProtocol {
+    /// Creates a protocol whose handler is only given the URI of the requests, like the handlers
+    /// of the previous versions.
+    pub fn new<F>(name: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(&str) -> Result<Vec<u8>> + Send + 'static,
+    {
+        Self::with_request(name, move |request| handler(&request.uri).map(Into::into))
+    }
+
+    /// Creates a protocol whose handler is given the method and headers of the requests too. It's
+    /// called on the main thread, like [`WebViewBuilder::register_request_protocol`].
+    ///
+    /// [`WebViewBuilder::register_request_protocol`]: crate::webview::WebViewBuilder::register_request_protocol
+    pub fn with_request<F>(name: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(&ProtocolRequest) -> Result<ProtocolResponse> + Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            handler: CustomProtocolHandler::Local(Box::new(handler)),
+        }
+    }
+
+    /// Creates a protocol whose handler is called on worker threads, like
+    /// [`WebViewBuilder::register_threaded_protocol`].
+    ///
+    /// [`WebViewBuilder::register_threaded_protocol`]: crate::webview::WebViewBuilder::register_threaded_protocol
+    pub fn threaded<F>(name: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(&ProtocolRequest) -> Result<ProtocolResponse> + Send + Sync + 'static,
+    {
+        Self {
+            name: name.into(),
+            handler: CustomProtocolHandler::Threaded(Arc::new(handler)),
+        }
+    }
+
+    pub(crate) fn into_handler(self) -> (String, ProtocolHandler) {
+        let handler = match self.handler {
+            CustomProtocolHandler::Local(handler) => ProtocolHandler::Local(handler),
+            CustomProtocolHandler::Threaded(handler) => ProtocolHandler::Threaded(handler),
+        };
+        (self.name, handler)
+    }
+
+    // Checked before the window is created, the WebView would only fail once it's built.
+    pub(crate) fn check_unique(protocols: &[CustomProtocol]) -> Result<()> {
+        for (i, protocol) in protocols.iter().enumerate() {
+            if protocols[..i]
+                .iter()
+                .any(|other| other.name == protocol.name)
+            {
+                return Err(Error::DuplicateCustomProtocol(protocol.name.clone()));
+            }
+        }
+        Ok(())
+    }
 }
 
 ///	An icon used for the window title bar, taskbar, etc.
@@ -24,6 +114,50 @@ impl Icon {
     }
 }
 
+/// How a window is shown in fullscreen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fullscreen {
+    /// The window isn't fullscreen.
+    None,
+    /// The window covers a whole monitor without changing its video mode. The monitor is given by
+    /// its index in the list of monitors of the system, `None` picks the one the window opens on.
+    Borderless(Option<usize>),
+    /// The window takes exclusive control of the primary monitor, switching it to its highest
+    /// resolution and refresh rate. This is what games usually do.
+    Exclusive,
+}
+
+impl From<bool> for Fullscreen {
+    /// Converts the former boolean option, `true` being [`Fullscreen::Borderless`] on the monitor
+    /// the window opens on.
+    fn from(fullscreen: bool) -> Self {
+        if fullscreen {
+            Fullscreen::Borderless(None)
+        } else {
+            Fullscreen::None
+        }
+    }
+}
+
+/// The kind of window, which decides how the window manager treats it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowTypeHint {
+    /// A normal top-level window.
+    Normal,
+    /// A dialog window.
+    Dialog,
+    /// A persistent utility window, like a palette or toolbox.
+    Utility,
+    /// A dock or panel, usually kept above other windows and without a taskbar entry.
+    Dock,
+    /// A tooltip.
+    Tooltip,
+    /// A splash screen shown while the application is starting.
+    Splashscreen,
+    /// A notification, like a bubble shown when something happened.
+    Notification,
+}
+
 /// Attributes to use when creating a webview window.
 #[derive(Debug, Clone)]
 pub struct Attributes {
@@ -32,11 +166,35 @@ pub struct Attributes {
     /// The default is `true`.
     pub resizable: bool,
 
+    /// Whether the window can be minimized from its title bar. See [`WindowProxy::set_minimizable`].
+    ///
+    /// The default is `true`.
+    pub minimizable: bool,
+
+    /// Whether the window can be maximized from its title bar. See [`WindowProxy::set_maximizable`].
+    ///
+    /// The default is `true`.
+    pub maximizable: bool,
+
+    /// Whether the window can be closed from its title bar. See [`WindowProxy::set_closable`].
+    ///
+    /// The default is `true`.
+    pub closable: bool,
+
     /// The title of the window in the title bar.
     ///
     /// The default is `"wry"`.
     pub title: String,
 
+    /// Whether the window title follows the title of the page, like a browser tab does.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Unsupported.
+    ///
+    /// The default is `false`.
+    pub html_title_as_window_title: bool,
+
     /// Whether the window should be maximized upon creation.
     ///
     /// The default is `false`.
@@ -47,12 +205,34 @@ pub struct Attributes {
     /// The default is `true`.
     pub visible: bool,
 
+    /// Whether the window takes the focus when it's shown and its WebView takes the keyboard
+    /// focus. Disable it for windows that shouldn't interrupt typing in another one, like
+    /// notifications and command palettes.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    ///
+    /// The default is `true`.
+    pub focused: bool,
+
     /// Whether the WebView window should be transparent. If this is true, writing colors
     /// with alpha values different than `1.0` will produce a transparent window.
     ///
     /// The default is `false`.
     pub transparent: bool,
 
+    /// The color of the window and the WebView until the page paints its own background, as
+    /// `(red, green, blue, alpha)`. It avoids a white flash before the pages of dark themes load.
+    /// It's ignored when [`Attributes::transparent`] is set.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported.
+    ///
+    /// The default is `None`, the color of the platform.
+    pub background_color: Option<(u8, u8, u8, u8)>,
+
     /// Whether the window should have borders and bars.
     ///
     /// The default is `true`.
@@ -63,6 +243,13 @@ pub struct Attributes {
     /// The default is `false`.
     pub always_on_top: bool,
 
+    /// Whether the window should always be below other windows, like a desktop widget. It's
+    /// ignored when [`Attributes::always_on_top`] is set. See
+    /// [`WindowProxy::set_always_on_bottom`].
+    ///
+    /// The default is `false`.
+    pub always_on_bottom: bool,
+
     /// The width of the window.
     ///
     /// The default is `800.0`.
@@ -93,6 +280,12 @@ pub struct Attributes {
     /// The default is `None`.
     pub max_height: Option<f64>,
 
+    /// Whether the initial size of the window should be shrunk to fit on the monitor it opens
+    /// on. Disable it for windows which are intentionally larger than the screen.
+    ///
+    /// The default is `true`.
+    pub fit_to_screen: bool,
+
     /// The horizontal position of the window's top left corner.
     ///
     /// The default is `None`.
@@ -103,21 +296,243 @@ pub struct Attributes {
     /// The default is `None`.
     pub y: Option<f64>,
 
-    /// Whether to start the window in fullscreen or not.
+    /// Whether to start the window in fullscreen, and on which monitor. A boolean can still be
+    /// converted into it with `.into()`.
     ///
-    /// The default is `false`.
-    pub fullscreen: bool,
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** [`Fullscreen::Exclusive`] is the same as [`Fullscreen::Borderless`] on the
+    ///   monitor the window opens on, as GTK can't change video modes.
+    ///
+    /// The default is [`Fullscreen::None`].
+    pub fullscreen: Fullscreen,
 
     /// The window icon.
     ///
     /// The default is `None`.
     pub icon: Option<Icon>,
 
+    /// An image shown in a small window of its own until the first page of this window is
+    /// loaded, e.g. a logo while the WebView starts up. The window is only shown afterwards. This
+    /// is ignored if the window isn't visible.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported, the window is shown right away.
+    ///
+    /// The default is `None`.
+    pub splash: Option<Icon>,
+
     /// Whether to hide the window icon in the taskbar/dock.
     ///
     /// The default is `false`
     pub skip_taskbar: bool,
 
+    /// The width in pixels of an invisible border along the edges of the WebView that resizes
+    /// the window when dragged, meant for windows without decorations.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    ///
+    /// The default is `None`.
+    pub resize_border: Option<u32>,
+
+    /// The kind of window, which decides how the window manager treats it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    ///
+    /// The default is [`WindowTypeHint::Normal`].
+    pub window_type: WindowTypeHint,
+
+    /// Whether to emit [`WindowEvent::KeyDown`](crate::WindowEvent::KeyDown) and
+    /// [`WindowEvent::KeyUp`](crate::WindowEvent::KeyUp) for the keys pressed and released in the
+    /// window, e.g. for custom shortcuts or game controls.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    ///
+    /// The default is `false`.
+    pub keyboard_events: bool,
+
+    /// Emits [`WindowEvent::Idle`](crate::WindowEvent::Idle) once the pointer and the keyboard
+    /// haven't been used in the window for this long, and
+    /// [`WindowEvent::Active`](crate::WindowEvent::Active) when they're used again, e.g. to hide
+    /// the controls of a media player while nobody touches them.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    ///
+    /// The default is `None`, which emits no idle events.
+    pub idle_hint_timeout: Option<Duration>,
+
+    /// Whether swiping with two fingers on a touchpad navigates back and forward in history.
+    ///
+    /// The default is `false`.
+    pub back_forward_navigation_gestures: bool,
+
+    /// Whether the page bounces (rubber-bands) when scrolled past its edges on a touchpad.
+    /// Disabling it also stops scroll chaining out of the page.
+    ///
+    /// The default is `true`.
+    pub overscroll: bool,
+
+    /// Whether the zoom level of the WebView is multiplied by the scale factor of its monitor, and
+    /// updated when the window moves to a monitor with another scale factor, for content that
+    /// doesn't scale its CSS pixels itself.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    ///
+    /// The default is `false`.
+    pub auto_dpi_zoom: bool,
+
+    /// The initial zoom level of the WebView, where `1.0` is 100%. It's clamped between `0.25`
+    /// and `5.0`, before [`Attributes::auto_dpi_zoom`] multiplies it.
+    ///
+    /// The default is `None`, which keeps the zoom level of the web engine.
+    pub zoom_level: Option<f64>,
+
+    /// Overrides the device scale factor the WebView renders at, i.e. its
+    /// `window.devicePixelRatio`, regardless of the monitor, e.g. to get the same rendering on
+    /// every machine for visual regression tests.
+    ///
+    /// The default is `None`, which follows the monitor.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Applied through the zoom level, which is multiplied by this scale factor over
+    ///   the one of the monitor. GTK can't change the scale of a single window.
+    /// - **Windows:** Unsupported.
+    pub device_scale_factor: Option<f64>,
+
+    /// Whether JavaScript running in a `file://` page can read other `file://` URLs, e.g. to
+    /// `fetch` files next to the page.
+    ///
+    /// This makes every local file readable by a page as long as its path is known, so only
+    /// enable it for content that is shipped with the application.
+    ///
+    /// The default is `false`.
+    pub allow_file_access_from_file_urls: bool,
+
+    /// Whether JavaScript running in a `file://` page can access content from any origin,
+    /// bypassing the same-origin policy.
+    ///
+    /// A page with this enabled can read local files and send them anywhere, so only enable it
+    /// for content that is shipped with the application and never loads remote scripts.
+    ///
+    /// The default is `false`.
+    pub allow_universal_access_from_file_urls: bool,
+
+    /// How long a custom protocol handler created with [`CustomProtocol::threaded`] may take to
+    /// answer a request. Requests it doesn't answer in time fail. Other handlers run on the main
+    /// thread and can't time out.
+    ///
+    /// The default is `None`.
+    pub custom_protocol_timeout: Option<Duration>,
+
+    /// How many requests a custom protocol handler created with [`CustomProtocol::threaded`] may
+    /// handle at once. When set, the handler runs on that many threads of the window and the
+    /// other requests wait in a queue. The threads exit once the window is closed.
+    ///
+    /// The default is `None`, which handles each request on a thread of its own.
+    pub custom_protocol_concurrency: Option<usize>,
+
+    /// Whether pages from other origins, e.g. a remote page loaded over `https://`, can `fetch`
+    /// from the custom protocol.
+    ///
+    /// The default is `false`.
+    pub custom_protocol_cors: bool,
+
+    /// Whether the custom protocol is treated like `file://`, so that only local pages can load
+    /// its URLs.
+    ///
+    /// The default is `false`.
+    pub custom_protocol_local: bool,
+
+    /// The languages the WebView asks content in, most preferred first, e.g. `["fr-CA", "fr"]`.
+    /// They are sent in the `Accept-Language` header and exposed to JavaScript as
+    /// `navigator.languages`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** The languages belong to the web context of the window, not to the window.
+    ///   Windows sharing a web context, because they're in the same
+    ///   [`storage_partition`](Attributes::storage_partition) or because of
+    ///   [`Application::set_web_process_limit`](crate::Application::set_web_process_limit), all use
+    ///   the languages of the last one created with `Some` languages.
+    /// - **Windows / macOS:** Unsupported.
+    ///
+    /// The default is `None`, which uses the system languages.
+    pub accept_languages: Option<Vec<String>>,
+
+    /// The user agent the WebView identifies itself with, e.g. to tell the embedding application
+    /// apart from browsers.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported.
+    ///
+    /// The default is `None`, which uses the user agent of the web engine.
+    pub user_agent: Option<String>,
+
+    /// Whether the developer tools of the WebView can be opened, from its context menu or with
+    /// [`WindowProxy::open_devtools`](crate::WindowProxy::open_devtools). They are always enabled
+    /// in debug builds.
+    ///
+    /// The default is `false`.
+    pub devtools: bool,
+
+    /// Where the developer tools are shown when opened with
+    /// [`WindowProxy::open_devtools`](crate::WindowProxy::open_devtools).
+    ///
+    /// The default is [`DevtoolsAttachment::Attached`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported, the developer tools are always detached.
+    pub devtools_attachment: DevtoolsAttachment,
+
+    /// The name of the storage partition of the window. Windows in the same partition share
+    /// cookies, local storage and caches, like the windows of a browser profile, while windows in
+    /// different partitions are isolated from each other. The name is used as a directory name,
+    /// so adding a window fails with [`Error::InvalidStoragePartition`] when it's empty, `.`,
+    /// `..` or contains a slash.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Windows in the same partition also share their web processes and custom
+    ///   protocol handlers.
+    /// - **Windows / macOS:** Unsupported.
+    ///
+    /// The default is `None`, which gives the window a web context of its own, unless
+    /// [`Application::set_web_process_limit`](crate::Application::set_web_process_limit) is used.
+    pub storage_partition: Option<String>,
+
+    /// The HTML shown instead of the default error page of the web engine when a page fails to
+    /// load, e.g. because of a network error or an invalid certificate. The `{url}` and `{error}`
+    /// placeholders are replaced with the URL of the page and the error message. Reloading the
+    /// window tries to load the page again.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    ///
+    /// The default is `None`.
+    pub error_page: Option<String>,
+
+    /// Whether to define `window.external` and listen to its messages, which the RPC handler of
+    /// the window relies on. Disable it for pages that define their own `window.external`; the
+    /// RPC handler is then never called.
+    ///
+    /// The default is `true`.
+    pub inject_rpc_bridge: bool,
+
     /// The URL to be loaded in the webview window.
     ///
     /// The default is `None`.
@@ -130,30 +545,85 @@ pub struct Attributes {
 }
 
 impl Attributes {
+    // The storage partition names a directory of the data directory, it can't lead out of it.
+    pub(crate) fn check_storage_partition(&self) -> Result<()> {
+        match self.storage_partition.as_deref() {
+            Some(name)
+                if name.is_empty()
+                    || name == "."
+                    || name == ".."
+                    || name.contains(&['/', '\\', '\0'][..]) =>
+            {
+                Err(Error::InvalidStoragePartition(name.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub(crate) fn split(self) -> (InnerWindowAttributes, InnerWebViewAttributes) {
         (
             InnerWindowAttributes {
                 resizable: self.resizable,
+                minimizable: self.minimizable,
+                maximizable: self.maximizable,
+                closable: self.closable,
                 title: self.title,
                 maximized: self.maximized,
                 visible: self.visible,
+                #[cfg(target_os = "linux")]
+                focused: self.focused,
                 transparent: self.transparent,
+                #[cfg(not(target_os = "windows"))]
+                background_color: self.background_color,
                 decorations: self.decorations,
                 always_on_top: self.always_on_top,
+                always_on_bottom: self.always_on_bottom,
                 width: self.width,
                 height: self.height,
                 min_width: self.min_width,
                 min_height: self.min_height,
                 max_width: self.max_width,
                 max_height: self.max_height,
+                fit_to_screen: self.fit_to_screen,
                 x: self.x,
                 y: self.y,
                 fullscreen: self.fullscreen,
                 icon: self.icon,
                 skip_taskbar: self.skip_taskbar,
+                #[cfg(target_os = "linux")]
+                window_type: self.window_type,
+                #[cfg(target_os = "linux")]
+                keyboard_events: self.keyboard_events,
+                #[cfg(target_os = "linux")]
+                idle_hint_timeout: self.idle_hint_timeout,
             },
             InnerWebViewAttributes {
+                focused: self.focused,
                 transparent: self.transparent,
+                background_color: self.background_color,
+                html_title_as_window_title: self.html_title_as_window_title,
+                resize_border: self.resize_border,
+                #[cfg(target_os = "linux")]
+                splash: self.splash,
+                back_forward_navigation_gestures: self.back_forward_navigation_gestures,
+                overscroll: self.overscroll,
+                auto_dpi_zoom: self.auto_dpi_zoom,
+                zoom_level: self.zoom_level,
+                device_scale_factor: self.device_scale_factor,
+                allow_file_access_from_file_urls: self.allow_file_access_from_file_urls,
+                allow_universal_access_from_file_urls: self.allow_universal_access_from_file_urls,
+                custom_protocol_timeout: self.custom_protocol_timeout,
+                custom_protocol_concurrency: self.custom_protocol_concurrency,
+                custom_protocol_cors: self.custom_protocol_cors,
+                custom_protocol_local: self.custom_protocol_local,
+                accept_languages: self.accept_languages,
+                user_agent: self.user_agent,
+                devtools: self.devtools,
+                devtools_attachment: self.devtools_attachment,
+                #[cfg(target_os = "linux")]
+                storage_partition: self.storage_partition,
+                error_page: self.error_page,
+                inject_rpc_bridge: self.inject_rpc_bridge,
                 url: self.url,
                 initialization_scripts: self.initialization_scripts,
             },
@@ -166,23 +636,54 @@ impl Default for Attributes {
     fn default() -> Self {
         Self {
             resizable: true,
+            minimizable: true,
+            maximizable: true,
+            closable: true,
             title: "wry".to_owned(),
+            html_title_as_window_title: false,
             maximized: false,
             visible: true,
+            focused: true,
             transparent: false,
+            background_color: None,
             decorations: true,
             always_on_top: false,
+            always_on_bottom: false,
             width: 800.0,
             height: 600.0,
             min_width: None,
             min_height: None,
             max_width: None,
             max_height: None,
+            fit_to_screen: true,
             x: None,
             y: None,
-            fullscreen: false,
+            fullscreen: Fullscreen::None,
             icon: None,
             skip_taskbar: false,
+            splash: None,
+            resize_border: None,
+            window_type: WindowTypeHint::Normal,
+            keyboard_events: false,
+            idle_hint_timeout: None,
+            back_forward_navigation_gestures: false,
+            overscroll: true,
+            auto_dpi_zoom: false,
+            zoom_level: None,
+            device_scale_factor: None,
+            allow_file_access_from_file_urls: false,
+            allow_universal_access_from_file_urls: false,
+            custom_protocol_timeout: None,
+            custom_protocol_concurrency: None,
+            custom_protocol_cors: false,
+            custom_protocol_local: false,
+            accept_languages: None,
+            user_agent: None,
+            devtools: false,
+            devtools_attachment: DevtoolsAttachment::Attached,
+            storage_partition: None,
+            error_page: None,
+            inject_rpc_bridge: true,
             url: None,
             initialization_scripts: vec![],
         }
@@ -191,27 +692,78 @@ impl Default for Attributes {
 
 pub(crate) struct InnerWindowAttributes {
     pub resizable: bool,
+    pub minimizable: bool,
+    pub maximizable: bool,
+    pub closable: bool,
     pub title: String,
     pub maximized: bool,
     pub visible: bool,
+    #[cfg(target_os = "linux")]
+    pub focused: bool,
     pub transparent: bool,
+    #[cfg(not(target_os = "windows"))]
+    pub background_color: Option<(u8, u8, u8, u8)>,
     pub decorations: bool,
     pub always_on_top: bool,
+    pub always_on_bottom: bool,
     pub width: f64,
     pub height: f64,
     pub min_width: Option<f64>,
     pub min_height: Option<f64>,
     pub max_width: Option<f64>,
     pub max_height: Option<f64>,
+    pub fit_to_screen: bool,
     pub x: Option<f64>,
     pub y: Option<f64>,
-    pub fullscreen: bool,
+    pub fullscreen: Fullscreen,
     pub icon: Option<Icon>,
     pub skip_taskbar: bool,
+    #[cfg(target_os = "linux")]
+    pub window_type: WindowTypeHint,
+    #[cfg(target_os = "linux")]
+    pub keyboard_events: bool,
+    #[cfg(target_os = "linux")]
+    pub idle_hint_timeout: Option<Duration>,
 }
 
 pub(crate) struct InnerWebViewAttributes {
+    pub focused: bool,
     pub transparent: bool,
+    pub background_color: Option<(u8, u8, u8, u8)>,
+    pub html_title_as_window_title: bool,
+    pub resize_border: Option<u32>,
+    #[cfg(target_os = "linux")]
+    pub splash: Option<Icon>,
+    pub back_forward_navigation_gestures: bool,
+    pub overscroll: bool,
+    pub auto_dpi_zoom: bool,
+    pub zoom_level: Option<f64>,
+    pub device_scale_factor: Option<f64>,
+    pub allow_file_access_from_file_urls: bool,
+    pub allow_universal_access_from_file_urls: bool,
+    pub custom_protocol_timeout: Option<Duration>,
+    pub custom_protocol_concurrency: Option<usize>,
+    pub custom_protocol_cors: bool,
+    pub custom_protocol_local: bool,
+    pub accept_languages: Option<Vec<String>>,
+    pub user_agent: Option<String>,
+    pub devtools: bool,
+    pub devtools_attachment: DevtoolsAttachment,
+    #[cfg(target_os = "linux")]
+    pub storage_partition: Option<String>,
+    pub error_page: Option<String>,
+    pub inject_rpc_bridge: bool,
     pub url: Option<String>,
     pub initialization_scripts: Vec<String>,
 }
+
+/// Settings of the [`Application`](crate::Application) applied to every window it adds.
+#[derive(Default)]
+pub(crate) struct ApplicationAttributes {
+    pub initialization_scripts: Vec<String>,
+    pub before_unload_handler: Option<WindowBeforeUnloadHandler>,
+    pub script_dialog_handler: Option<WindowScriptDialogHandler>,
+    pub paste_handler: Option<WindowPasteHandler>,
+    pub context_menu_handler: Option<WindowContextMenuHandler>,
+    pub rpc_serializer: Option<Rc<dyn RpcSerializer>>,
+}
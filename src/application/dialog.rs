@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+/// The set of buttons shown by a message dialog.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageDialogButtons {
+    Ok,
+    OkCancel,
+    YesNo,
+    YesNoCancel,
+}
+
+impl MessageDialogButtons {
+    /// The result reported when the dialog is closed without picking a button.
+    pub(crate) fn dismissed(self) -> MessageDialogResult {
+        match self {
+            MessageDialogButtons::Ok => MessageDialogResult::Ok,
+            MessageDialogButtons::YesNo => MessageDialogResult::No,
+            MessageDialogButtons::OkCancel | MessageDialogButtons::YesNoCancel => {
+                MessageDialogResult::Cancel
+            }
+        }
+    }
+}
+
+/// The button picked in a message dialog.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageDialogResult {
+    Ok,
+    Cancel,
+    Yes,
+    No,
+}
+
+/// Options of a native file dialog.
+#[derive(Debug, Clone, Default)]
+pub struct FileDialogOptions {
+    /// The title of the dialog.
+    ///
+    /// The default is `None`, which uses the platform's default title.
+    pub title: Option<String>,
+
+    /// The directory the dialog starts in.
+    ///
+    /// The default is `None`, which lets the platform pick one.
+    pub directory: Option<PathBuf>,
+
+    /// The file name suggested by a save dialog. Ignored by open dialogs.
+    ///
+    /// The default is `None`.
+    pub file_name: Option<String>,
+
+    /// Whether more than one file can be selected. Ignored by save dialogs.
+    ///
+    /// The default is `false`.
+    pub multiple: bool,
+
+    /// Filters the user can pick from to restrict the files shown. The first one is selected
+    /// initially.
+    ///
+    /// The default is empty, which shows every file.
+    pub filters: Vec<FileFilter>,
+}
+
+/// A named set of file extensions shown by a file dialog.
+#[derive(Debug, Clone)]
+pub struct FileFilter {
+    /// The name of the filter, e.g. `"Images"`.
+    pub name: String,
+    /// The extensions matched by the filter without the leading dot, e.g. `"png"`.
+    pub extensions: Vec<String>,
+}
+
+impl FileFilter {
+    /// Creates a filter matching the given extensions.
+    pub fn new<N: Into<String>, E: Into<String>>(
+        name: N,
+        extensions: impl IntoIterator<Item = E>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            extensions: extensions.into_iter().map(Into::into).collect(),
+        }
+    }
+}
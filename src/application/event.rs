@@ -0,0 +1,250 @@
+use crate::{Error, LoadState, Result, WindowId};
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc::{channel, Receiver, Sender},
+    Arc, Mutex, PoisonError,
+};
+
+/// How many events wait for [`ApplicationProxy::listen_event`](crate::ApplicationProxy::listen_event)
+/// at most. The oldest are dropped beyond that, so applications that never listen don't keep
+/// every event in memory.
+const MAX_QUEUED_EVENTS: usize = 1024;
+
+/// Describes an event emitted by the [`Application`](crate::Application).
+///
+/// Events are received with [`ApplicationProxy::listen_event`](crate::ApplicationProxy::listen_event).
+#[derive(Debug, Clone, PartialEq)]
+pub enum WryEvent {
+    /// An event emitted by one of the WebView windows.
+    WindowEvent {
+        window_id: WindowId,
+        event: WindowEvent,
+    },
+    /// The application was asked to open a URL with
+    /// [`Application::open_urls`](crate::Application::open_urls), by this launch or, for an
+    /// application with an ID, by a later one.
+    OpenUrl(String),
+    /// The system is running low on memory, e.g. to drop caches. The web processes free memory
+    /// on their own, see
+    /// [`Application::set_memory_pressure_settings`](crate::Application::set_memory_pressure_settings).
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Reported by the memory monitor of GLib, which needs GLib 2.64 and the `v2_36`
+    ///   feature.
+    /// - **Windows / macOS:** Unsupported.
+    MemoryPressure(MemoryPressureLevel),
+}
+
+/// How low the system is on memory, in [`WryEvent::MemoryPressure`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemoryPressureLevel {
+    /// Memory that can be cheaply recreated should be freed.
+    Low,
+    /// Memory that isn't needed right now should be freed.
+    Medium,
+    /// Processes are about to be killed to free memory, the application may be next.
+    Critical,
+}
+
+/// Describes an event emitted by a WebView window.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowEvent {
+    /// The user asked to close the window. The window is closed right after this is emitted.
+    CloseRequested,
+    /// The scale factor of the window changed, e.g. because it was moved to a monitor with a
+    /// different DPI or the user changed their display settings.
+    DpiChanged { old: f64, new: f64 },
+    /// The window was moved to another monitor, i.e. its center is now on a different one. The
+    /// monitor is given by its index in the list of monitors of the system, like in
+    /// [`Fullscreen::Borderless`](crate::Fullscreen::Borderless).
+    MonitorChanged(usize),
+    /// The window was resized, given its new inner size in logical pixels. Successive changes,
+    /// e.g. while the user drags an edge of the window, are coalesced.
+    Resized { width: u32, height: u32 },
+    /// The window was moved, given its new position in logical pixels. Successive changes, e.g.
+    /// while the user drags the window, are coalesced.
+    Moved { x: i32, y: i32 },
+    /// The window gained or lost the keyboard focus, e.g. to pause background work while it's
+    /// inactive. Focus moving inside the window, like clicking an input of the page, doesn't
+    /// emit it.
+    Focused(bool),
+    /// The pointer and the keyboard haven't been used in the window for the duration of
+    /// [`Attributes::idle_hint_timeout`](crate::Attributes::idle_hint_timeout).
+    Idle,
+    /// The pointer or the keyboard was used in the window again after [`WindowEvent::Idle`].
+    Active,
+    /// The window entered or left fullscreen.
+    Fullscreen(bool),
+    /// The developer tools of the WebView were opened.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    DevtoolsOpened,
+    /// The developer tools of the WebView were closed.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    DevtoolsClosed,
+    /// The cursor entered the content area of the window.
+    CursorEntered,
+    /// The cursor left the content area of the window.
+    CursorLeft,
+    /// A custom item of the context menu was activated, given by its id. See
+    /// [`Application::set_context_menu_handler`](crate::Application::set_context_menu_handler).
+    ContextMenuItem(String),
+    /// The WebView made progress loading a page of the main frame, e.g. to show a spinner while
+    /// it loads or detect failed navigations. Every load ends with either
+    /// [`LoadState::Finished`] or [`LoadState::Failed`].
+    PageLoad { url: String, state: LoadState },
+    /// The formatting at the caret or the selection of the page changed, e.g. to keep the buttons
+    /// of an editor toolbar in sync. Successive changes to the same state aren't emitted.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    EditorState {
+        /// Whether text typed at the caret, or the selected text, is bold.
+        bold: bool,
+        italic: bool,
+        underline: bool,
+        /// Whether some content of the page is selected.
+        selection: bool,
+    },
+    /// The matches of [`WindowProxy::find`](crate::WindowProxy::find) were counted, or the
+    /// selected one moved with [`WindowProxy::find_next`](crate::WindowProxy::find_next) or
+    /// [`WindowProxy::find_previous`](crate::WindowProxy::find_previous), e.g. to show "2/15" in
+    /// a search bar. `current` starts at `1`, both are `0` when the text isn't found.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    FindResult { current: u32, total: u32 },
+    /// A key was pressed while the window had the focus, including when the focus is in the
+    /// page, e.g. for global shortcuts. Only emitted when enabled with
+    /// [`Attributes::keyboard_events`](crate::Attributes::keyboard_events).
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** The WebView handles every key it receives. The keys the page doesn't handle
+    ///   are then emitted again, with `handled` being `false`.
+    /// - **Windows / macOS:** Unsupported.
+    KeyDown {
+        /// The name of the key, e.g. `a`, `A`, `Return` or `F5`, to match shortcuts against. Like
+        /// `keyval`, it depends on the keyboard layout and the modifiers.
+        key: String,
+        /// The symbol of the key, e.g. `GDK_KEY_a`, which depends on the keyboard layout.
+        keyval: u32,
+        /// The hardware code of the key, which doesn't depend on the keyboard layout.
+        keycode: u16,
+        modifiers: ModifiersState,
+        /// Whether the window or the WebView handled the key, e.g. as a shortcut or text input.
+        handled: bool,
+    },
+    /// A key was released while the window had the focus. See [`WindowEvent::KeyDown`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    KeyUp {
+        key: String,
+        keyval: u32,
+        keycode: u16,
+        modifiers: ModifiersState,
+        handled: bool,
+    },
+}
+
+bitflags! {
+    /// The modifier keys held during a keyboard event.
+    #[derive(Default)]
+    pub struct ModifiersState: u32 {
+        const SHIFT = 1 << 0;
+        const CTRL = 1 << 1;
+        const ALT = 1 << 2;
+        /// The Windows, Command or Super key.
+        const SUPER = 1 << 3;
+    }
+}
+
+/// Creates the queue of events the [`Application`](crate::Application) emits.
+pub(crate) fn event_queue() -> (EventSender, EventReceiver) {
+    let (sender, receiver) = channel();
+    let receiver = EventReceiver {
+        receiver: Arc::new(Mutex::new(receiver)),
+        queued: Arc::new(AtomicUsize::new(0)),
+    };
+    (
+        EventSender {
+            sender,
+            receiver: receiver.clone(),
+        },
+        receiver,
+    )
+}
+
+/// Emits events, dropping the oldest one when [`MAX_QUEUED_EVENTS`] are already waiting.
+#[derive(Clone)]
+pub(crate) struct EventSender {
+    sender: Sender<WryEvent>,
+    receiver: EventReceiver,
+}
+
+impl EventSender {
+    pub fn send(&self, event: WryEvent) -> Result<()> {
+        // Counted before sending so that receiving it never brings the count below zero.
+        let queued = self.receiver.queued.fetch_add(1, Ordering::SeqCst) + 1;
+        if self.sender.send(event).is_err() {
+            self.receiver.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(Error::MessageSender);
+        }
+        if queued > MAX_QUEUED_EVENTS {
+            // If a listener holds the lock, it's about to drain the queue anyway.
+            if let Ok(receiver) = self.receiver.receiver.try_lock() {
+                if receiver.try_recv().is_ok() {
+                    self.receiver.queued.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Receives the events of an [`EventSender`]. Clones share the same queue, so each event is
+/// received only once.
+#[derive(Clone)]
+pub(crate) struct EventReceiver {
+    receiver: Arc<Mutex<Receiver<WryEvent>>>,
+    queued: Arc<AtomicUsize>,
+}
+
+impl EventReceiver {
+    pub fn recv(&self) -> Result<WryEvent> {
+        let receiver = self.receiver.lock().unwrap_or_else(PoisonError::into_inner);
+        let event = receiver.recv()?;
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_drop_the_oldest_events_when_nobody_listens() {
+        let (sender, receiver) = event_queue();
+        for i in 0..=MAX_QUEUED_EVENTS {
+            sender.send(WryEvent::OpenUrl(i.to_string())).unwrap();
+        }
+
+        assert_eq!(receiver.recv().unwrap(), WryEvent::OpenUrl("1".to_string()));
+        assert_eq!(
+            receiver.queued.load(Ordering::SeqCst),
+            MAX_QUEUED_EVENTS - 1
+        );
+    }
+}
@@ -1,29 +1,87 @@
 use crate::{
-    application::{App, AppProxy, InnerWebViewAttributes, InnerWindowAttributes},
-    ApplicationProxy, Attributes, CustomProtocol, Error, Icon, Message, Result, WebView,
-    WebViewBuilder, WindowMessage, WindowProxy, WindowRpcHandler,
+    application::{
+        event_queue, App, AppProxy, ApplicationAttributes, EventReceiver, EventSender,
+        InnerWebViewAttributes, InnerWindowAttributes, WindowBeforeUnloadHandler,
+        WindowCloseRequestedHandler, WindowContextMenuHandler, WindowPasteHandler,
+        WindowScriptDialogHandler, MIN_CONTENT_SIZE_SCRIPT,
+    },
+    ApplicationProxy, Attributes, CustomProtocol, Error, FileDialogOptions, Icon,
+    MemoryPressureSettings, Message, MessageDialogButtons, MessageDialogResult, Result,
+    RpcSerializer, SnapRegion, UserAttentionType, WebView, WebViewBuilder, WindowMessage,
+    WindowProxy, WindowResponse, WindowRpcHandler, WryEvent,
 };
 #[cfg(target_os = "macos")]
-use winit::platform::macos::{ActivationPolicy, WindowBuilderExtMacOS};
+use winit::platform::macos::{ActivationPolicy, WindowBuilderExtMacOS, WindowExtMacOS};
 pub use winit::window::WindowId;
 use winit::{
-    dpi::{LogicalPosition, LogicalSize},
+    dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
     event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
-    window::{Fullscreen, Icon as WinitIcon, Window, WindowAttributes, WindowBuilder},
+    platform::run_return::EventLoopExtRunReturn,
+    window::{
+        Fullscreen, Icon as WinitIcon, UserAttentionType as WinitUserAttentionType, Window,
+        WindowAttributes, WindowBuilder,
+    },
+};
+#[cfg(target_os = "macos")]
+use {
+    cocoa::{
+        appkit::{NSApp, NSPasteboardTypeString, NSWindowButton, NSWindowLevel, NSWindowStyleMask},
+        base::{id, nil, BOOL, NO, YES},
+        foundation::{NSArray, NSInteger, NSString, NSUInteger},
+    },
+    std::ffi::CStr,
 };
 
-use std::{collections::HashMap, sync::mpsc::channel};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    rc::Rc,
+    sync::mpsc::channel,
+    thread::{self, ThreadId},
+};
 
 #[cfg(target_os = "windows")]
 use {
+    crate::{ProgressBarState, RoundedRect},
     libc::c_void,
-    std::ptr,
+    std::{
+        ffi::{OsStr, OsString},
+        iter::once,
+        mem,
+        os::windows::ffi::{OsStrExt, OsStringExt},
+        ptr, slice,
+    },
     winapi::{
-        shared::windef::HWND,
+        shared::{
+            minwindef::{DWORD, FALSE, UINT},
+            windef::HWND,
+        },
         um::{
             combaseapi::{CoCreateInstance, CLSCTX_SERVER},
-            shobjidl_core::{CLSID_TaskbarList, ITaskbarList},
+            commdlg::{
+                GetOpenFileNameW, GetSaveFileNameW, OFN_ALLOWMULTISELECT, OFN_EXPLORER,
+                OFN_FILEMUSTEXIST, OFN_NOCHANGEDIR, OFN_OVERWRITEPROMPT, OPENFILENAMEW,
+            },
+            dwmapi::{
+                DwmSetWindowAttribute, DWMNCRP_DISABLED, DWMNCRP_ENABLED, DWMWA_NCRENDERING_POLICY,
+            },
+            shobjidl_core::{
+                CLSID_TaskbarList, ITaskbarList, ITaskbarList3, TBPF_ERROR, TBPF_INDETERMINATE,
+                TBPF_NOPROGRESS, TBPF_NORMAL, TBPF_PAUSED,
+            },
+            winbase::{GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
+            wingdi::{CombineRgn, CreateRectRgn, CreateRoundRectRgn, DeleteObject, RGN_OR},
+            winuser::{
+                CloseClipboard, CreateIcon, DestroyIcon, EmptyClipboard, EnableMenuItem,
+                GetClipboardData, GetSystemMenu, GetWindowLongW, IsWindowVisible, MessageBoxW,
+                OpenClipboard, SetClipboardData, SetForegroundWindow, SetLayeredWindowAttributes,
+                SetWindowLongW, SetWindowPos, SetWindowRgn, CF_UNICODETEXT, GWL_EXSTYLE, GWL_STYLE,
+                HWND_BOTTOM, IDNO, IDOK, IDYES, LWA_ALPHA, MB_OK, MB_OKCANCEL, MB_YESNO,
+                MB_YESNOCANCEL, MF_BYCOMMAND, MF_ENABLED, MF_GRAYED, SC_CLOSE, SWP_FRAMECHANGED,
+                SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, WS_EX_LAYERED,
+                WS_MAXIMIZEBOX, WS_MINIMIZEBOX,
+            },
         },
         DEFINE_GUID,
     },
@@ -35,6 +93,8 @@ type EventLoopProxy = winit::event_loop::EventLoopProxy<Message>;
 #[derive(Clone)]
 pub struct InnerApplicationProxy {
     proxy: EventLoopProxy,
+    event_receiver: EventReceiver,
+    main_thread: ThreadId,
 }
 
 impl AppProxy for InnerApplicationProxy {
@@ -45,18 +105,32 @@ impl AppProxy for InnerApplicationProxy {
         Ok(())
     }
 
+    fn listen_event(&self) -> Result<WryEvent> {
+        self.event_receiver.recv()
+    }
+
+    fn is_main_thread(&self) -> bool {
+        thread::current().id() == self.main_thread
+    }
+
+    fn run_on_main(&self, f: Box<dyn FnOnce() + Send>) -> Result<()> {
+        self.send_message(Message::RunOnMain(f))
+    }
+
     fn add_window(
         &self,
         attributes: Attributes,
         rpc_handler: Option<WindowRpcHandler>,
-        custom_protocol: Option<CustomProtocol>,
+        close_requested_handler: Option<WindowCloseRequestedHandler>,
+        custom_protocols: Vec<CustomProtocol>,
     ) -> Result<WindowId> {
         let (sender, receiver) = channel();
         self.send_message(Message::NewWindow(
             attributes,
             sender,
             rpc_handler,
-            custom_protocol,
+            close_requested_handler,
+            custom_protocols,
         ))?;
         Ok(receiver.recv()?)
     }
@@ -78,12 +152,6 @@ impl From<&InnerWindowAttributes> for WindowAttributes {
             _ => None,
         };
 
-        let fullscreen = if w.fullscreen {
-            Some(Fullscreen::Borderless(None))
-        } else {
-            None
-        };
-
         Self {
             resizable: w.resizable,
             title: w.title.clone(),
@@ -95,7 +163,6 @@ impl From<&InnerWindowAttributes> for WindowAttributes {
             inner_size: Some(LogicalSize::new(w.width, w.height).into()),
             min_inner_size,
             max_inner_size,
-            fullscreen,
             ..Default::default()
         }
     }
@@ -105,19 +172,28 @@ pub struct InnerApplication {
     webviews: HashMap<WindowId, WebView>,
     event_loop: EventLoop<Message>,
     event_loop_proxy: EventLoopProxy,
+    event_channel: EventSender,
+    event_receiver: EventReceiver,
+    attributes: ApplicationAttributes,
+    close_requested_handlers: HashMap<WindowId, WindowCloseRequestedHandler>,
 }
 
 impl App for InnerApplication {
     type Id = WindowId;
     type Proxy = InnerApplicationProxy;
 
-    fn new() -> Result<Self> {
+    fn new(_application_id: Option<&str>) -> Result<Self> {
         let event_loop = EventLoop::<Message>::with_user_event();
         let proxy = event_loop.create_proxy();
+        let (event_channel, event_receiver) = event_queue();
         Ok(Self {
             webviews: HashMap::new(),
             event_loop,
             event_loop_proxy: proxy,
+            event_channel,
+            event_receiver,
+            attributes: ApplicationAttributes::default(),
+            close_requested_handlers: HashMap::new(),
         })
     }
 
@@ -125,7 +201,8 @@ impl App for InnerApplication {
         &mut self,
         attributes: Attributes,
         rpc_handler: Option<WindowRpcHandler>,
-        custom_protocol: Option<CustomProtocol>,
+        close_requested_handler: Option<WindowCloseRequestedHandler>,
+        custom_protocols: Vec<CustomProtocol>,
     ) -> Result<Self::Id> {
         let (window_attrs, webview_attrs) = attributes.split();
         let window = _create_window(&self.event_loop, window_attrs)?;
@@ -133,24 +210,99 @@ impl App for InnerApplication {
             self.application_proxy(),
             window,
             webview_attrs,
-            custom_protocol,
+            &self.attributes,
+            custom_protocols,
             rpc_handler,
+            self.event_channel.clone(),
         )?;
         let id = webview.window().id();
         self.webviews.insert(id, webview);
+        if let Some(handler) = close_requested_handler {
+            self.close_requested_handlers.insert(id, handler);
+        }
         Ok(id)
     }
 
+    fn add_initialization_script(&mut self, js: &str) {
+        self.attributes.initialization_scripts.push(js.to_string());
+    }
+
+    fn set_before_unload_handler(&mut self, handler: WindowBeforeUnloadHandler) {
+        self.attributes.before_unload_handler = Some(handler);
+    }
+
+    fn set_script_dialog_handler(&mut self, handler: WindowScriptDialogHandler) {
+        self.attributes.script_dialog_handler = Some(handler);
+    }
+
+    fn set_paste_handler(&mut self, handler: WindowPasteHandler) {
+        self.attributes.paste_handler = Some(handler);
+    }
+
+    fn set_context_menu_handler(&mut self, handler: WindowContextMenuHandler) {
+        self.attributes.context_menu_handler = Some(handler);
+    }
+
+    fn set_rpc_serializer(&mut self, serializer: Rc<dyn RpcSerializer>) {
+        self.attributes.rpc_serializer = Some(serializer);
+    }
+
+    fn set_web_process_limit(&mut self, _limit: u32) {}
+
+    fn set_memory_pressure_settings(&mut self, _settings: MemoryPressureSettings) {}
+
+    fn set_im_module(&mut self, _module: &str) {}
+
+    fn open_urls(&self, urls: &[String]) -> bool {
+        for url in urls {
+            let _ = self.event_channel.send(WryEvent::OpenUrl(url.clone()));
+        }
+        false
+    }
+
     fn application_proxy(&self) -> Self::Proxy {
         InnerApplicationProxy {
             proxy: self.event_loop_proxy.clone(),
+            event_receiver: self.event_receiver.clone(),
+            // The application can't leave the thread it was created on.
+            main_thread: thread::current().id(),
         }
     }
 
-    fn run(self) {
+    fn run(mut self) {
+        self.run_return()
+    }
+
+    fn run_return(&mut self) {
         let proxy = self.application_proxy();
-        let mut windows = self.webviews;
-        self.event_loop.run(move |event, event_loop, control_flow| {
+        let Self {
+            webviews: windows,
+            event_loop,
+            event_channel,
+            attributes: app_attributes,
+            close_requested_handlers,
+            ..
+        } = self;
+        // winit only reports the new scale factor, so keep track of the previous ones.
+        let mut scale_factors: HashMap<WindowId, f64> = windows
+            .iter()
+            .map(|(id, w)| (*id, w.window().scale_factor()))
+            .collect();
+        let mut monitors: HashMap<WindowId, usize> = windows
+            .iter()
+            .filter_map(|(id, w)| Some((*id, monitor_index(w.window())?)))
+            .collect();
+        // winit has no event for fullscreen changes, but they always resize the window.
+        let mut fullscreen_windows: HashSet<WindowId> = windows
+            .iter()
+            .filter(|(_, w)| w.window().fullscreen().is_some())
+            .map(|(id, _)| *id)
+            .collect();
+        // Geometry changes are reported once the pending events are processed, so the bursts
+        // of them while a window is dragged or resized are coalesced.
+        let mut resized: HashMap<WindowId, (u32, u32)> = HashMap::new();
+        let mut moved: HashMap<WindowId, (i32, i32)> = HashMap::new();
+        event_loop.run_return(|event, event_loop, control_flow| {
             *control_flow = ControlFlow::Wait;
 
             for (_, w) in windows.iter() {
@@ -158,126 +310,580 @@ impl App for InnerApplication {
             }
             match event {
                 Event::WindowEvent { event, window_id } => match event {
+                    // The window is kept open when the handler vetoes it.
+                    WindowEvent::CloseRequested
+                        if close_requested_handlers
+                            .get(&window_id)
+                            .map_or(false, |handler| {
+                                handler(WindowProxy::new(
+                                    ApplicationProxy {
+                                        inner: proxy.clone(),
+                                    },
+                                    window_id,
+                                ))
+                            }) => {}
                     WindowEvent::CloseRequested => {
+                        let _ = event_channel.send(WryEvent::WindowEvent {
+                            window_id,
+                            event: crate::WindowEvent::CloseRequested,
+                        });
                         windows.remove(&window_id);
+                        close_requested_handlers.remove(&window_id);
+                        scale_factors.remove(&window_id);
+                        monitors.remove(&window_id);
+                        fullscreen_windows.remove(&window_id);
+                        resized.remove(&window_id);
+                        moved.remove(&window_id);
 
                         if windows.is_empty() {
                             *control_flow = ControlFlow::Exit;
                         }
                     }
-                    WindowEvent::Resized(_) => {
-                        windows[&window_id].resize().unwrap();
+                    WindowEvent::Focused(focused) => {
+                        let _ = event_channel.send(WryEvent::WindowEvent {
+                            window_id,
+                            event: crate::WindowEvent::Focused(focused),
+                        });
+                    }
+                    WindowEvent::CursorEntered { .. } => {
+                        let _ = event_channel.send(WryEvent::WindowEvent {
+                            window_id,
+                            event: crate::WindowEvent::CursorEntered,
+                        });
+                    }
+                    WindowEvent::CursorLeft { .. } => {
+                        let _ = event_channel.send(WryEvent::WindowEvent {
+                            window_id,
+                            event: crate::WindowEvent::CursorLeft,
+                        });
+                    }
+                    WindowEvent::Resized(size) => {
+                        let webview = &windows[&window_id];
+                        webview.resize().unwrap();
+                        let size = size.to_logical::<u32>(webview.window().scale_factor());
+                        resized.insert(window_id, (size.width, size.height));
+                        track_monitor(&mut monitors, webview.window(), &event_channel);
+
+                        let fullscreen = webview.window().fullscreen().is_some();
+                        let changed = if fullscreen {
+                            fullscreen_windows.insert(window_id)
+                        } else {
+                            fullscreen_windows.remove(&window_id)
+                        };
+                        if changed {
+                            let _ = event_channel.send(WryEvent::WindowEvent {
+                                window_id,
+                                event: crate::WindowEvent::Fullscreen(fullscreen),
+                            });
+                        }
+                    }
+                    WindowEvent::Moved(position) => {
+                        if let Some(webview) = windows.get(&window_id) {
+                            track_monitor(&mut monitors, webview.window(), &event_channel);
+                            let position =
+                                position.to_logical::<i32>(webview.window().scale_factor());
+                            moved.insert(window_id, (position.x, position.y));
+                        }
+                    }
+                    WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                        let old = scale_factors
+                            .insert(window_id, scale_factor)
+                            .unwrap_or(scale_factor);
+                        if old != scale_factor {
+                            let _ = event_channel.send(WryEvent::WindowEvent {
+                                window_id,
+                                event: crate::WindowEvent::DpiChanged {
+                                    old,
+                                    new: scale_factor,
+                                },
+                            });
+                        }
                     }
                     _ => {}
                 },
-                Event::UserEvent(message) => match message {
-                    Message::NewWindow(attributes, sender, rpc_handler, custom_protocol) => {
-                        let (window_attrs, webview_attrs) = attributes.split();
-                        let window = _create_window(&event_loop, window_attrs).unwrap();
-                        sender.send(window.id()).unwrap();
-                        let webview = _create_webview(
-                            proxy.clone(),
-                            window,
-                            webview_attrs,
-                            custom_protocol,
-                            rpc_handler,
-                        )
-                        .unwrap();
-                        let id = webview.window().id();
-                        windows.insert(id, webview);
-                    }
-                    Message::Window(id, window_message) => {
-                        if let Some(webview) = windows.get_mut(&id) {
-                            let window = webview.window();
-                            match window_message {
-                                WindowMessage::SetResizable(resizable) => {
-                                    window.set_resizable(resizable)
-                                }
-                                WindowMessage::SetTitle(title) => window.set_title(&title),
-                                WindowMessage::Maximize => window.set_maximized(true),
-                                WindowMessage::Unmaximize => window.set_maximized(false),
-                                WindowMessage::Minimize => window.set_minimized(true),
-                                WindowMessage::Unminimize => window.set_minimized(false),
-                                WindowMessage::Show => window.set_visible(true),
-                                WindowMessage::Hide => window.set_visible(false),
-                                WindowMessage::Close => {
-                                    windows.remove(&id);
+                Event::UserEvent(message) => {
+                    for message in message.flatten() {
+                        match message {
+                            Message::NewWindow(
+                                attributes,
+                                sender,
+                                rpc_handler,
+                                close_requested_handler,
+                                custom_protocols,
+                            ) => {
+                                let (window_attrs, webview_attrs) = attributes.split();
+                                let window = _create_window(&event_loop, window_attrs).unwrap();
+                                sender.send(window.id()).unwrap();
+                                let webview = _create_webview(
+                                    proxy.clone(),
+                                    window,
+                                    webview_attrs,
+                                    app_attributes,
+                                    custom_protocols,
+                                    rpc_handler,
+                                    event_channel.clone(),
+                                )
+                                .unwrap();
+                                let id = webview.window().id();
+                                scale_factors.insert(id, webview.window().scale_factor());
+                                if let Some(monitor) = monitor_index(webview.window()) {
+                                    monitors.insert(id, monitor);
                                 }
-                                WindowMessage::SetDecorations(decorations) => {
-                                    window.set_decorations(decorations)
+                                if webview.window().fullscreen().is_some() {
+                                    fullscreen_windows.insert(id);
                                 }
-                                WindowMessage::SetAlwaysOnTop(always_on_top) => {
-                                    window.set_always_on_top(always_on_top)
+                                if let Some(handler) = close_requested_handler {
+                                    close_requested_handlers.insert(id, handler);
                                 }
-                                WindowMessage::SetWidth(width) => {
-                                    let mut size =
-                                        window.inner_size().to_logical(window.scale_factor());
-                                    size.width = width;
-                                    window.set_inner_size(size);
-                                }
-                                WindowMessage::SetHeight(height) => {
-                                    let mut size =
-                                        window.inner_size().to_logical(window.scale_factor());
-                                    size.height = height;
-                                    window.set_inner_size(size);
-                                }
-                                WindowMessage::Resize { width, height } => {
-                                    window.set_inner_size(LogicalSize::new(width, height));
-                                }
-                                WindowMessage::SetMinSize {
-                                    min_width,
-                                    min_height,
-                                } => {
-                                    window.set_min_inner_size(Some(LogicalSize::new(
-                                        min_width, min_height,
-                                    )));
-                                }
-                                WindowMessage::SetMaxSize {
-                                    max_width,
-                                    max_height,
-                                } => {
-                                    window.set_max_inner_size(Some(LogicalSize::new(
-                                        max_width, max_height,
-                                    )));
-                                }
-                                WindowMessage::SetX(x) => {
-                                    if let Ok(outer_position) = window.outer_position() {
-                                        let mut outer_position =
-                                            outer_position.to_logical(window.scale_factor());
-                                        outer_position.x = x;
-                                        window.set_outer_position(outer_position);
-                                    }
-                                }
-                                WindowMessage::SetY(y) => {
-                                    if let Ok(outer_position) = window.outer_position() {
-                                        let mut outer_position =
-                                            outer_position.to_logical(window.scale_factor());
-                                        outer_position.y = y;
-                                        window.set_outer_position(outer_position);
-                                    }
-                                }
-                                WindowMessage::SetPosition { x, y } => {
-                                    window.set_outer_position(LogicalPosition::new(x, y))
-                                }
-                                WindowMessage::SetFullscreen(fullscreen) => {
-                                    if fullscreen {
-                                        window.set_fullscreen(Some(Fullscreen::Borderless(None)))
-                                    } else {
-                                        window.set_fullscreen(None)
-                                    }
+                                windows.insert(id, webview);
+                            }
+                            Message::AddInitializationScript(js) => {
+                                app_attributes.initialization_scripts.push(js);
+                            }
+                            Message::RunOnMain(f) => f(),
+                            Message::Batch(_) => unreachable!("batches are flattened"),
+                            Message::CloseWindows { except } => {
+                                windows.retain(|id, _| Some(*id) == except);
+                                close_requested_handlers.retain(|id, _| windows.contains_key(id));
+                                scale_factors.retain(|id, _| windows.contains_key(id));
+                                monitors.retain(|id, _| windows.contains_key(id));
+                                fullscreen_windows.retain(|id| windows.contains_key(id));
+                                resized.retain(|id, _| windows.contains_key(id));
+                                moved.retain(|id, _| windows.contains_key(id));
+
+                                if windows.is_empty() {
+                                    *control_flow = ControlFlow::Exit;
                                 }
-                                WindowMessage::SetIcon(icon) => {
-                                    if let Ok(icon) = load_icon(icon) {
-                                        window.set_window_icon(Some(icon));
+                            }
+                            Message::Window(id, window_message) => {
+                                if let Some(webview) = windows.get_mut(&id) {
+                                    let window = webview.window();
+                                    match window_message {
+                                        WindowMessage::SetResizable(resizable) => {
+                                            window.set_resizable(resizable)
+                                        }
+                                        WindowMessage::SetMinimizable(minimizable) => {
+                                            set_minimizable(window, minimizable)
+                                        }
+                                        WindowMessage::SetMaximizable(maximizable) => {
+                                            set_maximizable(window, maximizable)
+                                        }
+                                        WindowMessage::SetClosable(closable) => {
+                                            set_closable(window, closable)
+                                        }
+                                        WindowMessage::SetTitle(title) => window.set_title(&title),
+                                        WindowMessage::Maximize => window.set_maximized(true),
+                                        WindowMessage::Unmaximize => window.set_maximized(false),
+                                        WindowMessage::Minimize => window.set_minimized(true),
+                                        WindowMessage::Unminimize => window.set_minimized(false),
+                                        WindowMessage::Show => window.set_visible(true),
+                                        WindowMessage::Hide => window.set_visible(false),
+                                        WindowMessage::IsVisible(responder) => {
+                                            let _ = responder.send(WindowResponse::IsVisible(
+                                                is_visible(window),
+                                            ));
+                                        }
+                                        WindowMessage::RequestUserAttention(request) => window
+                                            .request_user_attention(request.map(|request| {
+                                                match request {
+                                                    UserAttentionType::Critical => {
+                                                        WinitUserAttentionType::Critical
+                                                    }
+                                                    UserAttentionType::Informational => {
+                                                        WinitUserAttentionType::Informational
+                                                    }
+                                                }
+                                            })),
+                                        WindowMessage::Focus => {
+                                            window.set_minimized(false);
+                                            focus_window(window);
+                                            let _ = webview.focus();
+                                        }
+                                        WindowMessage::Close => {
+                                            windows.remove(&id);
+                                            close_requested_handlers.remove(&id);
+                                        }
+                                        WindowMessage::SetDecorations(decorations) => {
+                                            window.set_decorations(decorations)
+                                        }
+                                        WindowMessage::SetOpacity(opacity) => {
+                                            set_opacity(window, opacity);
+                                        }
+                                        WindowMessage::SetShadow(shadow) => {
+                                            set_shadow(window, shadow);
+                                        }
+                                        WindowMessage::SetAlwaysOnTop(always_on_top) => {
+                                            window.set_always_on_top(always_on_top)
+                                        }
+                                        WindowMessage::SetAlwaysOnBottom(always_on_bottom) => {
+                                            set_always_on_bottom(window, always_on_bottom)
+                                        }
+                                        WindowMessage::SetAlwaysOnTopYieldsToFullscreen(_) => {}
+                                        WindowMessage::SetWidth(width) => {
+                                            let mut size = window
+                                                .inner_size()
+                                                .to_logical(window.scale_factor());
+                                            size.width = width;
+                                            window.set_inner_size(size);
+                                        }
+                                        WindowMessage::SetHeight(height) => {
+                                            let mut size = window
+                                                .inner_size()
+                                                .to_logical(window.scale_factor());
+                                            size.height = height;
+                                            window.set_inner_size(size);
+                                        }
+                                        WindowMessage::Resize { width, height } => {
+                                            window.set_inner_size(LogicalSize::new(width, height));
+                                        }
+                                        WindowMessage::SetMinSize {
+                                            min_width,
+                                            min_height,
+                                        } => {
+                                            window.set_min_inner_size(Some(LogicalSize::new(
+                                                min_width, min_height,
+                                            )));
+                                        }
+                                        WindowMessage::SetMinSizeToContent => {
+                                            let proxy = proxy.clone();
+                                            let _ = webview.evaluate_script_with_callback(
+                                                MIN_CONTENT_SIZE_SCRIPT,
+                                                move |size| {
+                                                    let size = size.and_then(|size| {
+                                                        Ok(serde_json::from_value(size)?)
+                                                    });
+                                                    if let Ok((min_width, min_height)) = size {
+                                                        let _ =
+                                                            proxy.send_message(Message::Window(
+                                                                id,
+                                                                WindowMessage::SetMinSize {
+                                                                    min_width,
+                                                                    min_height,
+                                                                },
+                                                            ));
+                                                    }
+                                                },
+                                            );
+                                        }
+                                        WindowMessage::SetMaxSize {
+                                            max_width,
+                                            max_height,
+                                        } => {
+                                            window.set_max_inner_size(Some(LogicalSize::new(
+                                                max_width, max_height,
+                                            )));
+                                        }
+                                        WindowMessage::SetX(x) => {
+                                            if let Ok(outer_position) = window.outer_position() {
+                                                let mut outer_position = outer_position
+                                                    .to_logical(window.scale_factor());
+                                                outer_position.x = x;
+                                                window.set_outer_position(outer_position);
+                                            }
+                                        }
+                                        WindowMessage::SetY(y) => {
+                                            if let Ok(outer_position) = window.outer_position() {
+                                                let mut outer_position = outer_position
+                                                    .to_logical(window.scale_factor());
+                                                outer_position.y = y;
+                                                window.set_outer_position(outer_position);
+                                            }
+                                        }
+                                        WindowMessage::SetPosition { x, y } => {
+                                            window.set_outer_position(LogicalPosition::new(x, y))
+                                        }
+                                        WindowMessage::Snap(region) => snap(window, region),
+                                        WindowMessage::Center => {
+                                            if let Some(monitor) = window.current_monitor() {
+                                                let position = monitor.position();
+                                                let size = monitor.size();
+                                                let outer_size = window.outer_size();
+                                                window.set_outer_position(PhysicalPosition::new(
+                                                    position.x
+                                                        + (size.width as i32
+                                                            - outer_size.width as i32)
+                                                            / 2,
+                                                    position.y
+                                                        + (size.height as i32
+                                                            - outer_size.height as i32)
+                                                            / 2,
+                                                ));
+                                            }
+                                        }
+                                        WindowMessage::SetFullscreen(fullscreen) => {
+                                            if fullscreen {
+                                                window.set_fullscreen(Some(Fullscreen::Borderless(
+                                                    None,
+                                                )))
+                                            } else {
+                                                window.set_fullscreen(None)
+                                            }
+                                        }
+                                        WindowMessage::ToggleFullscreen => {
+                                            if window.fullscreen().is_some() {
+                                                window.set_fullscreen(None)
+                                            } else {
+                                                window.set_fullscreen(Some(Fullscreen::Borderless(
+                                                    None,
+                                                )))
+                                            }
+                                        }
+                                        WindowMessage::IsFullscreen(responder) => {
+                                            let _ = responder.send(WindowResponse::IsFullscreen(
+                                                window.fullscreen().is_some(),
+                                            ));
+                                        }
+                                        WindowMessage::SetIcon(icon) => {
+                                            if let Ok(icon) = load_icon(icon) {
+                                                window.set_window_icon(Some(icon));
+                                            }
+                                        }
+                                        WindowMessage::SetCursorImage {
+                                            rgba,
+                                            width,
+                                            height,
+                                            hotspot_x,
+                                            hotspot_y,
+                                        } => {
+                                            let _ = webview.set_cursor_image(
+                                                rgba, width, height, hotspot_x, hotspot_y,
+                                            );
+                                        }
+                                        WindowMessage::ResetCursor => {
+                                            let _ = webview.reset_cursor();
+                                        }
+                                        WindowMessage::EvaluationScript(script) => {
+                                            let _ = webview.dispatch_script(&script);
+                                        }
+                                        WindowMessage::EvaluationScriptWithCallback(
+                                            script,
+                                            callback,
+                                        ) => {
+                                            // Keep the order with the scripts dispatched before.
+                                            let _ = webview.evaluate_script();
+                                            let _ = webview
+                                                .evaluate_script_with_callback(&script, callback.0);
+                                        }
+                                        WindowMessage::LoadUrl(url) => {
+                                            let _ = webview.load_url(&url);
+                                        }
+                                        WindowMessage::Reload { bypass_cache } => {
+                                            let _ = webview.reload(bypass_cache);
+                                        }
+                                        WindowMessage::SetUserAgent(user_agent) => {
+                                            let _ = webview.set_user_agent(&user_agent);
+                                        }
+                                        WindowMessage::ReloadWithUserAgent {
+                                            user_agent,
+                                            restore,
+                                        } => {
+                                            let _ = webview
+                                                .reload_with_user_agent(&user_agent, restore);
+                                        }
+                                        WindowMessage::SetZoomLevel(zoom) => {
+                                            let _ = webview.set_zoom_level(zoom);
+                                        }
+                                        WindowMessage::ZoomLevel(responder) => {
+                                            let _ = responder.send(WindowResponse::ZoomLevel(
+                                                webview.zoom_level(),
+                                            ));
+                                        }
+                                        WindowMessage::GoBack => {
+                                            let _ = webview.go_back();
+                                        }
+                                        WindowMessage::GoForward => {
+                                            let _ = webview.go_forward();
+                                        }
+                                        WindowMessage::CanGoBack(responder) => {
+                                            let _ = responder.send(WindowResponse::CanGoBack(
+                                                webview.can_go_back(),
+                                            ));
+                                        }
+                                        WindowMessage::CanGoForward(responder) => {
+                                            let _ = responder.send(WindowResponse::CanGoForward(
+                                                webview.can_go_forward(),
+                                            ));
+                                        }
+                                        WindowMessage::Url(responder) => {
+                                            let _ =
+                                                responder.send(WindowResponse::Url(webview.url()));
+                                        }
+                                        WindowMessage::Title(responder) => {
+                                            let _ = responder
+                                                .send(WindowResponse::Title(webview.title()));
+                                        }
+                                        WindowMessage::LoadBytes {
+                                            bytes,
+                                            mime_type,
+                                            base_url,
+                                        } => {
+                                            let _ = webview.load_bytes(
+                                                &bytes,
+                                                &mime_type,
+                                                base_url.as_deref(),
+                                            );
+                                        }
+                                        WindowMessage::LoadHtml { html, base_url } => {
+                                            let _ = webview.load_html(&html, base_url.as_deref());
+                                        }
+                                        WindowMessage::WaitForLoad(responder) => {
+                                            webview.on_next_load(move || {
+                                                let _ =
+                                                    responder.send(WindowResponse::LoadFinished);
+                                            });
+                                        }
+                                        WindowMessage::SetEditable(editable) => {
+                                            let _ = webview.set_editable(editable);
+                                        }
+                                        WindowMessage::SetEnableDragRegion(enabled) => {
+                                            let _ = webview.set_enable_drag_region(enabled);
+                                        }
+                                        WindowMessage::Find {
+                                            text,
+                                            case_sensitive,
+                                        } => {
+                                            let _ = webview.find(&text, case_sensitive);
+                                        }
+                                        WindowMessage::FindNext => {
+                                            let _ = webview.find_next();
+                                        }
+                                        WindowMessage::FindPrevious => {
+                                            let _ = webview.find_previous();
+                                        }
+                                        WindowMessage::StopFind => {
+                                            let _ = webview.stop_find();
+                                        }
+                                        WindowMessage::SetSelectable(selectable) => {
+                                            let _ = webview.set_selectable(selectable);
+                                        }
+                                        WindowMessage::ShowMessageDialog {
+                                            title,
+                                            message,
+                                            buttons,
+                                            responder,
+                                        } => {
+                                            let _ = responder.send(WindowResponse::MessageDialog(
+                                                show_message_dialog(
+                                                    window, &title, &message, buttons,
+                                                ),
+                                            ));
+                                        }
+                                        WindowMessage::OpenFileDialog { options, responder } => {
+                                            let _ = responder.send(WindowResponse::OpenFileDialog(
+                                                open_file_dialog(window, &options),
+                                            ));
+                                        }
+                                        WindowMessage::SaveFileDialog { options, responder } => {
+                                            let _ = responder.send(WindowResponse::SaveFileDialog(
+                                                save_file_dialog(window, &options),
+                                            ));
+                                        }
+                                        WindowMessage::BackForwardList(responder) => {
+                                            let _ =
+                                                responder.send(WindowResponse::BackForwardList(
+                                                    webview.back_forward_list(),
+                                                ));
+                                        }
+                                        WindowMessage::ClearHistory => {
+                                            let _ = webview.clear_history();
+                                        }
+                                        WindowMessage::SessionState(responder) => {
+                                            let _ = responder.send(WindowResponse::SessionState(
+                                                webview.session_state(),
+                                            ));
+                                        }
+                                        WindowMessage::RestoreSessionState(state) => {
+                                            let _ = webview.restore_session_state(&state);
+                                        }
+                                        WindowMessage::Cookies { url, responder } => {
+                                            let _ = webview.cookies(&url, move |cookies| {
+                                                let _ = responder
+                                                    .send(WindowResponse::Cookies(cookies));
+                                            });
+                                        }
+                                        WindowMessage::SetCookie(cookie) => {
+                                            let _ = webview.set_cookie(&cookie);
+                                        }
+                                        WindowMessage::ClearAllCookies => {
+                                            let _ = webview.clear_all_cookies();
+                                        }
+                                        WindowMessage::ClearCache => {
+                                            let _ = webview.clear_cache();
+                                        }
+                                        WindowMessage::ClearStorage(types) => {
+                                            let _ = webview.clear_storage(types);
+                                        }
+                                        WindowMessage::IsDevtoolsOpen(responder) => {
+                                            let _ = responder.send(WindowResponse::IsDevtoolsOpen(
+                                                webview.is_devtools_open(),
+                                            ));
+                                        }
+                                        WindowMessage::OpenDevtools(attachment) => {
+                                            let _ = match attachment {
+                                                Some(attachment) => {
+                                                    webview.open_devtools_with(attachment)
+                                                }
+                                                None => webview.open_devtools(),
+                                            };
+                                        }
+                                        WindowMessage::DevtoolsAttachedHeight(responder) => {
+                                            let _ = responder.send(
+                                                WindowResponse::DevtoolsAttachedHeight(
+                                                    webview.devtools_attached_height(),
+                                                ),
+                                            );
+                                        }
+                                        WindowMessage::CloseDevtools => {
+                                            let _ = webview.close_devtools();
+                                        }
+                                        #[cfg(target_os = "windows")]
+                                        WindowMessage::SetProgressBar(progress, state) => {
+                                            set_progress_bar(window, progress, state);
+                                        }
+                                        #[cfg(target_os = "macos")]
+                                        WindowMessage::SetProgressBar(..) => {}
+                                        WindowMessage::SetWindowButtonsPosition(_) => {}
+                                        #[cfg(target_os = "windows")]
+                                        WindowMessage::SetTaskbarIcon(icon) => {
+                                            let _ = set_taskbar_icon(window, icon);
+                                        }
+                                        // Rejected by `WindowProxy::set_taskbar_icon`.
+                                        #[cfg(target_os = "macos")]
+                                        WindowMessage::SetTaskbarIcon(_) => {}
+                                        WindowMessage::SetClipboardText(text) => {
+                                            set_clipboard_text(&text)
+                                        }
+                                        WindowMessage::ClipboardText(responder) => {
+                                            let _ = responder.send(WindowResponse::ClipboardText(
+                                                clipboard_text(),
+                                            ));
+                                        }
+                                        #[cfg(target_os = "windows")]
+                                        WindowMessage::SetOpaqueRegion(region) => {
+                                            set_opaque_region(window, region);
+                                        }
+                                        #[cfg(target_os = "macos")]
+                                        WindowMessage::SetOpaqueRegion(_) => {}
                                     }
                                 }
-                                WindowMessage::EvaluationScript(script) => {
-                                    let _ = webview.dispatch_script(&script);
-                                }
                             }
                         }
                     }
-                },
+                }
+                Event::MainEventsCleared => {
+                    for (window_id, (width, height)) in resized.drain() {
+                        let _ = event_channel.send(WryEvent::WindowEvent {
+                            window_id,
+                            event: crate::WindowEvent::Resized { width, height },
+                        });
+                    }
+                    for (window_id, (x, y)) in moved.drain() {
+                        let _ = event_channel.send(WryEvent::WindowEvent {
+                            window_id,
+                            event: crate::WindowEvent::Moved { x, y },
+                        });
+                    }
+                }
                 _ => (),
             }
         });
@@ -310,16 +916,764 @@ fn skip_taskbar(_window: &Window) {
     }
 }
 
+#[cfg(target_os = "windows")]
+fn set_opaque_region(window: &Window, region: Option<Vec<RoundedRect>>) {
+    let scale = window.scale_factor();
+    // Safety: System calls are unsafe
+    unsafe {
+        let rgn = match region {
+            Some(region) => {
+                let rgn = CreateRectRgn(0, 0, 0, 0);
+                for rect in region {
+                    let diameter = (rect.radius * 2. * scale) as i32;
+                    let part = CreateRoundRectRgn(
+                        (rect.x * scale) as i32,
+                        (rect.y * scale) as i32,
+                        ((rect.x + rect.width) * scale) as i32 + 1,
+                        ((rect.y + rect.height) * scale) as i32 + 1,
+                        diameter,
+                        diameter,
+                    );
+                    CombineRgn(rgn, rgn, part, RGN_OR);
+                    DeleteObject(part as _);
+                }
+                rgn
+            }
+            None => ptr::null_mut(),
+        };
+        // The system owns the region from now on.
+        SetWindowRgn(window.hwnd() as HWND, rgn, 1);
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn set_progress_bar(window: &Window, progress: f64, state: ProgressBarState) {
+    unsafe {
+        let mut taskbar_list: *mut ITaskbarList3 = std::mem::zeroed();
+        DEFINE_GUID! {IID_ITASKBAR_LIST3,
+        0xea1afb91, 0x9e28, 0x4b86, 0x90, 0xe9, 0x9e, 0x9f, 0x8a, 0x5e, 0xef, 0xaf}
+        CoCreateInstance(
+            &CLSID_TaskbarList,
+            ptr::null_mut(),
+            CLSCTX_SERVER,
+            &IID_ITASKBAR_LIST3,
+            &mut taskbar_list as *mut *mut ITaskbarList3 as *mut *mut c_void,
+        );
+        let hwnd = window.hwnd() as HWND;
+        let flags = match state {
+            ProgressBarState::None => TBPF_NOPROGRESS,
+            ProgressBarState::Normal => TBPF_NORMAL,
+            ProgressBarState::Indeterminate => TBPF_INDETERMINATE,
+            ProgressBarState::Error => TBPF_ERROR,
+            ProgressBarState::Paused => TBPF_PAUSED,
+        };
+        // Setting a value switches an indeterminate progress bar back to normal
+        if state != ProgressBarState::Indeterminate {
+            let completed = (progress.max(0.).min(1.) * 100.) as u64;
+            (*taskbar_list).SetProgressValue(hwnd, completed, 100);
+        }
+        (*taskbar_list).SetProgressState(hwnd, flags);
+        (*taskbar_list).Release();
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn set_taskbar_icon(window: &Window, icon: Option<Icon>) -> crate::Result<()> {
+    let icon = match icon {
+        Some(icon) => {
+            let image = image::load_from_memory(&icon.0)?.into_bgra8();
+            let (width, height) = image.dimensions();
+            // Every pixel is drawn, the alpha channel of the color bits handles transparency.
+            let and_mask = vec![0u8; image.len() / 4];
+            // Safety: System calls are unsafe
+            unsafe {
+                CreateIcon(
+                    ptr::null_mut(),
+                    width as i32,
+                    height as i32,
+                    1,
+                    32,
+                    and_mask.as_ptr(),
+                    image.as_ptr(),
+                )
+            }
+        }
+        None => ptr::null_mut(),
+    };
+    unsafe {
+        let mut taskbar_list: *mut ITaskbarList3 = std::mem::zeroed();
+        DEFINE_GUID! {IID_ITASKBAR_LIST3,
+        0xea1afb91, 0x9e28, 0x4b86, 0x90, 0xe9, 0x9e, 0x9f, 0x8a, 0x5e, 0xef, 0xaf}
+        CoCreateInstance(
+            &CLSID_TaskbarList,
+            ptr::null_mut(),
+            CLSCTX_SERVER,
+            &IID_ITASKBAR_LIST3,
+            &mut taskbar_list as *mut *mut ITaskbarList3 as *mut *mut c_void,
+        );
+        (*taskbar_list).SetOverlayIcon(window.hwnd() as HWND, icon, ptr::null());
+        (*taskbar_list).Release();
+        // The taskbar keeps its own copy of the icon.
+        if !icon.is_null() {
+            DestroyIcon(icon);
+        }
+    }
+    Ok(())
+}
+
+// winit can show and hide windows, but can't tell whether they are visible.
+#[cfg(target_os = "windows")]
+fn is_visible(window: &Window) -> bool {
+    // Safety: System calls are unsafe
+    unsafe { IsWindowVisible(window.hwnd() as HWND) != 0 }
+}
+
+#[cfg(target_os = "macos")]
+fn is_visible(window: &Window) -> bool {
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+        let visible: BOOL = msg_send![window.ns_window() as id, isVisible];
+        visible == YES
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn focus_window(window: &Window) {
+    // Safety: System calls are unsafe
+    unsafe {
+        SetForegroundWindow(window.hwnd() as HWND);
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn focus_window(window: &Window) {
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+        let ns_window = window.ns_window() as id;
+        let _: () = msg_send![ns_window, makeKeyAndOrderFront: nil];
+        let _: () = msg_send![NSApp(), activateIgnoringOtherApps: YES];
+    }
+}
+
+// winit has no window opacity.
+#[cfg(target_os = "windows")]
+fn set_opacity(window: &Window, opacity: f64) {
+    let hwnd = window.hwnd() as HWND;
+    // Safety: System calls are unsafe
+    unsafe {
+        let style = GetWindowLongW(hwnd, GWL_EXSTYLE);
+        SetWindowLongW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED as i32);
+        SetLayeredWindowAttributes(hwnd, 0, (opacity * 255.0).round() as u8, LWA_ALPHA);
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_opacity(window: &Window, opacity: f64) {
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+        let _: () = msg_send![window.ns_window() as id, setAlphaValue: opacity];
+    }
+}
+
+// winit has no control over the window shadow either.
+#[cfg(target_os = "windows")]
+fn set_shadow(window: &Window, shadow: bool) {
+    let policy = if shadow {
+        DWMNCRP_ENABLED
+    } else {
+        DWMNCRP_DISABLED
+    };
+    // Safety: System calls are unsafe
+    unsafe {
+        DwmSetWindowAttribute(
+            window.hwnd() as HWND,
+            DWMWA_NCRENDERING_POLICY,
+            &policy as *const _ as *const c_void,
+            mem::size_of_val(&policy) as u32,
+        );
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_shadow(window: &Window, shadow: bool) {
+    let shadow = if shadow { YES } else { NO };
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+        let _: () = msg_send![window.ns_window() as id, setHasShadow: shadow];
+    }
+}
+
+// winit can only keep windows on top.
+#[cfg(target_os = "windows")]
+fn set_always_on_bottom(window: &Window, always_on_bottom: bool) {
+    if always_on_bottom {
+        window.set_always_on_top(false);
+        // Safety: System calls are unsafe
+        unsafe {
+            SetWindowPos(
+                window.hwnd() as HWND,
+                HWND_BOTTOM,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+            );
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_always_on_bottom(window: &Window, always_on_bottom: bool) {
+    let normal = NSWindowLevel::NSNormalWindowLevel as NSInteger;
+    if always_on_bottom {
+        window.set_always_on_top(false);
+    }
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+        let ns_window = window.ns_window() as id;
+        let level: NSInteger = msg_send![ns_window, level];
+        if always_on_bottom {
+            let _: () = msg_send![ns_window, setLevel: normal - 1];
+        } else if level < normal {
+            let _: () = msg_send![ns_window, setLevel: normal];
+        }
+    }
+}
+
+// winit has no control over the buttons of the title bar either.
+#[cfg(target_os = "windows")]
+fn set_style(window: &Window, style: DWORD, enabled: bool) {
+    let hwnd = window.hwnd() as HWND;
+    // Safety: System calls are unsafe
+    unsafe {
+        let current = GetWindowLongW(hwnd, GWL_STYLE) as DWORD;
+        let style = if enabled {
+            current | style
+        } else {
+            current & !style
+        };
+        SetWindowLongW(hwnd, GWL_STYLE, style as i32);
+        // The frame is cached until it's told to change.
+        SetWindowPos(
+            hwnd,
+            ptr::null_mut(),
+            0,
+            0,
+            0,
+            0,
+            SWP_FRAMECHANGED | SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+        );
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn set_minimizable(window: &Window, minimizable: bool) {
+    set_style(window, WS_MINIMIZEBOX, minimizable);
+}
+
+#[cfg(target_os = "windows")]
+fn set_maximizable(window: &Window, maximizable: bool) {
+    set_style(window, WS_MAXIMIZEBOX, maximizable);
+}
+
+#[cfg(target_os = "windows")]
+fn set_closable(window: &Window, closable: bool) {
+    let flags = if closable { MF_ENABLED } else { MF_GRAYED };
+    // Safety: System calls are unsafe
+    unsafe {
+        let menu = GetSystemMenu(window.hwnd() as HWND, FALSE);
+        EnableMenuItem(menu, SC_CLOSE as UINT, MF_BYCOMMAND | flags);
+    }
+}
+
+// The WebView doesn't draw its background, the window shows through until the page is painted.
+#[cfg(target_os = "macos")]
+fn set_background_color(window: &Window, (red, green, blue, alpha): (u8, u8, u8, u8)) {
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+        let color: id = msg_send![
+            class!(NSColor),
+            colorWithSRGBRed: red as f64 / 255.
+            green: green as f64 / 255.
+            blue: blue as f64 / 255.
+            alpha: alpha as f64 / 255.
+        ];
+        let _: () = msg_send![window.ns_window() as id, setBackgroundColor: color];
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_style_mask(window: &Window, mask: NSWindowStyleMask, enabled: bool) {
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+        let ns_window = window.ns_window() as id;
+        let mut style_mask: NSWindowStyleMask = msg_send![ns_window, styleMask];
+        style_mask.set(mask, enabled);
+        let _: () = msg_send![ns_window, setStyleMask: style_mask];
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_minimizable(window: &Window, minimizable: bool) {
+    set_style_mask(
+        window,
+        NSWindowStyleMask::NSMiniaturizableWindowMask,
+        minimizable,
+    );
+}
+
+#[cfg(target_os = "macos")]
+fn set_maximizable(window: &Window, maximizable: bool) {
+    let enabled = if maximizable { YES } else { NO };
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+        let button: id = msg_send![
+            window.ns_window() as id,
+            standardWindowButton: NSWindowButton::NSWindowZoomButton
+        ];
+        let _: () = msg_send![button, setEnabled: enabled];
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_closable(window: &Window, closable: bool) {
+    set_style_mask(window, NSWindowStyleMask::NSClosableWindowMask, closable);
+}
+
+// winit has no clipboard.
+#[cfg(target_os = "windows")]
+fn set_clipboard_text(text: &str) {
+    let text: Vec<u16> = OsStr::new(text).encode_wide().chain(once(0)).collect();
+    // Safety: System calls are unsafe
+    unsafe {
+        if OpenClipboard(ptr::null_mut()) == 0 {
+            return;
+        }
+        EmptyClipboard();
+        let memory = GlobalAlloc(GMEM_MOVEABLE, text.len() * mem::size_of::<u16>());
+        if !memory.is_null() {
+            let data = GlobalLock(memory) as *mut u16;
+            ptr::copy_nonoverlapping(text.as_ptr(), data, text.len());
+            GlobalUnlock(memory);
+            // The clipboard owns the memory once it's set.
+            if SetClipboardData(CF_UNICODETEXT, memory).is_null() {
+                GlobalFree(memory);
+            }
+        }
+        CloseClipboard();
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn clipboard_text() -> Option<String> {
+    // Safety: System calls are unsafe
+    unsafe {
+        if OpenClipboard(ptr::null_mut()) == 0 {
+            return None;
+        }
+        let memory = GetClipboardData(CF_UNICODETEXT);
+        let text = if memory.is_null() {
+            None
+        } else {
+            let data = GlobalLock(memory) as *const u16;
+            let len = (0..).take_while(|&i| *data.offset(i) != 0).count();
+            let text = OsString::from_wide(slice::from_raw_parts(data, len))
+                .into_string()
+                .ok();
+            GlobalUnlock(memory);
+            text
+        };
+        CloseClipboard();
+        text
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_clipboard_text(text: &str) {
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+        let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+        let _: NSInteger = msg_send![pasteboard, clearContents];
+        let text = NSString::alloc(nil).init_str(text);
+        let _: BOOL = msg_send![pasteboard, setString:text forType:NSPasteboardTypeString];
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn clipboard_text() -> Option<String> {
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+        let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+        let text: id = msg_send![pasteboard, stringForType: NSPasteboardTypeString];
+        if text.is_null() {
+            return None;
+        }
+        CStr::from_ptr(text.UTF8String())
+            .to_str()
+            .ok()
+            .map(String::from)
+    }
+}
+
+fn snap(window: &Window, region: SnapRegion) {
+    // winit doesn't expose the work area of the monitor.
+    if let Some(monitor) = window.current_monitor() {
+        let position = monitor.position();
+        let size = monitor.size();
+        let (x, y, width, height) = region.rect(
+            position.x as f64,
+            position.y as f64,
+            size.width as f64,
+            size.height as f64,
+        );
+        // The region includes the decorations of the window.
+        let outer_size = window.outer_size();
+        let inner_size = window.inner_size();
+        window.set_maximized(false);
+        window.set_outer_position(PhysicalPosition::new(x as i32, y as i32));
+        window.set_inner_size(PhysicalSize::new(
+            (width as u32).saturating_sub(outer_size.width - inner_size.width),
+            (height as u32).saturating_sub(outer_size.height - inner_size.height),
+        ));
+    }
+}
+
+// Shrinks the initial size of the window so it fits on the monitor it opens on.
+fn fit_to_screen(
+    event_loop: &EventLoopWindowTarget<Message>,
+    attributes: &mut InnerWindowAttributes,
+) {
+    let monitor = match (attributes.x, attributes.y) {
+        (Some(x), Some(y)) => event_loop.available_monitors().find(|monitor| {
+            let position = monitor.position().to_logical::<f64>(monitor.scale_factor());
+            let size = monitor.size().to_logical::<f64>(monitor.scale_factor());
+            x >= position.x
+                && x < position.x + size.width
+                && y >= position.y
+                && y < position.y + size.height
+        }),
+        _ => None,
+    }
+    .or_else(|| event_loop.primary_monitor());
+    if let Some(monitor) = monitor {
+        let size = monitor.size().to_logical::<f64>(monitor.scale_factor());
+        attributes.width = attributes.width.min(size.width);
+        attributes.height = attributes.height.min(size.height);
+    }
+}
+
+// The index of the monitor showing the center of the window.
+fn monitor_index(window: &Window) -> Option<usize> {
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size();
+    let x = position.x + size.width as i32 / 2;
+    let y = position.y + size.height as i32 / 2;
+    window.available_monitors().position(|monitor| {
+        let position = monitor.position();
+        let size = monitor.size();
+        x >= position.x
+            && x < position.x + size.width as i32
+            && y >= position.y
+            && y < position.y + size.height as i32
+    })
+}
+
+// Emits `MonitorChanged` when the center of the window moved to another monitor.
+fn track_monitor(
+    monitors: &mut HashMap<WindowId, usize>,
+    window: &Window,
+    event_channel: &EventSender,
+) {
+    let new = match monitor_index(window) {
+        Some(new) => new,
+        None => return,
+    };
+    if let Some(old) = monitors.insert(window.id(), new) {
+        if old != new {
+            let _ = event_channel.send(WryEvent::WindowEvent {
+                window_id: window.id(),
+                event: crate::WindowEvent::MonitorChanged(new),
+            });
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn show_message_dialog(
+    window: &Window,
+    title: &str,
+    message: &str,
+    buttons: MessageDialogButtons,
+) -> MessageDialogResult {
+    let title = to_wide(title);
+    let message = to_wide(message);
+    let flags = match buttons {
+        MessageDialogButtons::Ok => MB_OK,
+        MessageDialogButtons::OkCancel => MB_OKCANCEL,
+        MessageDialogButtons::YesNo => MB_YESNO,
+        MessageDialogButtons::YesNoCancel => MB_YESNOCANCEL,
+    };
+    // Safety: System calls are unsafe
+    let response = unsafe {
+        MessageBoxW(
+            window.hwnd() as HWND,
+            message.as_ptr(),
+            title.as_ptr(),
+            flags,
+        )
+    };
+    match response {
+        IDOK => MessageDialogResult::Ok,
+        IDYES => MessageDialogResult::Yes,
+        IDNO => MessageDialogResult::No,
+        _ => buttons.dismissed(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn show_message_dialog(
+    _window: &Window,
+    title: &str,
+    message: &str,
+    buttons: MessageDialogButtons,
+) -> MessageDialogResult {
+    // The first button is the default one and is placed on the right.
+    let choices: &[(&str, MessageDialogResult)] = match buttons {
+        MessageDialogButtons::Ok => &[("OK", MessageDialogResult::Ok)],
+        MessageDialogButtons::OkCancel => &[
+            ("OK", MessageDialogResult::Ok),
+            ("Cancel", MessageDialogResult::Cancel),
+        ],
+        MessageDialogButtons::YesNo => &[
+            ("Yes", MessageDialogResult::Yes),
+            ("No", MessageDialogResult::No),
+        ],
+        MessageDialogButtons::YesNoCancel => &[
+            ("Yes", MessageDialogResult::Yes),
+            ("No", MessageDialogResult::No),
+            ("Cancel", MessageDialogResult::Cancel),
+        ],
+    };
+
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+        let alert: id = msg_send![class!(NSAlert), new];
+        let _: () = msg_send![alert, setMessageText: NSString::alloc(nil).init_str(title)];
+        let _: () = msg_send![alert, setInformativeText: NSString::alloc(nil).init_str(message)];
+        for (label, _) in choices {
+            let _: id = msg_send![alert, addButtonWithTitle: NSString::alloc(nil).init_str(label)];
+        }
+        // NSAlertFirstButtonReturn is 1000, the following buttons count up from there.
+        let response: NSInteger = msg_send![alert, runModal];
+        choices
+            .get((response - 1000) as usize)
+            .map(|(_, result)| *result)
+            .unwrap_or_else(|| buttons.dismissed())
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn to_wide<S: AsRef<OsStr>>(s: S) -> Vec<u16> {
+    s.as_ref().encode_wide().chain(once(0)).collect()
+}
+
+#[cfg(target_os = "windows")]
+fn file_dialog(window: &Window, options: &FileDialogOptions, save: bool) -> Vec<PathBuf> {
+    // Each filter is a pair of null terminated strings, the list ends with an extra null.
+    let mut filters = Vec::new();
+    for filter in &options.filters {
+        let patterns: Vec<String> = filter
+            .extensions
+            .iter()
+            .map(|extension| format!("*.{}", extension))
+            .collect();
+        filters.extend(to_wide(&filter.name));
+        filters.extend(to_wide(patterns.join(";")));
+    }
+    filters.push(0);
+    let title = options.title.as_ref().map(to_wide);
+    let directory = options.directory.as_ref().map(to_wide);
+
+    // Receives the selected paths, starting with the suggested file name if any.
+    let mut file = vec![0u16; 32 * 1024];
+    if let Some(file_name) = &options.file_name {
+        let file_name: Vec<u16> = OsStr::new(file_name).encode_wide().collect();
+        let len = file_name.len().min(file.len() - 1);
+        file[..len].copy_from_slice(&file_name[..len]);
+    }
+
+    // Safety: System calls are unsafe
+    let selected = unsafe {
+        let mut ofn: OPENFILENAMEW = mem::zeroed();
+        ofn.lStructSize = mem::size_of::<OPENFILENAMEW>() as u32;
+        ofn.hwndOwner = window.hwnd() as HWND;
+        if !options.filters.is_empty() {
+            ofn.lpstrFilter = filters.as_ptr();
+        }
+        ofn.lpstrFile = file.as_mut_ptr();
+        ofn.nMaxFile = file.len() as u32;
+        ofn.lpstrTitle = title.as_ref().map_or(ptr::null(), |title| title.as_ptr());
+        ofn.lpstrInitialDir = directory
+            .as_ref()
+            .map_or(ptr::null(), |directory| directory.as_ptr());
+        ofn.Flags = OFN_EXPLORER | OFN_NOCHANGEDIR;
+        if save {
+            ofn.Flags |= OFN_OVERWRITEPROMPT;
+            GetSaveFileNameW(&mut ofn)
+        } else {
+            ofn.Flags |= OFN_FILEMUSTEXIST;
+            if options.multiple {
+                ofn.Flags |= OFN_ALLOWMULTISELECT;
+            }
+            GetOpenFileNameW(&mut ofn)
+        }
+    };
+    if selected == 0 {
+        return Vec::new();
+    }
+
+    // Selecting several files returns their directory followed by their names, all separated by
+    // nulls, while a single file is returned as a full path.
+    let paths: Vec<PathBuf> = file
+        .split(|c| *c == 0)
+        .take_while(|part| !part.is_empty())
+        .map(|part| PathBuf::from(OsString::from_wide(part)))
+        .collect();
+    match paths.split_first() {
+        Some((directory, names)) if !names.is_empty() => {
+            names.iter().map(|name| directory.join(name)).collect()
+        }
+        _ => paths,
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn open_file_dialog(window: &Window, options: &FileDialogOptions) -> Vec<PathBuf> {
+    file_dialog(window, options, false)
+}
+
+#[cfg(target_os = "windows")]
+fn save_file_dialog(window: &Window, options: &FileDialogOptions) -> Option<PathBuf> {
+    file_dialog(window, options, true).pop()
+}
+
+// Safety: `panel` must be a NSSavePanel or one of its subclasses.
+#[cfg(target_os = "macos")]
+unsafe fn configure_panel(panel: id, options: &FileDialogOptions) {
+    if let Some(title) = &options.title {
+        let _: () = msg_send![panel, setTitle: NSString::alloc(nil).init_str(title)];
+    }
+    if let Some(directory) = options.directory.as_ref().and_then(|d| d.to_str()) {
+        let url: id =
+            msg_send![class!(NSURL), fileURLWithPath: NSString::alloc(nil).init_str(directory)];
+        let _: () = msg_send![panel, setDirectoryURL: url];
+    }
+    // Panels don't let the user switch between filters, so every extension is allowed at once.
+    let extensions: Vec<id> = options
+        .filters
+        .iter()
+        .flat_map(|filter| &filter.extensions)
+        .map(|extension| NSString::alloc(nil).init_str(extension))
+        .collect();
+    if !extensions.is_empty() {
+        let _: () =
+            msg_send![panel, setAllowedFileTypes: NSArray::arrayWithObjects(nil, &extensions)];
+    }
+}
+
+#[cfg(target_os = "macos")]
+unsafe fn url_to_path(url: id) -> PathBuf {
+    let path: id = msg_send![url, path];
+    PathBuf::from(
+        CStr::from_ptr(path.UTF8String())
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn open_file_dialog(_window: &Window, options: &FileDialogOptions) -> Vec<PathBuf> {
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+        let panel: id = msg_send![class!(NSOpenPanel), openPanel];
+        configure_panel(panel, options);
+        let _: () = msg_send![panel, setCanChooseFiles: YES];
+        let _: () = msg_send![panel, setCanChooseDirectories: NO];
+        let _: () =
+            msg_send![panel, setAllowsMultipleSelection: if options.multiple { YES } else { NO }];
+
+        // NSModalResponseOK
+        let response: NSInteger = msg_send![panel, runModal];
+        if response != 1 {
+            return Vec::new();
+        }
+        let urls: id = msg_send![panel, URLs];
+        let count: NSUInteger = msg_send![urls, count];
+        (0..count)
+            .map(|i| url_to_path(msg_send![urls, objectAtIndex: i]))
+            .collect()
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn save_file_dialog(_window: &Window, options: &FileDialogOptions) -> Option<PathBuf> {
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+        let panel: id = msg_send![class!(NSSavePanel), savePanel];
+        configure_panel(panel, options);
+        if let Some(file_name) = &options.file_name {
+            let _: () =
+                msg_send![panel, setNameFieldStringValue: NSString::alloc(nil).init_str(file_name)];
+        }
+
+        // NSModalResponseOK
+        let response: NSInteger = msg_send![panel, runModal];
+        if response != 1 {
+            return None;
+        }
+        Some(url_to_path(msg_send![panel, URL]))
+    }
+}
+
+fn fullscreen(
+    event_loop: &EventLoopWindowTarget<Message>,
+    fullscreen: crate::Fullscreen,
+) -> Option<Fullscreen> {
+    match fullscreen {
+        crate::Fullscreen::None => None,
+        crate::Fullscreen::Borderless(monitor) => {
+            Some(Fullscreen::Borderless(monitor.and_then(|monitor| {
+                event_loop.available_monitors().nth(monitor)
+            })))
+        }
+        crate::Fullscreen::Exclusive => {
+            let monitor = event_loop
+                .primary_monitor()
+                .or_else(|| event_loop.available_monitors().next())?;
+            let mode = monitor.video_modes().max_by_key(|mode| {
+                let size = mode.size();
+                (size.width * size.height, mode.refresh_rate())
+            })?;
+            Some(Fullscreen::Exclusive(mode))
+        }
+    }
+}
+
 fn _create_window(
     event_loop: &EventLoopWindowTarget<Message>,
-    attributes: InnerWindowAttributes,
+    mut attributes: InnerWindowAttributes,
 ) -> Result<Window> {
+    if attributes.fit_to_screen {
+        fit_to_screen(event_loop, &mut attributes);
+    }
     let mut window_builder = WindowBuilder::new();
     #[cfg(target_os = "macos")]
     if attributes.skip_taskbar {
         window_builder = window_builder.with_activation_policy(ActivationPolicy::Accessory);
     }
-    let window_attributes = WindowAttributes::from(&attributes);
+    let mut window_attributes = WindowAttributes::from(&attributes);
+    window_attributes.fullscreen = fullscreen(event_loop, attributes.fullscreen);
     window_builder.window = window_attributes;
     let window = window_builder.build(event_loop)?;
     match (attributes.x, attributes.y) {
@@ -334,6 +1688,25 @@ fn _create_window(
     if attributes.skip_taskbar {
         skip_taskbar(&window);
     }
+    #[cfg(target_os = "macos")]
+    if let Some(color) = attributes
+        .background_color
+        .filter(|_| !attributes.transparent)
+    {
+        set_background_color(&window, color);
+    }
+    if attributes.always_on_bottom && !attributes.always_on_top {
+        set_always_on_bottom(&window, true);
+    }
+    if !attributes.minimizable {
+        set_minimizable(&window, false);
+    }
+    if !attributes.maximizable {
+        set_maximizable(&window, false);
+    }
+    if !attributes.closable {
+        set_closable(&window, false);
+    }
 
     Ok(window)
 }
@@ -342,21 +1715,156 @@ fn _create_webview(
     proxy: InnerApplicationProxy,
     window: Window,
     attributes: InnerWebViewAttributes,
-    custom_protocol: Option<CustomProtocol>,
+    app_attributes: &ApplicationAttributes,
+    custom_protocols: Vec<CustomProtocol>,
     rpc_handler: Option<WindowRpcHandler>,
+    event_channel: EventSender,
 ) -> Result<WebView> {
     let window_id = window.id();
 
-    let mut webview = WebViewBuilder::new(window)?.transparent(attributes.transparent);
+    let mut webview = WebViewBuilder::new(window)?
+        .focused(attributes.focused)
+        .transparent(attributes.transparent)
+        .html_title_as_window_title(attributes.html_title_as_window_title)
+        .back_forward_navigation_gestures(attributes.back_forward_navigation_gestures)
+        .overscroll(attributes.overscroll)
+        .auto_dpi_zoom(attributes.auto_dpi_zoom)
+        .allow_file_access_from_file_urls(attributes.allow_file_access_from_file_urls)
+        .allow_universal_access_from_file_urls(attributes.allow_universal_access_from_file_urls)
+        .custom_protocol_cors(attributes.custom_protocol_cors)
+        .custom_protocol_local(attributes.custom_protocol_local)
+        .inject_rpc_bridge(attributes.inject_rpc_bridge);
+    if let Some(zoom) = attributes.zoom_level {
+        webview = webview.zoom_level(zoom);
+    }
+    if let Some(scale_factor) = attributes.device_scale_factor {
+        webview = webview.device_scale_factor(scale_factor);
+    }
+    if let Some(width) = attributes.resize_border {
+        webview = webview.resize_border(width);
+    }
+    if let Some(color) = attributes.background_color {
+        webview = webview.background_color(color);
+    }
+    if let Some(timeout) = attributes.custom_protocol_timeout {
+        webview = webview.custom_protocol_timeout(timeout);
+    }
+    if let Some(threads) = attributes.custom_protocol_concurrency {
+        webview = webview.custom_protocol_concurrency(threads);
+    }
+    if let Some(languages) = attributes.accept_languages {
+        webview = webview.accept_languages(languages);
+    }
+    if let Some(user_agent) = attributes.user_agent {
+        webview = webview.user_agent(user_agent);
+    }
+    if attributes.devtools {
+        webview = webview.devtools(true);
+    }
+    webview = webview.devtools_attachment(attributes.devtools_attachment);
+    if let Some(html) = attributes.error_page {
+        webview = webview.error_page(html);
+    }
+    let events = event_channel.clone();
+    webview = webview.devtools_handler(move |open| {
+        let _ = events.send(WryEvent::WindowEvent {
+            window_id,
+            event: if open {
+                crate::WindowEvent::DevtoolsOpened
+            } else {
+                crate::WindowEvent::DevtoolsClosed
+            },
+        });
+    });
+    let events = event_channel.clone();
+    webview = webview.page_load_handler(move |url, state| {
+        let _ = events.send(WryEvent::WindowEvent {
+            window_id,
+            event: crate::WindowEvent::PageLoad {
+                url: url.to_string(),
+                state,
+            },
+        });
+    });
+    for js in &app_attributes.initialization_scripts {
+        webview = webview.initialize_script(js);
+    }
     for js in attributes.initialization_scripts {
         webview = webview.initialize_script(&js);
     }
 
-    if let Some(protocol) = custom_protocol {
-        webview = webview.register_protocol(protocol.name, protocol.handler)
+    for protocol in custom_protocols {
+        let (name, handler) = protocol.into_handler();
+        webview = webview.register_protocol_handler(name, handler);
+    }
+
+    if let Some(serializer) = app_attributes.rpc_serializer.clone() {
+        webview = webview.rpc_serializer(serializer);
+    }
+
+    if let Some(handler) = app_attributes.before_unload_handler.clone() {
+        let proxy = proxy.clone();
+        webview = webview.before_unload_handler(move |message| {
+            let proxy = WindowProxy::new(
+                ApplicationProxy {
+                    inner: proxy.clone(),
+                },
+                window_id,
+            );
+            handler(proxy, message)
+        });
+    }
+
+    if let Some(handler) = app_attributes.script_dialog_handler.clone() {
+        let proxy = proxy.clone();
+        webview = webview.script_dialog_handler(move |dialog| {
+            let proxy = WindowProxy::new(
+                ApplicationProxy {
+                    inner: proxy.clone(),
+                },
+                window_id,
+            );
+            handler(proxy, dialog)
+        });
+    }
+
+    if let Some(handler) = app_attributes.paste_handler.clone() {
+        let proxy = proxy.clone();
+        webview = webview.paste_handler(move |content| {
+            let proxy = WindowProxy::new(
+                ApplicationProxy {
+                    inner: proxy.clone(),
+                },
+                window_id,
+            );
+            handler(proxy, content)
+        });
+    }
+
+    if let Some(handler) = app_attributes.context_menu_handler.clone() {
+        let proxy = proxy.clone();
+        webview = webview.context_menu_handler(
+            move |menu| {
+                let proxy = WindowProxy::new(
+                    ApplicationProxy {
+                        inner: proxy.clone(),
+                    },
+                    window_id,
+                );
+                handler(proxy, menu)
+            },
+            move |id| {
+                let _ = event_channel.send(WryEvent::WindowEvent {
+                    window_id,
+                    event: crate::WindowEvent::ContextMenuItem(id.to_string()),
+                });
+            },
+        );
     }
 
-    if let Some(rpc_handler) = rpc_handler {
+    // Without the bridge, the RPC script would still define `window.external`.
+    let inject_rpc_bridge = attributes.inject_rpc_bridge;
+    if let Some(rpc_handler) = rpc_handler.filter(|_| inject_rpc_bridge) {
         webview = webview.set_rpc_handler(Box::new(move |requests| {
             let proxy = WindowProxy::new(
                 ApplicationProxy {
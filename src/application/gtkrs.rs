@@ -1,22 +1,45 @@
 use crate::{
-    application::{App, AppProxy, InnerWebViewAttributes, InnerWindowAttributes},
-    ApplicationProxy, Attributes, CustomProtocol, Error, Icon, Message, Result, WebView,
-    WebViewBuilder, WindowMessage, WindowProxy, WindowRpcHandler,
+    application::{
+        event_queue, App, AppProxy, ApplicationAttributes, EventReceiver, EventSender,
+        InnerWebViewAttributes, InnerWindowAttributes, WindowBeforeUnloadHandler,
+        WindowCloseRequestedHandler, WindowContextMenuHandler, WindowPasteHandler,
+        WindowScriptDialogHandler, MIN_CONTENT_SIZE_SCRIPT,
+    },
+    ApplicationProxy, Attributes, CustomProtocol, Error, FileDialogOptions, Fullscreen, Icon,
+    MemoryPressureSettings, Message, MessageDialogButtons, MessageDialogResult, ModifiersState,
+    ProgressBarState, Result, RoundedRect, RpcSerializer, SnapRegion, WebView, WebViewBuilder,
+    WindowButtonsPosition, WindowEvent, WindowMessage, WindowProxy, WindowResponse,
+    WindowRpcHandler, WindowTypeHint, WryEvent,
 };
 
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::HashMap,
+    mem,
+    path::PathBuf,
     rc::Rc,
     sync::mpsc::{channel, Receiver, Sender},
+    thread::{self, ThreadId},
+    time::{Duration, Instant},
 };
 
-use cairo::Operator;
-use gio::{ApplicationExt as GioApplicationExt, Cancellable};
+use cairo::{Context, Operator};
+use gdk::WindowExt;
+use gio::prelude::ApplicationExtManual;
+use gio::{ApplicationExt as GioApplicationExt, ApplicationFlags, Cancellable, File, FileExt};
+use glib::{
+    translate::{from_glib_none, ToGlibPtr},
+    Continue, MainContext, ObjectExt, ToValue, ToVariant, Variant, VariantDict,
+};
+use gtk::prelude::{GtkWindowExtManual, WidgetExtManual};
 use gtk::{
-    Application as GtkApp, ApplicationWindow, ApplicationWindowExt, GtkWindowExt, Inhibit,
-    WidgetExt,
+    Application as GtkApp, ApplicationWindow, ApplicationWindowExt, ButtonsType, Clipboard,
+    ContainerExt, CssProvider, CssProviderExt, DialogExt, DialogFlags, FileChooserAction,
+    FileChooserExt, FileChooserNative, GtkWindowExt, Image, Inhibit, MessageDialog, MessageType,
+    NativeDialogExt, ResponseType, Settings, SettingsExt, StyleContextExt, WidgetExt,
+    WindowPosition, WindowType, STYLE_PROVIDER_PRIORITY_APPLICATION,
 };
+use webkit2gtk::{ProcessModel, WebContext, WebContextExt, WebsiteDataManagerBuilder};
 
 pub type WindowId = u32;
 
@@ -31,6 +54,8 @@ impl Clone for EventLoopProxy {
 #[derive(Clone)]
 pub struct InnerApplicationProxy {
     proxy: EventLoopProxy,
+    event_receiver: EventReceiver,
+    main_thread: ThreadId,
 }
 
 impl AppProxy for InnerApplicationProxy {
@@ -42,18 +67,34 @@ impl AppProxy for InnerApplicationProxy {
         Ok(())
     }
 
+    fn listen_event(&self) -> Result<WryEvent> {
+        self.event_receiver.recv()
+    }
+
+    fn is_main_thread(&self) -> bool {
+        thread::current().id() == self.main_thread
+    }
+
+    fn run_on_main(&self, f: Box<dyn FnOnce() + Send>) -> Result<()> {
+        // Unlike messages, this wakes up the main loop right away.
+        MainContext::default().invoke(f);
+        Ok(())
+    }
+
     fn add_window(
         &self,
         attributes: Attributes,
         rpc_handler: Option<WindowRpcHandler>,
-        custom_protocol: Option<CustomProtocol>,
+        close_requested_handler: Option<WindowCloseRequestedHandler>,
+        custom_protocols: Vec<CustomProtocol>,
     ) -> Result<WindowId> {
         let (sender, receiver): (Sender<WindowId>, Receiver<WindowId>) = channel();
         self.send_message(Message::NewWindow(
             attributes,
             sender,
             rpc_handler,
-            custom_protocol,
+            close_requested_handler,
+            custom_protocols,
         ))?;
         Ok(receiver.recv()?)
     }
@@ -64,24 +105,72 @@ pub struct InnerApplication {
     app: GtkApp,
     event_loop_proxy: EventLoopProxy,
     event_loop_proxy_rx: Receiver<Message>,
+    web_context: Option<WebContext>,
+    storage_partitions: HashMap<String, WebContext>,
+    event_channel: EventSender,
+    event_receiver: EventReceiver,
+    attributes: ApplicationAttributes,
+}
+
+impl InnerApplication {
+    // The web context of windows in the given storage partition, created on first use.
+    fn partition_context(&mut self, partition: Option<&str>) -> Option<WebContext> {
+        let name = match partition {
+            Some(name) => name,
+            None => return self.web_context.clone(),
+        };
+        let shared = self.web_context.as_ref();
+        let context = self
+            .storage_partitions
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                let context = partition_web_context(name);
+                if let Some(shared) = shared {
+                    context.set_process_model(shared.get_process_model());
+                    context.set_web_process_count_limit(shared.get_web_process_count_limit());
+                }
+                context
+            });
+        Some(context.clone())
+    }
 }
 
 impl App for InnerApplication {
     type Id = u32;
     type Proxy = InnerApplicationProxy;
 
-    fn new() -> Result<Self> {
-        let app = GtkApp::new(None, Default::default())?;
+    fn new(application_id: Option<&str>) -> Result<Self> {
+        // Applications with an ID are single instance, the URLs opened by other launches are
+        // forwarded to the running one through the `open` signal.
+        let flags = match application_id {
+            Some(_) => ApplicationFlags::HANDLES_OPEN,
+            None => ApplicationFlags::empty(),
+        };
+        let app = GtkApp::new(application_id, flags)?;
         let cancellable: Option<&Cancellable> = None;
         app.register(cancellable)?;
 
         let (event_loop_proxy_tx, event_loop_proxy_rx) = channel();
+        let (event_channel, event_receiver) = event_queue();
+
+        let events = event_channel.clone();
+        app.connect_open(move |_, files, _| {
+            for file in files {
+                let _ = events.send(WryEvent::OpenUrl(file.get_uri().to_string()));
+            }
+        });
+        connect_memory_pressure_events(event_channel.clone());
 
         Ok(Self {
             webviews: HashMap::new(),
             app,
             event_loop_proxy: EventLoopProxy(event_loop_proxy_tx),
             event_loop_proxy_rx,
+            web_context: None,
+            storage_partitions: HashMap::new(),
+            event_channel,
+            event_receiver,
+            attributes: ApplicationAttributes::default(),
         })
     }
 
@@ -89,17 +178,34 @@ impl App for InnerApplication {
         &mut self,
         attributes: Attributes,
         rpc_handler: Option<WindowRpcHandler>,
-        custom_protocol: Option<CustomProtocol>,
+        close_requested_handler: Option<WindowCloseRequestedHandler>,
+        custom_protocols: Vec<CustomProtocol>,
     ) -> Result<Self::Id> {
         let (window_attrs, webview_attrs) = attributes.split();
+        let keyboard_events = window_attrs.keyboard_events;
+        let idle_hint_timeout = window_attrs.idle_hint_timeout;
         let window = _create_window(&self.app, window_attrs)?;
+        if let Some(handler) = close_requested_handler {
+            connect_close_requested_handler(&window, handler, self.application_proxy());
+        }
+        connect_window_events(&window, self.event_channel.clone());
+        if keyboard_events {
+            connect_keyboard_events(&window, self.event_channel.clone());
+        }
+        if let Some(timeout) = idle_hint_timeout {
+            connect_idle_events(&window, timeout, self.event_channel.clone());
+        }
 
+        let web_context = self.partition_context(webview_attrs.storage_partition.as_deref());
         let webview = _create_webview(
             self.application_proxy(),
             window,
             webview_attrs,
-            custom_protocol,
+            &self.attributes,
+            custom_protocols,
             rpc_handler,
+            web_context,
+            self.event_channel.clone(),
         )?;
         let id = webview.window().get_id();
         self.webviews.insert(id, webview);
@@ -107,15 +213,78 @@ impl App for InnerApplication {
         Ok(id)
     }
 
+    fn add_initialization_script(&mut self, js: &str) {
+        self.attributes.initialization_scripts.push(js.to_string());
+    }
+
+    fn set_before_unload_handler(&mut self, handler: WindowBeforeUnloadHandler) {
+        self.attributes.before_unload_handler = Some(handler);
+    }
+
+    fn set_script_dialog_handler(&mut self, handler: WindowScriptDialogHandler) {
+        self.attributes.script_dialog_handler = Some(handler);
+    }
+
+    fn set_paste_handler(&mut self, handler: WindowPasteHandler) {
+        self.attributes.paste_handler = Some(handler);
+    }
+
+    fn set_context_menu_handler(&mut self, handler: WindowContextMenuHandler) {
+        self.attributes.context_menu_handler = Some(handler);
+    }
+
+    fn set_rpc_serializer(&mut self, serializer: Rc<dyn RpcSerializer>) {
+        self.attributes.rpc_serializer = Some(serializer);
+    }
+
+    fn set_web_process_limit(&mut self, limit: u32) {
+        let context = WebContext::new();
+        context.set_process_model(ProcessModel::MultipleSecondaryProcesses);
+        context.set_web_process_count_limit(limit);
+        self.web_context = Some(context);
+    }
+
+    fn set_memory_pressure_settings(&mut self, settings: MemoryPressureSettings) {
+        apply_memory_pressure_settings(&settings);
+    }
+
+    fn open_urls(&self, urls: &[String]) -> bool {
+        // Without an ID, there's no other instance to forward them to.
+        if self.app.get_application_id().is_none() {
+            for url in urls {
+                let _ = self.event_channel.send(WryEvent::OpenUrl(url.clone()));
+            }
+            return false;
+        }
+        let files: Vec<File> = urls.iter().map(|url| File::new_for_uri(url)).collect();
+        if !files.is_empty() {
+            self.app.open(&files, "");
+        }
+        self.app.get_is_remote()
+    }
+
+    fn set_im_module(&mut self, module: &str) {
+        if let Some(settings) = Settings::get_default() {
+            settings.set_property_gtk_im_module(Some(module));
+        }
+    }
+
     fn application_proxy(&self) -> Self::Proxy {
         InnerApplicationProxy {
             proxy: self.event_loop_proxy.clone(),
+            event_receiver: self.event_receiver.clone(),
+            // The application can't leave the thread it was created on.
+            main_thread: thread::current().id(),
         }
     }
 
-    fn run(self) {
+    fn run(mut self) {
+        self.run_return()
+    }
+
+    fn run_return(&mut self) {
         let proxy = self.application_proxy();
-        let shared_webviews = Rc::new(RefCell::new(self.webviews));
+        let shared_webviews = Rc::new(RefCell::new(mem::take(&mut self.webviews)));
         let shared_webviews_ = shared_webviews.clone();
 
         {
@@ -143,18 +312,46 @@ impl App for InnerApplication {
                 }
             }
 
-            while let Ok(message) = self.event_loop_proxy_rx.try_recv() {
+            let messages: Vec<Message> = self
+                .event_loop_proxy_rx
+                .try_iter()
+                .flat_map(Message::flatten)
+                .collect();
+            for message in messages {
                 match message {
-                    Message::NewWindow(attributes, sender, rpc_handler, custom_protocol) => {
+                    Message::NewWindow(
+                        attributes,
+                        sender,
+                        rpc_handler,
+                        close_requested_handler,
+                        custom_protocols,
+                    ) => {
                         let (window_attrs, webview_attrs) = attributes.split();
+                        let keyboard_events = window_attrs.keyboard_events;
+                        let idle_hint_timeout = window_attrs.idle_hint_timeout;
                         let window = _create_window(&self.app, window_attrs).unwrap();
+                        if let Some(handler) = close_requested_handler {
+                            connect_close_requested_handler(&window, handler, proxy.clone());
+                        }
+                        connect_window_events(&window, self.event_channel.clone());
+                        if keyboard_events {
+                            connect_keyboard_events(&window, self.event_channel.clone());
+                        }
+                        if let Some(timeout) = idle_hint_timeout {
+                            connect_idle_events(&window, timeout, self.event_channel.clone());
+                        }
                         sender.send(window.get_id()).unwrap();
+                        let web_context =
+                            self.partition_context(webview_attrs.storage_partition.as_deref());
                         let webview = _create_webview(
                             proxy.clone(),
                             window,
                             webview_attrs,
-                            custom_protocol,
+                            &self.attributes,
+                            custom_protocols,
                             rpc_handler,
+                            web_context,
+                            self.event_channel.clone(),
                         )
                         .unwrap();
                         let id = webview.window().get_id();
@@ -168,12 +365,40 @@ impl App for InnerApplication {
                         let mut webviews = shared_webviews.borrow_mut();
                         webviews.insert(id, webview);
                     }
+                    Message::AddInitializationScript(js) => {
+                        self.attributes.initialization_scripts.push(js);
+                    }
+                    Message::RunOnMain(f) => f(),
+                    Message::Batch(_) => unreachable!("batches are flattened"),
+                    Message::CloseWindows { except } => {
+                        // Closing a window removes it from the map, so it can't be borrowed then.
+                        let windows: Vec<ApplicationWindow> = shared_webviews
+                            .borrow()
+                            .iter()
+                            .filter(|(id, _)| Some(**id) != except)
+                            .map(|(_, webview)| webview.window().clone())
+                            .collect();
+                        for window in windows {
+                            close(&window);
+                        }
+                    }
                     Message::Window(id, window_message) => {
                         if let Some(webview) = shared_webviews.borrow_mut().get_mut(&id) {
                             let window = webview.window();
                             match window_message {
                                 WindowMessage::SetResizable(resizable) => {
                                     window.set_resizable(resizable);
+                                    set_functions(window, gdk::WMFunction::RESIZE, resizable);
+                                }
+                                WindowMessage::SetMinimizable(minimizable) => {
+                                    set_functions(window, gdk::WMFunction::MINIMIZE, minimizable);
+                                }
+                                WindowMessage::SetMaximizable(maximizable) => {
+                                    set_functions(window, gdk::WMFunction::MAXIMIZE, maximizable);
+                                }
+                                WindowMessage::SetClosable(closable) => {
+                                    window.set_deletable(closable);
+                                    set_functions(window, gdk::WMFunction::CLOSE, closable);
                                 }
                                 WindowMessage::SetTitle(title) => window.set_title(&title),
                                 WindowMessage::Maximize => {
@@ -187,6 +412,9 @@ impl App for InnerApplication {
                                 }
                                 WindowMessage::Unminimize => {
                                     window.deiconify();
+                                    // Some window managers only restore the window once it's
+                                    // presented again.
+                                    window.present();
                                 }
                                 WindowMessage::Show => {
                                     window.show();
@@ -194,14 +422,39 @@ impl App for InnerApplication {
                                 WindowMessage::Hide => {
                                     window.hide();
                                 }
+                                WindowMessage::IsVisible(responder) => {
+                                    let _ = responder
+                                        .send(WindowResponse::IsVisible(window.get_visible()));
+                                }
+                                WindowMessage::RequestUserAttention(request) => {
+                                    window
+                                        .set_urgency_hint(request.is_some() && !window.is_active());
+                                }
+                                WindowMessage::Focus => {
+                                    window.deiconify();
+                                    window.present();
+                                    let _ = webview.focus();
+                                }
                                 WindowMessage::Close => {
-                                    window.close();
+                                    close(window);
                                 }
                                 WindowMessage::SetDecorations(decorations) => {
                                     window.set_decorated(decorations);
                                 }
+                                WindowMessage::SetOpacity(opacity) => {
+                                    window.set_opacity(opacity);
+                                }
+                                WindowMessage::SetShadow(shadow) => {
+                                    set_shadow(window, shadow);
+                                }
                                 WindowMessage::SetAlwaysOnTop(always_on_top) => {
-                                    window.set_keep_above(always_on_top);
+                                    set_always_on_top(window, always_on_top);
+                                }
+                                WindowMessage::SetAlwaysOnBottom(always_on_bottom) => {
+                                    set_always_on_bottom(window, always_on_bottom);
+                                }
+                                WindowMessage::SetAlwaysOnTopYieldsToFullscreen(yields) => {
+                                    set_always_on_top_yields_to_fullscreen(window, yields);
                                 }
                                 WindowMessage::SetWidth(width) => {
                                     window.resize(width as i32, window.get_size().1);
@@ -234,6 +487,25 @@ impl App for InnerApplication {
                                         gdk::WindowHints::MIN_SIZE,
                                     );
                                 }
+                                WindowMessage::SetMinSizeToContent => {
+                                    let proxy = proxy.clone();
+                                    let _ = webview.evaluate_script_with_callback(
+                                        MIN_CONTENT_SIZE_SCRIPT,
+                                        move |size| {
+                                            let size = size
+                                                .and_then(|size| Ok(serde_json::from_value(size)?));
+                                            if let Ok((min_width, min_height)) = size {
+                                                let _ = proxy.send_message(Message::Window(
+                                                    id,
+                                                    WindowMessage::SetMinSize {
+                                                        min_width,
+                                                        min_height,
+                                                    },
+                                                ));
+                                            }
+                                        },
+                                    );
+                                }
                                 WindowMessage::SetMaxSize {
                                     max_width,
                                     max_height,
@@ -267,6 +539,8 @@ impl App for InnerApplication {
                                 WindowMessage::SetPosition { x, y } => {
                                     window.move_(x as i32, y as i32);
                                 }
+                                WindowMessage::Center => center(window),
+                                WindowMessage::Snap(region) => snap(window, region),
                                 WindowMessage::SetFullscreen(fullscreen) => {
                                     if fullscreen {
                                         window.fullscreen();
@@ -274,14 +548,234 @@ impl App for InnerApplication {
                                         window.unfullscreen();
                                     }
                                 }
+                                WindowMessage::ToggleFullscreen => {
+                                    if is_fullscreen(window) {
+                                        window.unfullscreen();
+                                    } else {
+                                        window.fullscreen();
+                                    }
+                                }
+                                WindowMessage::IsFullscreen(responder) => {
+                                    let _ = responder
+                                        .send(WindowResponse::IsFullscreen(is_fullscreen(window)));
+                                }
                                 WindowMessage::SetIcon(icon) => {
                                     if let Ok(icon) = load_icon(icon) {
                                         window.set_icon(Some(&icon));
                                     }
                                 }
+                                WindowMessage::SetCursorImage {
+                                    rgba,
+                                    width,
+                                    height,
+                                    hotspot_x,
+                                    hotspot_y,
+                                } => {
+                                    let _ = webview.set_cursor_image(
+                                        rgba, width, height, hotspot_x, hotspot_y,
+                                    );
+                                }
+                                WindowMessage::ResetCursor => {
+                                    let _ = webview.reset_cursor();
+                                }
                                 WindowMessage::EvaluationScript(script) => {
                                     let _ = webview.dispatch_script(&script);
                                 }
+                                WindowMessage::EvaluationScriptWithCallback(script, callback) => {
+                                    // Keep the order with the scripts dispatched before.
+                                    let _ = webview.evaluate_script();
+                                    let _ =
+                                        webview.evaluate_script_with_callback(&script, callback.0);
+                                }
+                                WindowMessage::LoadUrl(url) => {
+                                    let _ = webview.load_url(&url);
+                                }
+                                WindowMessage::Reload { bypass_cache } => {
+                                    let _ = webview.reload(bypass_cache);
+                                }
+                                WindowMessage::SetUserAgent(user_agent) => {
+                                    let _ = webview.set_user_agent(&user_agent);
+                                }
+                                WindowMessage::ReloadWithUserAgent {
+                                    user_agent,
+                                    restore,
+                                } => {
+                                    let _ = webview.reload_with_user_agent(&user_agent, restore);
+                                }
+                                WindowMessage::SetZoomLevel(zoom) => {
+                                    let _ = webview.set_zoom_level(zoom);
+                                }
+                                WindowMessage::ZoomLevel(responder) => {
+                                    let _ = responder
+                                        .send(WindowResponse::ZoomLevel(webview.zoom_level()));
+                                }
+                                WindowMessage::GoBack => {
+                                    let _ = webview.go_back();
+                                }
+                                WindowMessage::GoForward => {
+                                    let _ = webview.go_forward();
+                                }
+                                WindowMessage::CanGoBack(responder) => {
+                                    let _ = responder
+                                        .send(WindowResponse::CanGoBack(webview.can_go_back()));
+                                }
+                                WindowMessage::CanGoForward(responder) => {
+                                    let _ = responder.send(WindowResponse::CanGoForward(
+                                        webview.can_go_forward(),
+                                    ));
+                                }
+                                WindowMessage::Url(responder) => {
+                                    let _ = responder.send(WindowResponse::Url(webview.url()));
+                                }
+                                WindowMessage::Title(responder) => {
+                                    let _ = responder.send(WindowResponse::Title(webview.title()));
+                                }
+                                WindowMessage::LoadBytes {
+                                    bytes,
+                                    mime_type,
+                                    base_url,
+                                } => {
+                                    let _ =
+                                        webview.load_bytes(&bytes, &mime_type, base_url.as_deref());
+                                }
+                                WindowMessage::LoadHtml { html, base_url } => {
+                                    let _ = webview.load_html(&html, base_url.as_deref());
+                                }
+                                WindowMessage::WaitForLoad(responder) => {
+                                    webview.on_next_load(move || {
+                                        let _ = responder.send(WindowResponse::LoadFinished);
+                                    });
+                                }
+                                WindowMessage::SetOpaqueRegion(region) => {
+                                    // Safety: The key is only ever set to this type
+                                    unsafe { window.set_data(OPAQUE_REGION_KEY, region) };
+                                    window.queue_draw();
+                                }
+                                WindowMessage::SetEditable(editable) => {
+                                    let _ = webview.set_editable(editable);
+                                }
+                                WindowMessage::SetEnableDragRegion(enabled) => {
+                                    let _ = webview.set_enable_drag_region(enabled);
+                                }
+                                WindowMessage::Find {
+                                    text,
+                                    case_sensitive,
+                                } => {
+                                    let _ = webview.find(&text, case_sensitive);
+                                }
+                                WindowMessage::FindNext => {
+                                    let _ = webview.find_next();
+                                }
+                                WindowMessage::FindPrevious => {
+                                    let _ = webview.find_previous();
+                                }
+                                WindowMessage::StopFind => {
+                                    let _ = webview.stop_find();
+                                }
+                                WindowMessage::SetSelectable(selectable) => {
+                                    let _ = webview.set_selectable(selectable);
+                                }
+                                WindowMessage::ShowMessageDialog {
+                                    title,
+                                    message,
+                                    buttons,
+                                    responder,
+                                } => {
+                                    show_message_dialog(
+                                        window, &title, &message, buttons, responder,
+                                    );
+                                }
+                                WindowMessage::OpenFileDialog { options, responder } => {
+                                    open_file_dialog(window, &options, responder);
+                                }
+                                WindowMessage::SaveFileDialog { options, responder } => {
+                                    save_file_dialog(window, &options, responder);
+                                }
+                                WindowMessage::BackForwardList(responder) => {
+                                    let _ = responder.send(WindowResponse::BackForwardList(
+                                        webview.back_forward_list(),
+                                    ));
+                                }
+                                WindowMessage::ClearHistory => {
+                                    let _ = webview.clear_history();
+                                }
+                                WindowMessage::SessionState(responder) => {
+                                    let _ = responder.send(WindowResponse::SessionState(
+                                        webview.session_state(),
+                                    ));
+                                }
+                                WindowMessage::RestoreSessionState(state) => {
+                                    let _ = webview.restore_session_state(&state);
+                                }
+                                WindowMessage::Cookies { url, responder } => {
+                                    let _ = webview.cookies(&url, move |cookies| {
+                                        let _ = responder.send(WindowResponse::Cookies(cookies));
+                                    });
+                                }
+                                WindowMessage::SetCookie(cookie) => {
+                                    let _ = webview.set_cookie(&cookie);
+                                }
+                                WindowMessage::ClearAllCookies => {
+                                    let _ = webview.clear_all_cookies();
+                                }
+                                WindowMessage::ClearCache => {
+                                    let _ = webview.clear_cache();
+                                }
+                                WindowMessage::ClearStorage(types) => {
+                                    let _ = webview.clear_storage(types);
+                                }
+                                WindowMessage::IsDevtoolsOpen(responder) => {
+                                    let _ = responder.send(WindowResponse::IsDevtoolsOpen(
+                                        webview.is_devtools_open(),
+                                    ));
+                                }
+                                WindowMessage::OpenDevtools(attachment) => {
+                                    let _ = match attachment {
+                                        Some(attachment) => webview.open_devtools_with(attachment),
+                                        None => webview.open_devtools(),
+                                    };
+                                }
+                                WindowMessage::DevtoolsAttachedHeight(responder) => {
+                                    let _ = responder.send(WindowResponse::DevtoolsAttachedHeight(
+                                        webview.devtools_attached_height(),
+                                    ));
+                                }
+                                WindowMessage::CloseDevtools => {
+                                    let _ = webview.close_devtools();
+                                }
+                                WindowMessage::SetProgressBar(progress, state) => {
+                                    set_progress_bar(&self.app, progress, state);
+                                }
+                                // Rejected by `WindowProxy::set_taskbar_icon`.
+                                WindowMessage::SetTaskbarIcon(_) => {}
+                                WindowMessage::SetClipboardText(text) => {
+                                    Clipboard::get(&gdk::SELECTION_CLIPBOARD).set_text(&text);
+                                }
+                                WindowMessage::ClipboardText(responder) => {
+                                    // The text is received asynchronously, keep processing the
+                                    // other messages in the meantime.
+                                    Clipboard::get(&gdk::SELECTION_CLIPBOARD).request_text(
+                                        move |_, text| {
+                                            let _ = responder.send(WindowResponse::ClipboardText(
+                                                text.map(String::from),
+                                            ));
+                                        },
+                                    );
+                                }
+                                WindowMessage::SetWindowButtonsPosition(position) => {
+                                    if let Some(settings) = window.get_settings() {
+                                        settings.set_property_gtk_decoration_layout(Some(
+                                            match position {
+                                                WindowButtonsPosition::Left => {
+                                                    "close,minimize,maximize:menu"
+                                                }
+                                                WindowButtonsPosition::Right => {
+                                                    "menu:minimize,maximize,close"
+                                                }
+                                            },
+                                        ));
+                                    }
+                                }
                             }
                         }
                     }
@@ -307,9 +801,785 @@ fn load_icon(icon: Icon) -> Result<gdk_pixbuf::Pixbuf> {
     ))
 }
 
-fn _create_window(app: &GtkApp, attributes: InnerWindowAttributes) -> Result<ApplicationWindow> {
+fn show_message_dialog(
+    window: &ApplicationWindow,
+    title: &str,
+    message: &str,
+    buttons: MessageDialogButtons,
+    responder: Sender<WindowResponse>,
+) {
+    let dialog = MessageDialog::new(
+        Some(window),
+        DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+        MessageType::Other,
+        ButtonsType::None,
+        message,
+    );
+    dialog.set_title(title);
+
+    let choices: &[(&str, MessageDialogResult)] = match buttons {
+        MessageDialogButtons::Ok => &[("_OK", MessageDialogResult::Ok)],
+        MessageDialogButtons::OkCancel => &[
+            ("_Cancel", MessageDialogResult::Cancel),
+            ("_OK", MessageDialogResult::Ok),
+        ],
+        MessageDialogButtons::YesNo => &[
+            ("_No", MessageDialogResult::No),
+            ("_Yes", MessageDialogResult::Yes),
+        ],
+        MessageDialogButtons::YesNoCancel => &[
+            ("_Cancel", MessageDialogResult::Cancel),
+            ("_No", MessageDialogResult::No),
+            ("_Yes", MessageDialogResult::Yes),
+        ],
+    };
+    for (i, (label, _)) in choices.iter().enumerate() {
+        dialog.add_button(label, ResponseType::Other(i as u16));
+    }
+
+    let choices = choices.to_vec();
+    dialog.connect_response(move |dialog, response| {
+        let result = match response {
+            ResponseType::Other(i) => choices[i as usize].1,
+            _ => buttons.dismissed(),
+        };
+        let _ = responder.send(WindowResponse::MessageDialog(result));
+        // Safety: The dialog is owned by this function and isn't used after the response.
+        unsafe { dialog.destroy() };
+    });
+    dialog.show_all();
+}
+
+fn file_chooser(
+    window: &ApplicationWindow,
+    action: FileChooserAction,
+    options: &FileDialogOptions,
+) -> FileChooserNative {
+    let dialog = FileChooserNative::new(options.title.as_deref(), Some(window), action, None, None);
+    dialog.set_modal(true);
+    if let Some(directory) = &options.directory {
+        dialog.set_current_folder(directory);
+    }
+    for filter in &options.filters {
+        let file_filter = gtk::FileFilter::new();
+        file_filter.set_name(Some(&filter.name));
+        for extension in &filter.extensions {
+            file_filter.add_pattern(&format!("*.{}", extension));
+        }
+        dialog.add_filter(&file_filter);
+    }
+    dialog
+}
+
+// Shows the dialog without blocking the main loop. Nothing else references a native dialog, so it's
+// kept alive until it's answered.
+fn show_file_chooser<F>(dialog: FileChooserNative, answer: F)
+where
+    F: Fn(&FileChooserNative, bool) + 'static,
+{
+    let kept = RefCell::new(Some(dialog.clone()));
+    dialog.connect_response(move |dialog, response| {
+        answer(dialog, response == ResponseType::Accept);
+        dialog.destroy();
+        kept.borrow_mut().take();
+    });
+    dialog.show();
+}
+
+fn open_file_dialog(
+    window: &ApplicationWindow,
+    options: &FileDialogOptions,
+    responder: Sender<WindowResponse>,
+) {
+    let dialog = file_chooser(window, FileChooserAction::Open, options);
+    dialog.set_select_multiple(options.multiple);
+
+    show_file_chooser(dialog, move |dialog, accepted| {
+        let paths = if accepted {
+            dialog.get_filenames()
+        } else {
+            Vec::new()
+        };
+        let _ = responder.send(WindowResponse::OpenFileDialog(paths));
+    });
+}
+
+fn save_file_dialog(
+    window: &ApplicationWindow,
+    options: &FileDialogOptions,
+    responder: Sender<WindowResponse>,
+) {
+    let dialog = file_chooser(window, FileChooserAction::Save, options);
+    dialog.set_do_overwrite_confirmation(true);
+    if let Some(file_name) = &options.file_name {
+        dialog.set_current_name(file_name);
+    }
+
+    show_file_chooser(dialog, move |dialog, accepted| {
+        let path = if accepted {
+            dialog.get_filename()
+        } else {
+            None
+        };
+        let _ = responder.send(WindowResponse::SaveFileDialog(path));
+    });
+}
+
+// Best-effort implementation of the Unity launcher API. The entry is matched by docks against
+// the `.desktop` file named after the program.
+fn set_progress_bar(app: &GtkApp, progress: f64, state: ProgressBarState) {
+    let connection = match app.get_dbus_connection() {
+        Some(connection) => connection,
+        None => return,
+    };
+    let app_uri = match glib::get_prgname() {
+        Some(name) => format!("application://{}.desktop", name),
+        None => return,
+    };
+
+    let properties = VariantDict::new(None);
+    properties.insert("progress", &progress.clamp(0., 1.));
+    properties.insert("progress-visible", &(state != ProgressBarState::None));
+    // glib 0.10 can't convert tuples, so the `(sa{sv})` parameters are built by hand.
+    let children = [app_uri.to_variant(), properties.end()];
+    let children: Vec<*mut glib::glib_sys::GVariant> = children
+        .iter()
+        .map(|child| child.to_glib_none().0)
+        .collect();
+    // Safety: The children outlive the call, and the floating tuple is sunk by `from_glib_none`
+    let parameters: Variant = unsafe {
+        from_glib_none(glib::glib_sys::g_variant_new_tuple(
+            children.as_ptr(),
+            children.len(),
+        ))
+    };
+    let _ = connection.emit_signal(
+        None,
+        "/com/canonical/unity/launcherentry/wry",
+        "com.canonical.Unity.LauncherEntry",
+        "Update",
+        Some(&parameters),
+    );
+}
+
+// Shrinks the initial size of the window so it fits in the work area of the monitor it opens on.
+fn fit_to_screen(attributes: &mut InnerWindowAttributes) {
+    let display = match gdk::Display::get_default() {
+        Some(display) => display,
+        None => return,
+    };
+    let monitor = match (attributes.x, attributes.y) {
+        (Some(x), Some(y)) => display.get_monitor_at_point(x as i32, y as i32),
+        _ => display
+            .get_primary_monitor()
+            .or_else(|| display.get_monitor(0)),
+    };
+    if let Some(monitor) = monitor {
+        let workarea = monitor.get_workarea();
+        attributes.width = attributes.width.min(workarea.width as f64);
+        attributes.height = attributes.height.min(workarea.height as f64);
+    }
+}
+
+const CLOSING_KEY: &str = "wry-closing";
+
+// Closing the window from the application isn't asked to the close requested handler.
+fn close(window: &ApplicationWindow) {
+    // Safety: The key is only ever set to this type
+    unsafe { window.set_data(CLOSING_KEY, ()) };
+    window.close();
+}
+
+// Connected before the other handlers of the delete event, which aren't run when it's vetoed.
+fn connect_close_requested_handler(
+    window: &ApplicationWindow,
+    handler: WindowCloseRequestedHandler,
+    proxy: InnerApplicationProxy,
+) {
+    let window_id = window.get_id();
+    window.connect_delete_event(move |window, _| {
+        // Safety: The key is only ever set to this type
+        if unsafe { window.get_data::<()>(CLOSING_KEY) }.is_some() {
+            return Inhibit(false);
+        }
+        let proxy = WindowProxy::new(
+            ApplicationProxy {
+                inner: proxy.clone(),
+            },
+            window_id,
+        );
+        Inhibit(handler(proxy))
+    });
+}
+
+// In milliseconds.
+const GEOMETRY_EVENTS_INTERVAL: u32 = 100;
+
+// Forwards the events of the window to the listeners of the application.
+fn connect_window_events(window: &ApplicationWindow, event_channel: EventSender) {
+    let window_id = window.get_id();
+
+    let events = event_channel.clone();
+    window.connect_delete_event(move |_, _| {
+        let _ = events.send(WryEvent::WindowEvent {
+            window_id,
+            event: WindowEvent::CloseRequested,
+        });
+        Inhibit(false)
+    });
+
+    // Crossings between the window and its children, like the WebView, aren't the cursor
+    // entering or leaving the window.
+    window.add_events(gdk::EventMask::ENTER_NOTIFY_MASK | gdk::EventMask::LEAVE_NOTIFY_MASK);
+    let events = event_channel.clone();
+    window.connect_enter_notify_event(move |_, event| {
+        if event.get_detail() != gdk::NotifyType::Inferior {
+            let _ = events.send(WryEvent::WindowEvent {
+                window_id,
+                event: WindowEvent::CursorEntered,
+            });
+        }
+        Inhibit(false)
+    });
+    let events = event_channel.clone();
+    window.connect_leave_notify_event(move |_, event| {
+        if event.get_detail() != gdk::NotifyType::Inferior {
+            let _ = events.send(WryEvent::WindowEvent {
+                window_id,
+                event: WindowEvent::CursorLeft,
+            });
+        }
+        Inhibit(false)
+    });
+
+    // The initially focused window gets a focus in event as well, once it's shown.
+    let events = event_channel.clone();
+    window.connect_focus_in_event(move |window, _| {
+        // The attention of the user was got.
+        window.set_urgency_hint(false);
+        let _ = events.send(WryEvent::WindowEvent {
+            window_id,
+            event: WindowEvent::Focused(true),
+        });
+        Inhibit(false)
+    });
+    let events = event_channel.clone();
+    window.connect_focus_out_event(move |_, _| {
+        let _ = events.send(WryEvent::WindowEvent {
+            window_id,
+            event: WindowEvent::Focused(false),
+        });
+        Inhibit(false)
+    });
+
+    // Configure events come in bursts while the window is dragged or resized, so only report the
+    // latest geometry at most once per interval.
+    let events = event_channel.clone();
+    let pending = Rc::new(Cell::new(None));
+    let reported = Rc::new(Cell::new((None, None)));
+    window.connect_configure_event(move |_, event| {
+        if pending
+            .replace(Some((event.get_size(), event.get_position())))
+            .is_none()
+        {
+            let events = events.clone();
+            let pending = pending.clone();
+            let reported = reported.clone();
+            glib::timeout_add_local(GEOMETRY_EVENTS_INTERVAL, move || {
+                if let Some((size, position)) = pending.take() {
+                    let (old_size, old_position) = reported.replace((Some(size), Some(position)));
+                    if old_size != Some(size) {
+                        let _ = events.send(WryEvent::WindowEvent {
+                            window_id,
+                            event: WindowEvent::Resized {
+                                width: size.0,
+                                height: size.1,
+                            },
+                        });
+                    }
+                    if old_position != Some(position) {
+                        let _ = events.send(WryEvent::WindowEvent {
+                            window_id,
+                            event: WindowEvent::Moved {
+                                x: position.0,
+                                y: position.1,
+                            },
+                        });
+                    }
+                }
+                Continue(false)
+            });
+        }
+        false
+    });
+
+    let events = event_channel.clone();
+    let monitor = Cell::new(None);
+    window.connect_configure_event(move |window, event| {
+        let (x, y) = event.get_position();
+        let (width, height) = event.get_size();
+        let center = (x + width as i32 / 2, y + height as i32 / 2);
+        if let Some(new) = monitor_index(&window.get_display(), center) {
+            if let Some(old) = monitor.replace(Some(new)) {
+                if old != new {
+                    let _ = events.send(WryEvent::WindowEvent {
+                        window_id,
+                        event: WindowEvent::MonitorChanged(new),
+                    });
+                }
+            }
+        }
+        false
+    });
+
+    let events = event_channel.clone();
+    window.connect_window_state_event(move |_, event| {
+        if event
+            .get_changed_mask()
+            .contains(gdk::WindowState::FULLSCREEN)
+        {
+            let fullscreen = event
+                .get_new_window_state()
+                .contains(gdk::WindowState::FULLSCREEN);
+            let _ = events.send(WryEvent::WindowEvent {
+                window_id,
+                event: WindowEvent::Fullscreen(fullscreen),
+            });
+        }
+        Inhibit(false)
+    });
+
+    // GTK only notifies the new scale factor, so keep track of the previous one.
+    let scale_factor = Cell::new(window.get_scale_factor());
+    window.connect_property_scale_factor_notify(move |window| {
+        let new = window.get_scale_factor();
+        let old = scale_factor.replace(new);
+        if old != new {
+            let _ = event_channel.send(WryEvent::WindowEvent {
+                window_id,
+                event: WindowEvent::DpiChanged {
+                    old: old as f64,
+                    new: new as f64,
+                },
+            });
+        }
+    });
+}
+
+fn connect_keyboard_events(window: &ApplicationWindow, event_channel: EventSender) {
+    let window_id = window.get_id();
+
+    // Handlers connected after the default one only run when no handler stopped the key, so
+    // they find out whether it was handled before `event-after` reports it.
+    let handled = Rc::new(Cell::new(false));
+    let before = handled.clone();
+    window.connect_key_press_event(move |_, _| {
+        before.set(true);
+        Inhibit(false)
+    });
+    let before = handled.clone();
+    window.connect_key_release_event(move |_, _| {
+        before.set(true);
+        Inhibit(false)
+    });
+    for signal in ["key-press-event", "key-release-event"].iter().copied() {
+        let after = handled.clone();
+        let _ = window.connect_local(signal, true, move |_| {
+            after.set(false);
+            Some(false.to_value())
+        });
+    }
+
+    window.connect_event_after(move |_, event| {
+        let pressed = match event.get_event_type() {
+            gdk::EventType::KeyPress => true,
+            gdk::EventType::KeyRelease => false,
+            _ => return,
+        };
+        if let Ok(key) = event.clone().downcast::<gdk::EventKey>() {
+            let (key, keyval, keycode, modifiers, handled) = (
+                key_name(&key),
+                *key.get_keyval(),
+                key.get_hardware_keycode(),
+                modifiers_state(key.get_state()),
+                handled.get(),
+            );
+            let _ = event_channel.send(WryEvent::WindowEvent {
+                window_id,
+                event: if pressed {
+                    WindowEvent::KeyDown {
+                        key,
+                        keyval,
+                        keycode,
+                        modifiers,
+                        handled,
+                    }
+                } else {
+                    WindowEvent::KeyUp {
+                        key,
+                        keyval,
+                        keycode,
+                        modifiers,
+                        handled,
+                    }
+                },
+            });
+        }
+    });
+}
+
+fn key_name(key: &gdk::EventKey) -> String {
+    key.get_keyval()
+        .name()
+        .map(|name| name.to_string())
+        .unwrap_or_default()
+}
+
+fn modifiers_state(state: gdk::ModifierType) -> ModifiersState {
+    let mut modifiers = ModifiersState::empty();
+    modifiers.set(
+        ModifiersState::SHIFT,
+        state.contains(gdk::ModifierType::SHIFT_MASK),
+    );
+    modifiers.set(
+        ModifiersState::CTRL,
+        state.contains(gdk::ModifierType::CONTROL_MASK),
+    );
+    modifiers.set(
+        ModifiersState::ALT,
+        state.contains(gdk::ModifierType::MOD1_MASK),
+    );
+    modifiers.set(
+        ModifiersState::SUPER,
+        state.contains(gdk::ModifierType::SUPER_MASK),
+    );
+    modifiers
+}
+
+struct IdleState {
+    last_activity: Cell<Instant>,
+    idle: Cell<bool>,
+}
+
+fn connect_idle_events(window: &ApplicationWindow, timeout: Duration, event_channel: EventSender) {
+    let state = Rc::new(IdleState {
+        last_activity: Cell::new(Instant::now()),
+        idle: Cell::new(false),
+    });
+    schedule_idle_check(
+        window,
+        timeout,
+        timeout,
+        state.clone(),
+        event_channel.clone(),
+    );
+
+    // Events are captured by the window before its children, like the WebView, handle them.
+    let window_ = window.downgrade();
+    let _ = window.connect_local("captured-event", false, move |values| {
+        let event = values
+            .get(1)
+            .and_then(|value| value.get::<gdk::Event>().ok().flatten());
+        if let Some(event) = event {
+            match event.get_event_type() {
+                gdk::EventType::MotionNotify
+                | gdk::EventType::ButtonPress
+                | gdk::EventType::Scroll
+                | gdk::EventType::KeyPress => {
+                    state.last_activity.set(Instant::now());
+                    if state.idle.replace(false) {
+                        if let Some(window) = window_.upgrade() {
+                            let _ = event_channel.send(WryEvent::WindowEvent {
+                                window_id: window.get_id(),
+                                event: WindowEvent::Active,
+                            });
+                            schedule_idle_check(
+                                &window,
+                                timeout,
+                                timeout,
+                                state.clone(),
+                                event_channel.clone(),
+                            );
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some(false.to_value())
+    });
+}
+
+// Checks for inactivity once the timeout may have elapsed, instead of restarting a timer on every
+// event.
+fn schedule_idle_check(
+    window: &ApplicationWindow,
+    timeout: Duration,
+    delay: Duration,
+    state: Rc<IdleState>,
+    event_channel: EventSender,
+) {
+    let window = window.downgrade();
+    glib::timeout_add_local(delay.as_millis() as u32, move || {
+        if let Some(window) = window.upgrade() {
+            let elapsed = state.last_activity.get().elapsed();
+            if elapsed >= timeout {
+                state.idle.set(true);
+                let _ = event_channel.send(WryEvent::WindowEvent {
+                    window_id: window.get_id(),
+                    event: WindowEvent::Idle,
+                });
+            } else {
+                schedule_idle_check(
+                    &window,
+                    timeout,
+                    timeout - elapsed,
+                    state.clone(),
+                    event_channel.clone(),
+                );
+            }
+        }
+        Continue(false)
+    });
+}
+
+const OPAQUE_REGION_KEY: &str = "wry-opaque-region";
+
+fn rounded_rect_path(cr: &Context, rect: &RoundedRect) {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    let radius = rect
+        .radius
+        .min(rect.width / 2.)
+        .min(rect.height / 2.)
+        .max(0.);
+    let (left, top) = (rect.x, rect.y);
+    let (right, bottom) = (rect.x + rect.width, rect.y + rect.height);
+    cr.new_sub_path();
+    cr.arc(right - radius, top + radius, radius, -FRAC_PI_2, 0.);
+    cr.arc(right - radius, bottom - radius, radius, 0., FRAC_PI_2);
+    cr.arc(left + radius, bottom - radius, radius, FRAC_PI_2, PI);
+    cr.arc(left + radius, top + radius, radius, PI, 3. * FRAC_PI_2);
+    cr.close_path();
+}
+
+const FUNCTIONS_KEY: &str = "wry-functions";
+
+// GTK has no API for the minimize and maximize buttons, the window manager is asked through the
+// functions hint of the GDK window instead. The hint is applied again when the window is realized.
+// It's only set once a function is disabled, until then the window manager keeps its defaults.
+// `RESIZE` follows whether the window is resizable, which GTK already tells the window manager.
+fn set_functions(window: &ApplicationWindow, function: gdk::WMFunction, enabled: bool) {
+    // Safety: The key is only ever set to this type
+    unsafe {
+        let functions = window.get_data::<gdk::WMFunction>(FUNCTIONS_KEY).copied();
+        if functions.is_none() && (enabled || function == gdk::WMFunction::RESIZE) {
+            return;
+        }
+        let mut functions = functions.unwrap_or_else(|| {
+            let mut functions = gdk::WMFunction::MOVE
+                | gdk::WMFunction::MINIMIZE
+                | gdk::WMFunction::MAXIMIZE
+                | gdk::WMFunction::CLOSE;
+            functions.set(gdk::WMFunction::RESIZE, window.get_resizable());
+            functions
+        });
+        functions.set(function, enabled);
+        window.set_data(FUNCTIONS_KEY, functions);
+        if let Some(gdk_window) = window.get_window() {
+            gdk_window.set_functions(functions);
+        }
+    }
+}
+
+const NO_SHADOW_KEY: &str = "wry-no-shadow";
+
+// The shadow of client-side decorations is part of the theme, override it for this window only.
+fn set_shadow(window: &ApplicationWindow, shadow: bool) {
+    let context = window.get_style_context();
+    // Safety: The key is only ever set to this type
+    unsafe {
+        if shadow {
+            if let Some(provider) = window.steal_data::<CssProvider>(NO_SHADOW_KEY) {
+                context.remove_provider(&provider);
+            }
+        } else if window.get_data::<CssProvider>(NO_SHADOW_KEY).is_none() {
+            let provider = CssProvider::new();
+            let _ = provider.load_from_data(b"decoration { box-shadow: none; margin: 0; }");
+            context.add_provider(&provider, STYLE_PROVIDER_PRIORITY_APPLICATION);
+            window.set_data(NO_SHADOW_KEY, provider);
+        }
+    }
+}
+
+const ALWAYS_ON_TOP_KEY: &str = "wry-always-on-top";
+
+#[derive(Default)]
+struct AlwaysOnTop {
+    requested: Cell<bool>,
+    applied: Cell<bool>,
+    yields_to_fullscreen: Cell<bool>,
+    tracking: Cell<bool>,
+}
+
+fn always_on_top_state(window: &ApplicationWindow) -> Rc<AlwaysOnTop> {
+    // Safety: The key is only ever set to this type
+    unsafe {
+        if let Some(state) = window.get_data::<Rc<AlwaysOnTop>>(ALWAYS_ON_TOP_KEY) {
+            return state.clone();
+        }
+        let state = Rc::new(AlwaysOnTop::default());
+        window.set_data(ALWAYS_ON_TOP_KEY, state.clone());
+        state
+    }
+}
+
+fn set_always_on_top(window: &ApplicationWindow, always_on_top: bool) {
+    // Both hints at once would fight each other.
+    if always_on_top {
+        window.set_keep_below(false);
+    }
+    let state = always_on_top_state(window);
+    state.requested.set(always_on_top);
+    // Always apply an explicit request, in case the window manager changed it in the meantime.
+    state.applied.set(!always_on_top);
+    update_always_on_top(window, &state);
+}
+
+fn set_always_on_bottom(window: &ApplicationWindow, always_on_bottom: bool) {
+    if always_on_bottom {
+        set_always_on_top(window, false);
+    }
+    window.set_keep_below(always_on_bottom);
+}
+
+fn set_always_on_top_yields_to_fullscreen(window: &ApplicationWindow, yields: bool) {
+    let state = always_on_top_state(window);
+    state.yields_to_fullscreen.set(yields);
+    update_always_on_top(window, &state);
+
+    // GDK doesn't signal state changes of other applications' windows, but another window
+    // becoming active takes the focus away from this one.
+    if yields && !state.tracking.replace(true) {
+        window.connect_focus_in_event(|window, _| {
+            update_always_on_top_later(window);
+            Inhibit(false)
+        });
+        window.connect_focus_out_event(|window, _| {
+            update_always_on_top_later(window);
+            Inhibit(false)
+        });
+        window.connect_window_state_event(|window, _| {
+            update_always_on_top_later(window);
+            Inhibit(false)
+        });
+    }
+}
+
+// The active window of the screen is only updated after the focus changed.
+fn update_always_on_top_later(window: &ApplicationWindow) {
+    let window = window.downgrade();
+    glib::idle_add_local(move || {
+        if let Some(window) = window.upgrade() {
+            let state = always_on_top_state(&window);
+            if state.yields_to_fullscreen.get() {
+                update_always_on_top(&window, &state);
+            }
+        }
+        Continue(false)
+    });
+}
+
+fn update_always_on_top(window: &ApplicationWindow, state: &AlwaysOnTop) {
+    let suspended = state.yields_to_fullscreen.get() && is_fullscreen_window_active(window);
+    let keep_above = state.requested.get() && !suspended;
+    if state.applied.replace(keep_above) != keep_above {
+        window.set_keep_above(keep_above);
+    }
+}
+
+// The index of the monitor showing the given point of the screen, or the closest one to it.
+fn monitor_index(display: &gdk::Display, (x, y): (i32, i32)) -> Option<usize> {
+    let monitor = display.get_monitor_at_point(x, y)?;
+    (0..display.get_n_monitors())
+        .position(|index| display.get_monitor(index).as_ref() == Some(&monitor))
+}
+
+// Centers the window on the monitor showing the center of the window.
+fn center(window: &ApplicationWindow) {
+    let (x, y) = window.get_position();
+    let (width, height) = window.get_size();
+    let monitor = window
+        .get_display()
+        .get_monitor_at_point(x + width / 2, y + height / 2);
+    if let Some(monitor) = monitor {
+        let geometry = monitor.get_geometry();
+        window.move_(
+            geometry.x + (geometry.width - width) / 2,
+            geometry.y + (geometry.height - height) / 2,
+        );
+    }
+}
+
+fn snap(window: &ApplicationWindow, region: SnapRegion) {
+    let (x, y) = window.get_position();
+    let (width, height) = window.get_size();
+    let monitor = window
+        .get_display()
+        .get_monitor_at_point(x + width / 2, y + height / 2);
+    if let Some(monitor) = monitor {
+        let workarea = monitor.get_workarea();
+        let (x, y, width, height) = region.rect(
+            workarea.x as f64,
+            workarea.y as f64,
+            workarea.width as f64,
+            workarea.height as f64,
+        );
+        window.unmaximize();
+        // Both are applied with the next configure request.
+        window.resize(width as i32, height as i32);
+        window.move_(x as i32, y as i32);
+    }
+}
+
+fn is_fullscreen(window: &ApplicationWindow) -> bool {
+    window
+        .get_window()
+        .is_some_and(|window| window.get_state().contains(gdk::WindowState::FULLSCREEN))
+}
+
+/// Whether another window is fullscreen and focused on the same monitor as `window`.
+// GDK has no replacement for the active window of the screen.
+#[allow(deprecated)]
+fn is_fullscreen_window_active(window: &ApplicationWindow) -> bool {
+    let own = match window.get_window() {
+        Some(own) => own,
+        None => return false,
+    };
+    let active = match window
+        .get_screen()
+        .and_then(|screen| screen.get_active_window())
+    {
+        Some(active) => active,
+        None => return false,
+    };
+    if active == own || !active.get_state().contains(gdk::WindowState::FULLSCREEN) {
+        return false;
+    }
+    let display = own.get_display();
+    display.get_monitor_at_window(&active) == display.get_monitor_at_window(&own)
+}
+
+fn _create_window(
+    app: &GtkApp,
+    mut attributes: InnerWindowAttributes,
+) -> Result<ApplicationWindow> {
     let window = ApplicationWindow::new(app);
 
+    if attributes.fit_to_screen {
+        fit_to_screen(&mut attributes);
+    }
+
     window.set_geometry_hints::<ApplicationWindow>(
         None,
         Some(&gdk::Geometry {
@@ -349,17 +1619,49 @@ fn _create_window(app: &GtkApp, attributes: InnerWindowAttributes) -> Result<App
             }
         }
 
-        window.connect_draw(|_, cr| {
+        window.connect_draw(|window, cr| {
             cr.set_source_rgba(0., 0., 0., 0.);
             cr.set_operator(Operator::Source);
             cr.paint();
             cr.set_operator(Operator::Over);
+            // The clip stays in place while the children, including the WebView, are drawn.
+            // Safety: The key is only ever set to this type
+            if let Some(Some(region)) =
+                unsafe { window.get_data::<Option<Vec<RoundedRect>>>(OPAQUE_REGION_KEY) }
+            {
+                for rect in region {
+                    rounded_rect_path(cr, rect);
+                }
+                cr.clip();
+            }
+            Inhibit(false)
+        });
+        window.set_app_paintable(true);
+    } else if let Some((red, green, blue, alpha)) = attributes.background_color {
+        window.connect_draw(move |_, cr| {
+            cr.set_source_rgba(
+                red as f64 / 255.,
+                green as f64 / 255.,
+                blue as f64 / 255.,
+                alpha as f64 / 255.,
+            );
+            cr.paint();
             Inhibit(false)
         });
         window.set_app_paintable(true);
     }
 
     window.set_skip_taskbar_hint(attributes.skip_taskbar);
+    window.set_focus_on_map(attributes.focused);
+    window.set_type_hint(match attributes.window_type {
+        WindowTypeHint::Normal => gdk::WindowTypeHint::Normal,
+        WindowTypeHint::Dialog => gdk::WindowTypeHint::Dialog,
+        WindowTypeHint::Utility => gdk::WindowTypeHint::Utility,
+        WindowTypeHint::Dock => gdk::WindowTypeHint::Dock,
+        WindowTypeHint::Tooltip => gdk::WindowTypeHint::Tooltip,
+        WindowTypeHint::Splashscreen => gdk::WindowTypeHint::Splashscreen,
+        WindowTypeHint::Notification => gdk::WindowTypeHint::Notification,
+    });
     window.set_resizable(attributes.resizable);
     window.set_title(&attributes.title);
     if attributes.maximized {
@@ -367,15 +1669,42 @@ fn _create_window(app: &GtkApp, attributes: InnerWindowAttributes) -> Result<App
     }
     window.set_visible(attributes.visible);
     window.set_decorated(attributes.decorations);
-    window.set_keep_above(attributes.always_on_top);
+    set_always_on_top(&window, attributes.always_on_top);
+    if attributes.always_on_bottom && !attributes.always_on_top {
+        set_always_on_bottom(&window, true);
+    }
+    window.set_deletable(attributes.closable);
+    set_functions(&window, gdk::WMFunction::MINIMIZE, attributes.minimizable);
+    set_functions(&window, gdk::WMFunction::MAXIMIZE, attributes.maximizable);
+    set_functions(&window, gdk::WMFunction::CLOSE, attributes.closable);
+    window.connect_realize(|window| {
+        // Safety: The key is only ever set to this type
+        if let Some(functions) = unsafe { window.get_data::<gdk::WMFunction>(FUNCTIONS_KEY) } {
+            if let Some(gdk_window) = window.get_window() {
+                gdk_window.set_functions(*functions);
+            }
+        }
+    });
 
     match (attributes.x, attributes.y) {
         (Some(x), Some(y)) => window.move_(x as i32, y as i32),
         _ => {}
     }
 
-    if attributes.fullscreen {
-        window.fullscreen();
+    match attributes.fullscreen {
+        Fullscreen::None => {}
+        Fullscreen::Borderless(monitor) => {
+            // Without a monitor, GTK goes fullscreen on the one the window is on.
+            let display = window.get_display();
+            let monitor = monitor
+                .map(|monitor| monitor as i32)
+                .filter(|monitor| display.get_monitor(*monitor).is_some());
+            match (monitor, window.get_screen()) {
+                (Some(monitor), Some(screen)) => window.fullscreen_on_monitor(&screen, monitor),
+                _ => window.fullscreen(),
+            }
+        }
+        Fullscreen::Exclusive => window.fullscreen(),
     }
     if let Some(icon) = attributes.icon {
         window.set_icon(Some(&load_icon(icon)?));
@@ -384,15 +1713,243 @@ fn _create_window(app: &GtkApp, attributes: InnerWindowAttributes) -> Result<App
     Ok(window)
 }
 
+// Creates a web context storing its data in directories of its own, named after the partition.
+fn partition_web_context(partition: &str) -> WebContext {
+    let (data, cache) = match (glib::get_user_data_dir(), glib::get_user_cache_dir()) {
+        (Some(data), Some(cache)) => (data, cache),
+        // Without directories of its own, the partition keeps its data in memory.
+        _ => return WebContext::new_ephemeral(),
+    };
+    let program = glib::get_prgname()
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| "wry".to_string());
+    let directory = |base: PathBuf| {
+        base.join(&program)
+            .join("partitions")
+            .join(partition)
+            .to_string_lossy()
+            .into_owned()
+    };
+    let manager = WebsiteDataManagerBuilder::new()
+        .base_data_directory(&directory(data))
+        .base_cache_directory(&directory(cache))
+        .build();
+    WebContext::with_website_data_manager(&manager)
+}
+
+#[cfg(not(feature = "v2_36"))]
+fn apply_memory_pressure_settings(_settings: &MemoryPressureSettings) {}
+
+// The bindings predate WebKitGTK 2.34, which lets the web processes started after this call free
+// memory according to the settings.
+#[cfg(feature = "v2_36")]
+fn apply_memory_pressure_settings(settings: &MemoryPressureSettings) {
+    use std::os::raw::{c_uint, c_void};
+
+    extern "C" {
+        fn webkit_memory_pressure_settings_new() -> *mut c_void;
+        fn webkit_memory_pressure_settings_set_memory_limit(settings: *mut c_void, limit: c_uint);
+        fn webkit_memory_pressure_settings_set_conservative_threshold(
+            settings: *mut c_void,
+            value: f64,
+        );
+        fn webkit_memory_pressure_settings_set_strict_threshold(settings: *mut c_void, value: f64);
+        fn webkit_memory_pressure_settings_set_kill_threshold(settings: *mut c_void, value: f64);
+        fn webkit_memory_pressure_settings_set_poll_interval(settings: *mut c_void, value: f64);
+        fn webkit_memory_pressure_settings_free(settings: *mut c_void);
+        fn webkit_web_context_set_memory_pressure_settings(settings: *mut c_void);
+    }
+
+    // Safety: The web contexts copy the settings, which are freed right after
+    unsafe {
+        let pressure = webkit_memory_pressure_settings_new();
+        if let Some(limit) = settings.memory_limit {
+            webkit_memory_pressure_settings_set_memory_limit(pressure, limit);
+        }
+        if let Some(threshold) = settings.conservative_threshold {
+            webkit_memory_pressure_settings_set_conservative_threshold(pressure, threshold);
+        }
+        if let Some(threshold) = settings.strict_threshold {
+            webkit_memory_pressure_settings_set_strict_threshold(pressure, threshold);
+        }
+        if let Some(threshold) = settings.kill_threshold {
+            webkit_memory_pressure_settings_set_kill_threshold(pressure, threshold);
+        }
+        if let Some(interval) = settings.poll_interval {
+            webkit_memory_pressure_settings_set_poll_interval(pressure, interval.as_secs_f64());
+        }
+        webkit_web_context_set_memory_pressure_settings(pressure);
+        webkit_memory_pressure_settings_free(pressure);
+    }
+}
+
+#[cfg(not(feature = "v2_36"))]
+fn connect_memory_pressure_events(_events: EventSender) {}
+
+// The bindings predate GLib 2.64, which reports when the system is low on memory.
+#[cfg(feature = "v2_36")]
+fn connect_memory_pressure_events(events: EventSender) {
+    use crate::MemoryPressureLevel;
+    use glib::{gobject_sys, translate::from_glib_full};
+
+    extern "C" {
+        fn g_memory_monitor_dup_default() -> *mut gobject_sys::GObject;
+    }
+    // The values of `GMemoryMonitorWarningLevel`.
+    const WARNING_LEVEL_MEDIUM: i32 = 100;
+    const WARNING_LEVEL_CRITICAL: i32 = 255;
+
+    // Safety: The monitor is returned with a reference of its own. GIO keeps the default monitor
+    // alive once created, so the signal stays connected after it's dropped here.
+    let monitor: glib::Object = unsafe { from_glib_full(g_memory_monitor_dup_default()) };
+    let _ = monitor.connect_local("low-memory-warning", false, move |values| {
+        let level = values.get(1)?;
+        // Safety: The value holds a `GMemoryMonitorWarningLevel`, which is an enum
+        let level = unsafe { gobject_sys::g_value_get_enum(level.to_glib_none().0) };
+        let level = if level >= WARNING_LEVEL_CRITICAL {
+            MemoryPressureLevel::Critical
+        } else if level >= WARNING_LEVEL_MEDIUM {
+            MemoryPressureLevel::Medium
+        } else {
+            MemoryPressureLevel::Low
+        };
+        let _ = events.send(WryEvent::MemoryPressure(level));
+        None
+    });
+}
+
+// Shows `image` centered in an undecorated window.
+fn show_splash(image: Icon) -> Result<gtk::Window> {
+    let splash = gtk::Window::new(WindowType::Toplevel);
+    splash.set_decorated(false);
+    splash.set_type_hint(gdk::WindowTypeHint::Splashscreen);
+    splash.set_position(WindowPosition::Center);
+    splash.add(&Image::from_pixbuf(Some(&load_icon(image)?)));
+    splash.show_all();
+    Ok(splash)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn _create_webview(
     proxy: InnerApplicationProxy,
     window: ApplicationWindow,
     attributes: InnerWebViewAttributes,
-    custom_protocol: Option<CustomProtocol>,
+    app_attributes: &ApplicationAttributes,
+    custom_protocols: Vec<CustomProtocol>,
     rpc_handler: Option<WindowRpcHandler>,
+    web_context: Option<WebContext>,
+    event_channel: EventSender,
 ) -> Result<WebView> {
     let window_id = window.get_id();
-    let mut webview = WebViewBuilder::new(window)?.transparent(attributes.transparent);
+
+    // Show the splash in place of the window until its first page is loaded.
+    let splash_handler = match attributes.splash {
+        Some(image) if window.get_visible() => {
+            let splash = Cell::new(Some(show_splash(image)?));
+            window.hide();
+            let window = window.clone();
+            Some(move || {
+                if let Some(splash) = splash.take() {
+                    splash.close();
+                    window.show_all();
+                }
+            })
+        }
+        _ => None,
+    };
+
+    let mut webview = WebViewBuilder::new(window)?
+        .focused(attributes.focused)
+        .transparent(attributes.transparent)
+        .html_title_as_window_title(attributes.html_title_as_window_title)
+        .back_forward_navigation_gestures(attributes.back_forward_navigation_gestures)
+        .overscroll(attributes.overscroll)
+        .auto_dpi_zoom(attributes.auto_dpi_zoom)
+        .allow_file_access_from_file_urls(attributes.allow_file_access_from_file_urls)
+        .allow_universal_access_from_file_urls(attributes.allow_universal_access_from_file_urls)
+        .custom_protocol_cors(attributes.custom_protocol_cors)
+        .custom_protocol_local(attributes.custom_protocol_local)
+        .inject_rpc_bridge(attributes.inject_rpc_bridge);
+    if let Some(zoom) = attributes.zoom_level {
+        webview = webview.zoom_level(zoom);
+    }
+    if let Some(scale_factor) = attributes.device_scale_factor {
+        webview = webview.device_scale_factor(scale_factor);
+    }
+    if let Some(width) = attributes.resize_border {
+        webview = webview.resize_border(width);
+    }
+    if let Some(color) = attributes.background_color {
+        webview = webview.background_color(color);
+    }
+    if let Some(timeout) = attributes.custom_protocol_timeout {
+        webview = webview.custom_protocol_timeout(timeout);
+    }
+    if let Some(threads) = attributes.custom_protocol_concurrency {
+        webview = webview.custom_protocol_concurrency(threads);
+    }
+    if let Some(languages) = attributes.accept_languages {
+        webview = webview.accept_languages(languages);
+    }
+    if let Some(user_agent) = attributes.user_agent {
+        webview = webview.user_agent(user_agent);
+    }
+    if attributes.devtools {
+        webview = webview.devtools(true);
+    }
+    webview = webview.devtools_attachment(attributes.devtools_attachment);
+    if let Some(html) = attributes.error_page {
+        webview = webview.error_page(html);
+    }
+    if let Some(context) = web_context {
+        webview = webview.web_context(context);
+    }
+    if let Some(handler) = splash_handler {
+        webview = webview.load_finished_handler(handler);
+    }
+    let events = event_channel.clone();
+    webview = webview.devtools_handler(move |open| {
+        let _ = events.send(WryEvent::WindowEvent {
+            window_id,
+            event: if open {
+                WindowEvent::DevtoolsOpened
+            } else {
+                WindowEvent::DevtoolsClosed
+            },
+        });
+    });
+    let events = event_channel.clone();
+    webview = webview.page_load_handler(move |url, state| {
+        let _ = events.send(WryEvent::WindowEvent {
+            window_id,
+            event: WindowEvent::PageLoad {
+                url: url.to_string(),
+                state,
+            },
+        });
+    });
+    let events = event_channel.clone();
+    webview = webview.editor_state_handler(move |state| {
+        let _ = events.send(WryEvent::WindowEvent {
+            window_id,
+            event: WindowEvent::EditorState {
+                bold: state.bold,
+                italic: state.italic,
+                underline: state.underline,
+                selection: state.selection,
+            },
+        });
+    });
+    let events = event_channel.clone();
+    webview = webview.find_result_handler(move |current, total| {
+        let _ = events.send(WryEvent::WindowEvent {
+            window_id,
+            event: WindowEvent::FindResult { current, total },
+        });
+    });
+    for js in &app_attributes.initialization_scripts {
+        webview = webview.initialize_script(js);
+    }
     for js in attributes.initialization_scripts {
         webview = webview.initialize_script(&js);
     }
@@ -401,11 +1958,78 @@ fn _create_webview(
         Some(url) => webview.load_url(&url)?,
         None => webview,
     };
-    if let Some(protocol) = custom_protocol {
-        webview = webview.register_protocol(protocol.name, protocol.handler);
+    for protocol in custom_protocols {
+        let (name, handler) = protocol.into_handler();
+        webview = webview.register_protocol_handler(name, handler);
+    }
+
+    if let Some(serializer) = app_attributes.rpc_serializer.clone() {
+        webview = webview.rpc_serializer(serializer);
+    }
+
+    if let Some(handler) = app_attributes.before_unload_handler.clone() {
+        let proxy = proxy.clone();
+        webview = webview.before_unload_handler(move |message| {
+            let proxy = WindowProxy::new(
+                ApplicationProxy {
+                    inner: proxy.clone(),
+                },
+                window_id,
+            );
+            handler(proxy, message)
+        });
     }
 
-    if let Some(rpc_handler) = rpc_handler {
+    if let Some(handler) = app_attributes.script_dialog_handler.clone() {
+        let proxy = proxy.clone();
+        webview = webview.script_dialog_handler(move |dialog| {
+            let proxy = WindowProxy::new(
+                ApplicationProxy {
+                    inner: proxy.clone(),
+                },
+                window_id,
+            );
+            handler(proxy, dialog)
+        });
+    }
+
+    if let Some(handler) = app_attributes.paste_handler.clone() {
+        let proxy = proxy.clone();
+        webview = webview.paste_handler(move |content| {
+            let proxy = WindowProxy::new(
+                ApplicationProxy {
+                    inner: proxy.clone(),
+                },
+                window_id,
+            );
+            handler(proxy, content)
+        });
+    }
+
+    if let Some(handler) = app_attributes.context_menu_handler.clone() {
+        let proxy = proxy.clone();
+        webview = webview.context_menu_handler(
+            move |menu| {
+                let proxy = WindowProxy::new(
+                    ApplicationProxy {
+                        inner: proxy.clone(),
+                    },
+                    window_id,
+                );
+                handler(proxy, menu)
+            },
+            move |id| {
+                let _ = event_channel.send(WryEvent::WindowEvent {
+                    window_id,
+                    event: WindowEvent::ContextMenuItem(id.to_string()),
+                });
+            },
+        );
+    }
+
+    // Without the bridge, the RPC script would still define `window.external`.
+    let inject_rpc_bridge = attributes.inject_rpc_bridge;
+    if let Some(rpc_handler) = rpc_handler.filter(|_| inject_rpc_bridge) {
         webview = webview.set_rpc_handler(Box::new(move |requests| {
             let proxy = WindowProxy::new(
                 ApplicationProxy {
@@ -420,3 +2044,45 @@ fn _create_webview(
     let webview = webview.build()?;
     Ok(webview)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window_proxy(proxy: Sender<Message>, main_thread: ThreadId) -> WindowProxy {
+        let (_, event_receiver) = event_queue();
+        let inner = InnerApplicationProxy {
+            proxy: EventLoopProxy(proxy),
+            event_receiver,
+            main_thread,
+        };
+        WindowProxy::new(ApplicationProxy { inner }, 1)
+    }
+
+    #[test]
+    fn should_answer_requests_through_the_responder() {
+        let (sender, messages) = channel();
+        let application = thread::spawn(move || {
+            for message in messages {
+                if let Message::Window(1, WindowMessage::IsVisible(responder)) = message {
+                    let _ = responder.send(WindowResponse::IsVisible(true));
+                }
+            }
+        });
+
+        let window = window_proxy(sender, application.thread().id());
+        assert!(window.is_visible().unwrap());
+
+        drop(window);
+        application.join().unwrap();
+    }
+
+    #[test]
+    fn should_refuse_requests_from_the_main_thread() {
+        let (sender, messages) = channel();
+        let window = window_proxy(sender, thread::current().id());
+
+        assert!(matches!(window.is_visible(), Err(Error::MainThreadRequest)));
+        assert!(messages.try_recv().is_err());
+    }
+}
@@ -11,19 +11,47 @@ pub use gtkrs::WindowId;
 #[cfg(target_os = "linux")]
 use gtkrs::{InnerApplication, InnerApplicationProxy};
 mod attributes;
-pub use attributes::{Attributes, CustomProtocol, Icon, WindowRpcHandler};
-pub(crate) use attributes::{InnerWebViewAttributes, InnerWindowAttributes};
+pub(crate) use attributes::{
+    ApplicationAttributes, InnerWebViewAttributes, InnerWindowAttributes,
+    WindowBeforeUnloadHandler, WindowContextMenuHandler, WindowPasteHandler,
+    WindowScriptDialogHandler,
+};
+pub use attributes::{
+    Attributes, CustomProtocol, Fullscreen, Icon, WindowCloseRequestedHandler, WindowRpcHandler,
+    WindowTypeHint,
+};
+mod dialog;
+mod event;
+pub use dialog::{FileDialogOptions, FileFilter, MessageDialogButtons, MessageDialogResult};
+pub(crate) use event::{event_queue, EventReceiver, EventSender};
+pub use event::{MemoryPressureLevel, ModifiersState, WindowEvent, WryEvent};
 
-use crate::Result;
+use crate::{
+    webview::{
+        BackForwardList, ClipboardContent, ContextMenu, Cookie, DevtoolsAttachment, RpcSerializer,
+        ScriptDialog, ScriptDialogAnswer, StorageTypes,
+    },
+    Error, Result,
+};
 
-use std::sync::mpsc::Sender;
+use std::{
+    fmt,
+    path::PathBuf,
+    rc::Rc,
+    sync::mpsc::{channel, Sender},
+    time::Duration,
+};
 
 use serde_json::Value;
+use url::Url;
 
 /// Describes a message for a WebView window.
 #[derive(Debug)]
 pub enum WindowMessage {
     SetResizable(bool),
+    SetMinimizable(bool),
+    SetMaximizable(bool),
+    SetClosable(bool),
     SetTitle(String),
     Maximize,
     Unmaximize,
@@ -31,20 +59,271 @@ pub enum WindowMessage {
     Unminimize,
     Show,
     Hide,
+    Focus,
+    RequestUserAttention(Option<UserAttentionType>),
     Close,
     SetDecorations(bool),
+    SetOpacity(f64),
+    SetShadow(bool),
     SetAlwaysOnTop(bool),
+    SetAlwaysOnBottom(bool),
+    SetAlwaysOnTopYieldsToFullscreen(bool),
     SetWidth(f64),
     SetHeight(f64),
-    Resize { width: f64, height: f64 },
-    SetMinSize { min_width: f64, min_height: f64 },
-    SetMaxSize { max_width: f64, max_height: f64 },
+    Resize {
+        width: f64,
+        height: f64,
+    },
+    SetMinSize {
+        min_width: f64,
+        min_height: f64,
+    },
+    SetMaxSize {
+        max_width: f64,
+        max_height: f64,
+    },
+    SetMinSizeToContent,
     SetX(f64),
     SetY(f64),
-    SetPosition { x: f64, y: f64 },
+    SetPosition {
+        x: f64,
+        y: f64,
+    },
+    Center,
+    Snap(SnapRegion),
     SetFullscreen(bool),
+    ToggleFullscreen,
+    IsFullscreen(Sender<WindowResponse>),
+    IsVisible(Sender<WindowResponse>),
     SetIcon(Icon),
+    SetCursorImage {
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    },
+    ResetCursor,
     EvaluationScript(String),
+    EvaluationScriptWithCallback(String, ScriptCallback),
+    LoadUrl(String),
+    Reload {
+        bypass_cache: bool,
+    },
+    SetUserAgent(String),
+    ReloadWithUserAgent {
+        user_agent: String,
+        restore: bool,
+    },
+    SetZoomLevel(f64),
+    ZoomLevel(Sender<WindowResponse>),
+    GoBack,
+    GoForward,
+    CanGoBack(Sender<WindowResponse>),
+    CanGoForward(Sender<WindowResponse>),
+    Url(Sender<WindowResponse>),
+    Title(Sender<WindowResponse>),
+    LoadBytes {
+        bytes: Vec<u8>,
+        mime_type: String,
+        base_url: Option<String>,
+    },
+    LoadHtml {
+        html: String,
+        base_url: Option<String>,
+    },
+    WaitForLoad(Sender<WindowResponse>),
+    IsDevtoolsOpen(Sender<WindowResponse>),
+    OpenDevtools(Option<DevtoolsAttachment>),
+    CloseDevtools,
+    DevtoolsAttachedHeight(Sender<WindowResponse>),
+    BackForwardList(Sender<WindowResponse>),
+    ClearHistory,
+    SessionState(Sender<WindowResponse>),
+    RestoreSessionState(Vec<u8>),
+    SetProgressBar(f64, ProgressBarState),
+    SetTaskbarIcon(Option<Icon>),
+    SetClipboardText(String),
+    ClipboardText(Sender<WindowResponse>),
+    SetWindowButtonsPosition(WindowButtonsPosition),
+    SetOpaqueRegion(Option<Vec<RoundedRect>>),
+    SetEditable(bool),
+    SetEnableDragRegion(bool),
+    Find {
+        text: String,
+        case_sensitive: bool,
+    },
+    FindNext,
+    FindPrevious,
+    StopFind,
+    SetSelectable(bool),
+    ShowMessageDialog {
+        title: String,
+        message: String,
+        buttons: MessageDialogButtons,
+        responder: Sender<WindowResponse>,
+    },
+    OpenFileDialog {
+        options: FileDialogOptions,
+        responder: Sender<WindowResponse>,
+    },
+    SaveFileDialog {
+        options: FileDialogOptions,
+        responder: Sender<WindowResponse>,
+    },
+    Cookies {
+        url: String,
+        responder: Sender<WindowResponse>,
+    },
+    SetCookie(Cookie),
+    ClearAllCookies,
+    ClearCache,
+    ClearStorage(StorageTypes),
+}
+
+/// Receives the result of a script evaluated with
+/// [`WindowProxy::evaluate_script_with_callback`].
+pub struct ScriptCallback(pub(crate) Box<dyn FnOnce(Result<Value>) + Send>);
+
+impl fmt::Debug for ScriptCallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ScriptCallback")
+    }
+}
+
+/// What's at a point of the page, returned by [`WindowProxy::hit_test`].
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct HitTestResult {
+    /// The URL of the link at the point, if any.
+    pub link_url: Option<String>,
+    /// The URL of the image or the media at the point, if any.
+    pub media_url: Option<String>,
+    /// Whether the content at the point is editable, like a text field.
+    pub editable: bool,
+}
+
+/// The answer to a [`WindowMessage`] querying the window, sent back through its responder.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowResponse {
+    IsDevtoolsOpen(bool),
+    DevtoolsAttachedHeight(Option<u32>),
+    IsFullscreen(bool),
+    IsVisible(bool),
+    LoadFinished,
+    ZoomLevel(f64),
+    CanGoBack(bool),
+    CanGoForward(bool),
+    Url(Option<String>),
+    Title(Option<String>),
+    BackForwardList(BackForwardList),
+    SessionState(Option<Vec<u8>>),
+    MessageDialog(MessageDialogResult),
+    OpenFileDialog(Vec<PathBuf>),
+    SaveFileDialog(Option<PathBuf>),
+    ClipboardText(Option<String>),
+    Cookies(Vec<Cookie>),
+}
+
+/// The state of the progress bar shown on a window's taskbar entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProgressBarState {
+    /// Hides the progress bar.
+    None,
+    Normal,
+    /// The progress bar keeps cycling without showing an actual progress.
+    Indeterminate,
+    Error,
+    Paused,
+}
+
+/// The side of the title bar on which the close, minimize and maximize buttons are placed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowButtonsPosition {
+    /// The buttons are on the left, like on macOS.
+    Left,
+    /// The buttons are on the right, like on Windows.
+    Right,
+}
+
+/// How insistently a window asks for the attention of the user, with
+/// [`WindowProxy::request_user_attention`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UserAttentionType {
+    /// Until the window is focused, e.g. for an incoming call.
+    Critical,
+    /// Briefly, e.g. for a new message.
+    Informational,
+}
+
+/// A part of the screen to snap a window to, with [`WindowProxy::snap`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapRegion {
+    /// The left half.
+    Left,
+    /// The right half.
+    Right,
+    /// The top half.
+    Top,
+    /// The bottom half.
+    Bottom,
+    /// The top-left quarter.
+    TopLeft,
+    /// The top-right quarter.
+    TopRight,
+    /// The bottom-left quarter.
+    BottomLeft,
+    /// The bottom-right quarter.
+    BottomRight,
+}
+
+impl SnapRegion {
+    // The part of the given area covered by the region, as `(x, y, width, height)`.
+    pub(crate) fn rect(self, x: f64, y: f64, width: f64, height: f64) -> (f64, f64, f64, f64) {
+        let (half_width, half_height) = (width / 2., height / 2.);
+        match self {
+            SnapRegion::Left => (x, y, half_width, height),
+            SnapRegion::Right => (x + half_width, y, half_width, height),
+            SnapRegion::Top => (x, y, width, half_height),
+            SnapRegion::Bottom => (x, y + half_height, width, half_height),
+            SnapRegion::TopLeft => (x, y, half_width, half_height),
+            SnapRegion::TopRight => (x + half_width, y, half_width, half_height),
+            SnapRegion::BottomLeft => (x, y + half_height, half_width, half_height),
+            SnapRegion::BottomRight => (x + half_width, y + half_height, half_width, half_height),
+        }
+    }
+}
+
+/// A rectangle with rounded corners, in logical pixels relative to the top-left corner of the
+/// window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoundedRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// The radius of the corners, `0.0` gives square corners.
+    pub radius: f64,
+}
+
+/// When the web processes start freeing memory, with
+/// [`Application::set_memory_pressure_settings`]. The fields left to `None` keep the defaults of
+/// the web engine.
+///
+/// The thresholds are fractions of [`memory_limit`](Self::memory_limit). Above the conservative
+/// one, a web process frees memory it can cheaply recreate, like caches. Above the strict one, it
+/// frees all it can. Above the kill one, it's terminated. They must keep that order, and the strict
+/// and kill ones can't exceed `1.0`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MemoryPressureSettings {
+    /// The memory a web process should stay under, in megabytes.
+    pub memory_limit: Option<u32>,
+    pub conservative_threshold: Option<f64>,
+    pub strict_threshold: Option<f64>,
+    /// `0.0` never terminates the web processes.
+    pub kill_threshold: Option<f64>,
+    /// How often the memory usage of the web processes is checked.
+    pub poll_interval: Option<Duration>,
 }
 
 /// Describes a general message.
@@ -54,8 +333,27 @@ pub enum Message {
         Attributes,
         Sender<WindowId>,
         Option<WindowRpcHandler>,
-        Option<CustomProtocol>,
+        Option<WindowCloseRequestedHandler>,
+        Vec<CustomProtocol>,
     ),
+    AddInitializationScript(String),
+    RunOnMain(Box<dyn FnOnce() + Send>),
+    CloseWindows {
+        except: Option<WindowId>,
+    },
+    /// Messages processed back-to-back, without returning to the event loop in between, e.g. to
+    /// apply many changes to windows with a single repaint. Batches can be nested.
+    Batch(Vec<Message>),
+}
+
+impl Message {
+    // Expands the batches into the messages they contain, in order.
+    pub(crate) fn flatten(self) -> Vec<Message> {
+        match self {
+            Message::Batch(messages) => messages.into_iter().flat_map(Message::flatten).collect(),
+            message => vec![message],
+        }
+    }
 }
 
 /// A proxy to sent custom messages to [`Application`].
@@ -74,32 +372,95 @@ impl ApplicationProxy {
         self.inner.send_message(message)
     }
     /// Adds another WebView window to the application. Returns its [`WindowProxy`] after created.
+    ///
+    /// Returns [`Error::MainThreadRequest`] when called from the thread running the
+    /// [`Application`], use [`Application::add_window`] there instead.
     pub fn add_window(&self, attributes: Attributes) -> Result<WindowProxy> {
-        let id = self.inner.add_window(attributes, None, None)?;
+        self.check_thread()?;
+        attributes.check_storage_partition()?;
+        let id = self.inner.add_window(attributes, None, None, Vec::new())?;
         Ok(WindowProxy::new(self.clone(), id))
     }
 
     /// Adds another WebView window to the application with more configuration options. Returns its [`WindowProxy`] after created.
+    ///
+    /// See [`Application::add_window_with_configs`] for the options.
+    ///
+    /// Returns [`Error::MainThreadRequest`] when called from the thread running the
+    /// [`Application`], use [`Application::add_window_with_configs`] there instead.
     pub fn add_window_with_configs(
         &self,
         attributes: Attributes,
         rpc_handler: Option<WindowRpcHandler>,
-        custom_protocol: Option<CustomProtocol>,
+        close_requested_handler: Option<WindowCloseRequestedHandler>,
+        custom_protocols: Vec<CustomProtocol>,
     ) -> Result<WindowProxy> {
-        let id = self
-            .inner
-            .add_window(attributes, rpc_handler, custom_protocol)?;
+        self.check_thread()?;
+        attributes.check_storage_partition()?;
+        CustomProtocol::check_unique(&custom_protocols)?;
+        let id = self.inner.add_window(
+            attributes,
+            rpc_handler,
+            close_requested_handler,
+            custom_protocols,
+        )?;
         Ok(WindowProxy::new(self.clone(), id))
     }
+
+    /// Adds JavaScript code to be initialized in every WebView window added after this call, like
+    /// [`Attributes::initialization_scripts`]. These scripts run before the ones of the window.
+    pub fn add_initialization_script(&self, js: &str) -> Result<()> {
+        self.send_message(Message::AddInitializationScript(js.to_string()))
+    }
+
+    /// Closes every window of the application, which then stops running.
+    pub fn close_all_windows(&self) -> Result<()> {
+        self.send_message(Message::CloseWindows { except: None })
+    }
+
+    /// Runs `f` on the thread running the [`Application`], where the windows and WebViews live.
+    /// This is meant for calling platform APIs that have to be called on that thread.
+    pub fn run_on_main(&self, f: Box<dyn FnOnce() + Send>) -> Result<()> {
+        self.inner.run_on_main(f)
+    }
+
+    /// Blocks until the [`Application`] emits an event, and returns it.
+    ///
+    /// Events are queued from the moment the application is created, so none are missed before
+    /// the first call. The queue keeps the latest 1024 events at most and drops older ones, so
+    /// an application that listens late may miss some.
+    ///
+    /// Each event is returned only once: every proxy of the application shares the same queue,
+    /// so if several threads listen at the same time, each event goes to only one of them.
+    ///
+    /// Returns [`Error::MainThreadRequest`] when called from the thread running the
+    /// [`Application`], which would never get to emit the event.
+    pub fn listen_event(&self) -> Result<WryEvent> {
+        self.check_thread()?;
+        self.inner.listen_event()
+    }
+
+    /// Fails instead of blocking forever on the thread that has to answer.
+    fn check_thread(&self) -> Result<()> {
+        if self.inner.is_main_thread() {
+            Err(Error::MainThreadRequest)
+        } else {
+            Ok(())
+        }
+    }
 }
 
 trait AppProxy {
     fn send_message(&self, message: Message) -> Result<()>;
+    fn listen_event(&self) -> Result<WryEvent>;
+    fn is_main_thread(&self) -> bool;
+    fn run_on_main(&self, f: Box<dyn FnOnce() + Send>) -> Result<()>;
     fn add_window(
         &self,
         attributes: Attributes,
         rpc_handler: Option<WindowRpcHandler>,
-        custom_protocol: Option<CustomProtocol>,
+        close_requested_handler: Option<WindowCloseRequestedHandler>,
+        custom_protocols: Vec<CustomProtocol>,
     ) -> Result<WindowId>;
 }
 
@@ -130,6 +491,49 @@ impl WindowProxy {
         ))
     }
 
+    /// Enables or disables the minimize button of the window. The window can still be minimized
+    /// with [`WindowProxy::minimize`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** It's a hint that some window managers ignore. It has no effect with
+    ///   client-side decorations, like the ones of Wayland.
+    pub fn set_minimizable(&self, minimizable: bool) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetMinimizable(minimizable),
+        ))
+    }
+
+    /// Enables or disables the maximize button of the window, while it can still be moved and
+    /// resized. The window can still be maximized with [`WindowProxy::maximize`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** It's a hint that some window managers ignore. It has no effect with
+    ///   client-side decorations, like the ones of Wayland, which only hide the button of windows
+    ///   that aren't resizable.
+    /// - **Windows:** [`WindowProxy::set_resizable`] enables the button again.
+    pub fn set_maximizable(&self, maximizable: bool) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetMaximizable(maximizable),
+        ))
+    }
+
+    /// Enables or disables the close button of the window. The window can still be closed with
+    /// [`WindowProxy::close`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Some window managers still let the user close the window with a shortcut.
+    pub fn set_closable(&self, closable: bool) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetClosable(closable),
+        ))
+    }
+
     pub fn set_title<S: Into<String>>(&self, title: S) -> Result<()> {
         self.proxy.send_message(Message::Window(
             self.id,
@@ -156,11 +560,61 @@ impl WindowProxy {
             .send_message(Message::Window(self.id, WindowMessage::Unminimize))
     }
 
+    /// Minimizes the window, or restores it from being minimized.
+    pub fn set_minimized(&self, minimized: bool) -> Result<()> {
+        if minimized {
+            self.minimize()
+        } else {
+            self.unminimize()
+        }
+    }
+
     pub fn show(&self) -> Result<()> {
         self.proxy
             .send_message(Message::Window(self.id, WindowMessage::Show))
     }
 
+    /// Brings the window to the front, restoring it first if it's minimized, and gives the
+    /// keyboard focus to its WebView.
+    pub fn set_focus(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::Focus))
+    }
+
+    /// Asks for the attention of the user without taking the focus, e.g. by flashing the entry
+    /// of the window in the taskbar when a message arrives. `None` stops asking. It stops by
+    /// itself once the window is focused, and does nothing if it already is.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Sets the urgency hint of the window, whatever the type. How it's shown depends
+    ///   on the desktop: GNOME shows a notification that the window is ready, KDE and most taskbars
+    ///   highlight its entry, and some window managers ignore it.
+    /// - **Windows:** [`UserAttentionType::Informational`] flashes the taskbar entry only once.
+    /// - **macOS:** [`UserAttentionType::Critical`] bounces the dock icon until the application
+    ///   is activated, [`UserAttentionType::Informational`] bounces it once.
+    pub fn request_user_attention(&self, request: Option<UserAttentionType>) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::RequestUserAttention(request),
+        ))
+    }
+
+    /// Returns whether the window is visible, i.e. shown and not hidden, even if it's minimized.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Minimized windows aren't visible.
+    pub fn is_visible(&self) -> Result<bool> {
+        match self.request(WindowMessage::IsVisible)? {
+            WindowResponse::IsVisible(visible) => Ok(visible),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
     pub fn hide(&self) -> Result<()> {
         self.proxy
             .send_message(Message::Window(self.id, WindowMessage::Hide))
@@ -178,6 +632,32 @@ impl WindowProxy {
         ))
     }
 
+    /// Sets the opacity of the whole window, from `0.0` (fully transparent) to `1.0` (opaque),
+    /// e.g. to fade it in or out. Values out of this range are clamped.
+    ///
+    /// Opacities below `1.0` need a compositing window manager, without one the window stays
+    /// opaque.
+    pub fn set_opacity(&self, opacity: f64) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetOpacity(opacity.clamp(0.0, 1.0)),
+        ))
+    }
+
+    /// Sets whether the window casts a drop shadow, e.g. to turn it off for a borderless overlay
+    /// that must stay perfectly rectangular.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Only removes the shadow GTK draws around client-side decorations. The shadow
+    ///   of undecorated windows is drawn by the compositor, which may not allow turning it off.
+    /// - **Windows:** Turns the non-client area rendering of the window off, which also removes
+    ///   its frame if it's decorated.
+    pub fn set_shadow(&self, shadow: bool) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::SetShadow(shadow)))
+    }
+
     pub fn set_always_on_top(&self, always_on_top: bool) -> Result<()> {
         self.proxy.send_message(Message::Window(
             self.id,
@@ -185,6 +665,36 @@ impl WindowProxy {
         ))
     }
 
+    /// Sets whether the window is always kept below other windows, e.g. a desktop widget or a
+    /// dashboard. Keeping it below stops keeping it on top, and the other way around.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** It's a hint that some window managers ignore.
+    /// - **Windows:** The window is sent to the bottom once, activating it brings it up again.
+    pub fn set_always_on_bottom(&self, always_on_bottom: bool) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetAlwaysOnBottom(always_on_bottom),
+        ))
+    }
+
+    /// Sets whether an always-on-top window steps back while a fullscreen window of another
+    /// application is focused on the same monitor, e.g. a video player or a game. It's put on
+    /// top again once that window loses focus, or when the focus changes after it left
+    /// fullscreen.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Only works on X11, as Wayland doesn't expose other applications' windows.
+    /// - **Windows / macOS:** Unsupported.
+    pub fn set_always_on_top_yields_to_fullscreen(&self, yields: bool) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetAlwaysOnTopYieldsToFullscreen(yields),
+        ))
+    }
+
     pub fn set_width(&self, width: f64) -> Result<()> {
         self.proxy
             .send_message(Message::Window(self.id, WindowMessage::SetWidth(width)))
@@ -212,6 +722,18 @@ impl WindowProxy {
         ))
     }
 
+    /// Sets the minimum size of the window to the size the page needs at its narrowest, so its
+    /// content is never squished or cut. The page is measured once, call this again after its
+    /// layout changed.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Unsupported.
+    pub fn set_min_size_to_content(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::SetMinSizeToContent))
+    }
+
     pub fn set_max_size(&self, max_width: f64, max_height: f64) -> Result<()> {
         self.proxy.send_message(Message::Window(
             self.id,
@@ -239,6 +761,33 @@ impl WindowProxy {
         ))
     }
 
+    /// Closes every other window of the application.
+    pub fn close_others(&self) -> Result<()> {
+        self.proxy.send_message(Message::CloseWindows {
+            except: Some(self.id),
+        })
+    }
+
+    /// Centers the window on the monitor it's on.
+    pub fn center(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::Center))
+    }
+
+    /// Moves and resizes the window at once to cover a half or a quarter of the monitor it's on,
+    /// whatever the keybindings of the window manager. A maximized window is unmaximized first.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** The region is taken from the work area of the monitor, which excludes the
+    ///   panels of the desktop.
+    /// - **Windows / macOS:** The region is taken from the whole monitor, including the taskbar or
+    ///   the dock.
+    pub fn snap(&self, region: SnapRegion) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::Snap(region)))
+    }
+
     pub fn set_fullscreen(&self, fullscreen: bool) -> Result<()> {
         self.proxy.send_message(Message::Window(
             self.id,
@@ -246,17 +795,764 @@ impl WindowProxy {
         ))
     }
 
+    /// Leaves fullscreen if the window is fullscreen, enters it otherwise.
+    pub fn toggle_fullscreen(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::ToggleFullscreen))
+    }
+
+    /// Returns whether the window is fullscreen. Changes are also notified with
+    /// [`WindowEvent::Fullscreen`].
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    pub fn is_fullscreen(&self) -> Result<bool> {
+        match self.request(WindowMessage::IsFullscreen)? {
+            WindowResponse::IsFullscreen(fullscreen) => Ok(fullscreen),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
     pub fn set_icon(&self, icon: Icon) -> Result<()> {
         self.proxy
             .send_message(Message::Window(self.id, WindowMessage::SetIcon(icon)))
     }
 
+    /// Replaces the cursor over the WebView with a custom image, given as RGBA pixels row by row,
+    /// until [`WindowProxy::reset_cursor`] is called. The hotspot is the pixel of the image
+    /// pointing at the cursor position.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn set_cursor_from_image(
+        &self,
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    ) -> Result<()> {
+        if rgba.len() != width as usize * height as usize * 4 {
+            return Err(Error::InvalidCursorImage(rgba.len()));
+        }
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetCursorImage {
+                rgba,
+                width,
+                height,
+                hotspot_x,
+                hotspot_y,
+            },
+        ))
+    }
+
+    /// Gives the cursor back to the page after [`WindowProxy::set_cursor_from_image`].
+    pub fn reset_cursor(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::ResetCursor))
+    }
+
     pub fn evaluate_script<S: Into<String>>(&self, script: S) -> Result<()> {
         self.proxy.send_message(Message::Window(
             self.id,
             WindowMessage::EvaluationScript(script.into()),
         ))
     }
+
+    /// Evaluates `script` and calls `callback` with its result, i.e. the value of its last
+    /// statement, once it completed. The callback is called on the thread running the
+    /// [`Application`]. Scripts sent with [`WindowProxy::evaluate_script`] before still run first.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Unsupported, the callback always receives `null`.
+    pub fn evaluate_script_with_callback<S, F>(&self, script: S, callback: F) -> Result<()>
+    where
+        S: Into<String>,
+        F: FnOnce(Result<Value>) + Send + 'static,
+    {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::EvaluationScriptWithCallback(
+                script.into(),
+                ScriptCallback(Box::new(callback)),
+            ),
+        ))
+    }
+
+    /// Evaluates `script` and blocks until it completed, returning its result, e.g. to read a
+    /// value of the DOM without going through the RPC handler.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Unsupported, the result is always `null`.
+    pub fn evaluate_script_with_result<S: Into<String>>(&self, script: S) -> Result<Value> {
+        self.proxy.check_thread()?;
+        let (sender, receiver) = channel();
+        self.evaluate_script_with_callback(script, move |result| {
+            let _ = sender.send(result);
+        })?;
+        receiver.recv()?
+    }
+
+    /// Returns what's at the point `(x, y)` of the page, in CSS pixels from the top left corner
+    /// of the WebView, e.g. for link previews or custom context menus.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Unsupported, nothing is ever found.
+    pub fn hit_test(&self, x: f64, y: f64) -> Result<HitTestResult> {
+        let value =
+            self.evaluate_script_with_result(format!("{}({}, {})", HIT_TEST_SCRIPT, x, y))?;
+        if value.is_null() {
+            return Ok(HitTestResult::default());
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Navigates the WebView to the provided URL. A navigation still in progress, e.g. from a
+    /// previous call, is cancelled so only the latest URL ends up being loaded.
+    ///
+    /// Returns [`Error::UrlError`] without navigating if `url` isn't a valid URL.
+    pub fn load_url<S: Into<String>>(&self, url: S) -> Result<()> {
+        let url = url.into();
+        Url::parse(&url)?;
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::LoadUrl(url)))
+    }
+
+    /// Reloads the current page. With `bypass_cache`, resources are loaded again instead of
+    /// taken from the cache, e.g. to pick up assets of a custom protocol that changed on disk.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** `bypass_cache` is unsupported, resources may still come from the cache.
+    pub fn reload(&self, bypass_cache: bool) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::Reload { bypass_cache },
+        ))
+    }
+
+    /// Sets the user agent the WebView identifies itself with, from the next request on.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported.
+    pub fn set_user_agent<S: Into<String>>(&self, user_agent: S) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetUserAgent(user_agent.into()),
+        ))
+    }
+
+    /// Reloads the current page with another user agent, e.g. for a "request desktop site"
+    /// option. With `restore`, the previous user agent is used again once the page is reloaded,
+    /// otherwise the new one is kept for the next navigations.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported, the page is reloaded with the same user agent.
+    /// - **macOS:** `restore` is unsupported, the new user agent is kept.
+    pub fn reload_with_user_agent<S: Into<String>>(
+        &self,
+        user_agent: S,
+        restore: bool,
+    ) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::ReloadWithUserAgent {
+                user_agent: user_agent.into(),
+                restore,
+            },
+        ))
+    }
+
+    /// Sets the zoom level of the page, where `1.0` is 100%, e.g. for zoom shortcuts. It's
+    /// clamped between `0.25` and `5.0`.
+    pub fn set_zoom_level(&self, zoom: f64) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::SetZoomLevel(zoom)))
+    }
+
+    /// Returns the zoom level of the page, where `1.0` is 100%.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    pub fn zoom_level(&self) -> Result<f64> {
+        match self.request(WindowMessage::ZoomLevel)? {
+            WindowResponse::ZoomLevel(zoom) => Ok(zoom),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Navigates to the previous page of the history. Does nothing if there's none.
+    pub fn go_back(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::GoBack))
+    }
+
+    /// Navigates to the next page of the history. Does nothing if there's none.
+    pub fn go_forward(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::GoForward))
+    }
+
+    /// Returns whether there's a previous page in the history, e.g. to enable a back button.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    pub fn can_go_back(&self) -> Result<bool> {
+        match self.request(WindowMessage::CanGoBack)? {
+            WindowResponse::CanGoBack(can) => Ok(can),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Returns whether there's a next page in the history, e.g. to enable a forward button.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    pub fn can_go_forward(&self) -> Result<bool> {
+        match self.request(WindowMessage::CanGoForward)? {
+            WindowResponse::CanGoForward(can) => Ok(can),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Returns the URL of the page currently shown, following redirects and client-side
+    /// navigation, or `None` if nothing has been loaded yet.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    pub fn url(&self) -> Result<Option<String>> {
+        match self.request(WindowMessage::Url)? {
+            WindowResponse::Url(url) => Ok(url),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Returns the title of the page currently shown, or `None` if it hasn't loaded yet.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    pub fn title(&self) -> Result<Option<String>> {
+        match self.request(WindowMessage::Title)? {
+            WindowResponse::Title(title) => Ok(title),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Displays `bytes` as content of the given MIME type, e.g. `application/pdf` or
+    /// `text/html; charset=utf-8`, without writing it to a file first. Relative URLs in the
+    /// content are resolved against `base_url`.
+    ///
+    /// Content the WebView can't display is ignored, see [`WebView::load_bytes`] for the details.
+    ///
+    /// [`WebView::load_bytes`]: crate::webview::WebView::load_bytes
+    pub fn load_bytes<B: Into<Vec<u8>>, M: Into<String>>(
+        &self,
+        bytes: B,
+        mime_type: M,
+        base_url: Option<&str>,
+    ) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::LoadBytes {
+                bytes: bytes.into(),
+                mime_type: mime_type.into(),
+                base_url: base_url.map(String::from),
+            },
+        ))
+    }
+
+    /// Displays the `html` string, without encoding it in a `data:` URL first. Relative URLs in
+    /// it, including the ones of custom protocols, are resolved against `base_url`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** `base_url` is ignored.
+    pub fn load_html<S: Into<String>>(&self, html: S, base_url: Option<&str>) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::LoadHtml {
+                html: html.into(),
+                base_url: base_url.map(String::from),
+            },
+        ))
+    }
+
+    /// Blocks until the page being loaded, or the next one, finished loading, successfully or
+    /// not, e.g. to evaluate scripts right after [`WindowProxy::load_url`]. Returns an error if
+    /// it didn't within `timeout`.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    pub fn wait_for_load(&self, timeout: Duration) -> Result<()> {
+        self.proxy.check_thread()?;
+        let (responder, response) = channel();
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::WaitForLoad(responder),
+        ))?;
+        match response.recv_timeout(timeout)? {
+            WindowResponse::LoadFinished => Ok(()),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Shows a progress bar on the taskbar entry of the window. `progress` ranges from `0.0` to
+    /// `1.0`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Best-effort through the Unity launcher API, which is also understood by docks
+    ///   like Plank or Dash to Dock. The progress is shown for the whole application and the dock
+    ///   needs a `.desktop` file named after the program to match it. Only `None` hides the
+    ///   progress bar, other states are shown as `Normal`.
+    /// - **macOS:** Unsupported.
+    pub fn set_progress_bar(&self, progress: f64, state: ProgressBarState) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetProgressBar(progress, state),
+        ))
+    }
+
+    /// Shows `icon` as a small overlay over the taskbar button of the window, independently from
+    /// the window icon, e.g. to signal unread messages. `None` removes the overlay.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux / macOS:** Unsupported, returns [`Error::Unsupported`]. Their docks only show the
+    ///   icon of the application.
+    pub fn set_taskbar_icon(&self, icon: Option<Icon>) -> Result<()> {
+        if cfg!(not(target_os = "windows")) {
+            return Err(Error::Unsupported("Taskbar overlay icons"));
+        }
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetTaskbarIcon(icon),
+        ))
+    }
+
+    /// Puts text in the clipboard of the system, without going through the page, where writing to
+    /// the clipboard needs a user gesture.
+    pub fn set_clipboard_text<S: Into<String>>(&self, text: S) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetClipboardText(text.into()),
+        ))
+    }
+
+    /// Returns the text in the clipboard of the system, or `None` if it holds no text.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    pub fn clipboard_text(&self) -> Result<Option<String>> {
+        match self.request(WindowMessage::ClipboardText)? {
+            WindowResponse::ClipboardText(text) => Ok(text),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Sets on which side of the title bar the close, minimize and maximize buttons are placed.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Only applies to title bars drawn by GTK, like on Wayland, and not to the ones
+    ///   drawn by the window manager. The layout is shared by every window of the application.
+    /// - **Windows / macOS:** Unsupported.
+    pub fn set_window_buttons_position(&self, position: WindowButtonsPosition) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetWindowButtonsPosition(position),
+        ))
+    }
+
+    /// Restricts the visible part of the window to the union of `region`, everything outside of
+    /// it is fully transparent. This is how windows with rounded corners are made. `None` makes
+    /// the whole window visible again.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** The window must be created with [`Attributes::transparent`] set, and a
+    ///   compositing window manager has to be running.
+    /// - **Windows:** Coordinates are relative to the whole window, including its decorations.
+    ///   Edges are not antialiased.
+    /// - **macOS:** Unsupported.
+    pub fn set_opaque_region(&self, region: Option<Vec<RoundedRect>>) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetOpaqueRegion(region),
+        ))
+    }
+
+    /// Sets whether the whole document can be edited by the user, like a `contenteditable`
+    /// element.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Only applies to the current page, it has to be set again after
+    ///   navigating.
+    pub fn set_editable(&self, editable: bool) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetEditable(editable),
+        ))
+    }
+
+    /// Sets whether pressing the elements of the page styled with `-webkit-app-region: drag`
+    /// moves the window, like the title bar would, e.g. for windows without decorations that
+    /// draw their own title bar. Elements styled with `-webkit-app-region: no-drag` inside them,
+    /// like buttons, can still be clicked.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** WebKitGTK drops the property from stylesheets, it only works in the `style`
+    ///   attribute of the elements.
+    /// - **Windows / macOS:** Unsupported.
+    pub fn set_enable_drag_region(&self, enabled: bool) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetEnableDragRegion(enabled),
+        ))
+    }
+
+    /// Highlights the occurrences of `text` in the page and selects the first one, wrapping
+    /// around at the end. The matches are reported with [`WindowEvent::FindResult`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn find(&self, text: &str, case_sensitive: bool) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::Find {
+                text: text.to_string(),
+                case_sensitive,
+            },
+        ))
+    }
+
+    /// Selects the next match of [`WindowProxy::find`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn find_next(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::FindNext))
+    }
+
+    /// Selects the previous match of [`WindowProxy::find`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn find_previous(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::FindPrevious))
+    }
+
+    /// Removes the highlights of [`WindowProxy::find`], e.g. when the search bar is closed.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn stop_find(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::StopFind))
+    }
+
+    /// Sets whether the user can select text in the page and copy or cut it, e.g. to protect the
+    /// content shown in the window. This only applies to the current page, it has to be set again
+    /// after navigating.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Making the page unselectable also stops JavaScript from accessing the
+    ///   clipboard, for every page of the window.
+    pub fn set_selectable(&self, selectable: bool) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::SetSelectable(selectable),
+        ))
+    }
+
+    /// Shows a modal message dialog attached to the window and blocks until the user picks one of
+    /// the `buttons`. Closing the dialog without picking a button is reported as `Cancel`, or as
+    /// `No` and `Ok` when there is no cancel button.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    pub fn show_message_dialog<T: Into<String>, M: Into<String>>(
+        &self,
+        title: T,
+        message: M,
+        buttons: MessageDialogButtons,
+    ) -> Result<MessageDialogResult> {
+        match self.request(|responder| WindowMessage::ShowMessageDialog {
+            title: title.into(),
+            message: message.into(),
+            buttons,
+            responder,
+        })? {
+            WindowResponse::MessageDialog(result) => Ok(result),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Shows a native dialog to pick files to open and blocks until the user closes it. Returns
+    /// the selected paths, which is empty if the dialog was cancelled.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** The filters can't be picked from, every extension they list is allowed at once.
+    pub fn open_file_dialog(&self, options: FileDialogOptions) -> Result<Vec<PathBuf>> {
+        match self.request(|responder| WindowMessage::OpenFileDialog { options, responder })? {
+            WindowResponse::OpenFileDialog(paths) => Ok(paths),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Shows a native dialog to pick where to save a file and blocks until the user closes it.
+    /// Returns `None` if the dialog was cancelled.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** The filters can't be picked from, every extension they list is allowed at once.
+    pub fn save_file_dialog(&self, options: FileDialogOptions) -> Result<Option<PathBuf>> {
+        match self.request(|responder| WindowMessage::SaveFileDialog { options, responder })? {
+            WindowResponse::SaveFileDialog(path) => Ok(path),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Returns whether the developer tools of the WebView are currently open. To follow it without
+    /// polling, listen to [`WindowEvent::DevtoolsOpened`] and [`WindowEvent::DevtoolsClosed`].
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported, always returns `false`.
+    pub fn is_devtools_open(&self) -> Result<bool> {
+        match self.request(WindowMessage::IsDevtoolsOpen)? {
+            WindowResponse::IsDevtoolsOpen(open) => Ok(open),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Opens the developer tools of the WebView. They must be enabled with
+    /// [`Attributes::devtools`], unless this is a debug build.
+    pub fn open_devtools(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::OpenDevtools(None)))
+    }
+
+    /// Opens the developer tools like [`WindowProxy::open_devtools`], attached or detached
+    /// regardless of [`Attributes::devtools_attachment`]. If they're already open, they're moved.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The developer tools are always detached.
+    pub fn open_devtools_with(&self, attachment: DevtoolsAttachment) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::OpenDevtools(Some(attachment)),
+        ))
+    }
+
+    /// Returns the height of the developer tools when they're attached to the window, or `None`
+    /// if they're closed or detached.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported, always returns `None`.
+    pub fn devtools_attached_height(&self) -> Result<Option<u32>> {
+        match self.request(WindowMessage::DevtoolsAttachedHeight)? {
+            WindowResponse::DevtoolsAttachedHeight(height) => Ok(height),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Closes the developer tools of the WebView.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported.
+    pub fn close_devtools(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::CloseDevtools))
+    }
+
+    /// Returns the back-forward history of the WebView, e.g. to show it in a dropdown next to
+    /// the address bar.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported, always returns an empty list.
+    pub fn back_forward_list(&self) -> Result<BackForwardList> {
+        match self.request(WindowMessage::BackForwardList)? {
+            WindowResponse::BackForwardList(list) => Ok(list),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Clears the back-forward history of the WebView, except for the current page.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** The current page is loaded again, losing its state like the scroll position.
+    /// - **Windows:** Unsupported, returns [`Error::Unsupported`]. The web engine has no way to
+    ///   clear it.
+    pub fn clear_history(&self) -> Result<()> {
+        if cfg!(target_os = "windows") {
+            return Err(Error::Unsupported("Clearing the history"));
+        }
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::ClearHistory))
+    }
+
+    /// Returns the navigation session of the WebView, including its back-forward history and
+    /// scroll positions, as an opaque blob. Store it to bring the window back where it was with
+    /// [`WindowProxy::restore_session_state`], e.g. after a crash.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported, always returns `None`.
+    /// - **macOS:** Requires macOS 12, returns `None` on older versions.
+    pub fn session_state(&self) -> Result<Option<Vec<u8>>> {
+        match self.request(WindowMessage::SessionState)? {
+            WindowResponse::SessionState(state) => Ok(state),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Restores a navigation session returned by [`WindowProxy::session_state`], reloading its
+    /// current page. The state must come from the same platform and web engine version.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported.
+    /// - **macOS:** Requires macOS 12, does nothing on older versions.
+    pub fn restore_session_state(&self, state: Vec<u8>) -> Result<()> {
+        self.proxy.send_message(Message::Window(
+            self.id,
+            WindowMessage::RestoreSessionState(state),
+        ))
+    }
+
+    /// Returns the cookies that would be sent to `url`. The window keeps handling events while
+    /// the web engine reads them.
+    ///
+    /// This blocks until the window answers, so it returns [`Error::MainThreadRequest`] when
+    /// called from the thread running the [`Application`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported, always returns no cookies.
+    pub fn cookies<S: Into<String>>(&self, url: S) -> Result<Vec<Cookie>> {
+        match self.request(|responder| WindowMessage::Cookies {
+            url: url.into(),
+            responder,
+        })? {
+            WindowResponse::Cookies(cookies) => Ok(cookies),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Stores `cookie`, replacing the one with the same name, domain and path if any.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn set_cookie(&self, cookie: Cookie) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::SetCookie(cookie)))
+    }
+
+    /// Deletes every cookie stored by the web engine, including the ones of the other windows.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn clear_all_cookies(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::ClearAllCookies))
+    }
+
+    /// Clears the cache of the resources loaded by the pages, so they're loaded again from their
+    /// source, e.g. the assets of an app served through a custom protocol.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Unsupported.
+    pub fn clear_cache(&self) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::ClearCache))
+    }
+
+    /// Deletes the website data of `types`, for every website and every window, e.g. for a
+    /// "reset app" button.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** `localStorage`, `sessionStorage` and IndexedDB are only cleared for the
+    ///   origin of the current page.
+    /// - **macOS:** Unsupported.
+    pub fn clear_storage(&self, types: StorageTypes) -> Result<()> {
+        self.proxy
+            .send_message(Message::Window(self.id, WindowMessage::ClearStorage(types)))
+    }
+
+    /// Sends several messages to the window at once. They're processed back-to-back, so the
+    /// window is only repainted once they're all applied, e.g. to restore a saved layout without
+    /// flickering.
+    pub fn apply(&self, messages: Vec<WindowMessage>) -> Result<()> {
+        self.proxy.send_message(Message::Batch(
+            messages
+                .into_iter()
+                .map(|message| Message::Window(self.id, message))
+                .collect(),
+        ))
+    }
+
+    /// Sends a message carrying a responder and blocks until the window answers it.
+    fn request<F: FnOnce(Sender<WindowResponse>) -> WindowMessage>(
+        &self,
+        message: F,
+    ) -> Result<WindowResponse> {
+        self.proxy.check_thread()?;
+        let (responder, response) = channel();
+        self.proxy
+            .send_message(Message::Window(self.id, message(responder)))?;
+        Ok(response.recv()?)
+    }
 }
 
 /// Provides a way to create and manage WebView windows.
@@ -282,18 +1578,49 @@ impl Application {
     /// imposed to eliminate any nasty surprises when porting to platforms that require it.
     pub fn new() -> Result<Self> {
         Ok(Self {
-            inner: InnerApplication::new()?,
+            inner: InnerApplication::new(None)?,
             //rpc_handler: None,
         })
     }
 
+    /// Builds a new application identified by `application_id`, a reverse-DNS name like
+    /// `"com.example.App"`. Only a single instance of an application with an ID runs at once, and
+    /// it receives the URLs other launches are given with [`Application::open_urls`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** The ID has to be a valid D-Bus name, or this fails.
+    /// - **Windows / macOS:** Unsupported, the ID is ignored.
+    pub fn with_id(application_id: &str) -> Result<Self> {
+        Ok(Self {
+            inner: InnerApplication::new(Some(application_id))?,
+        })
+    }
+
+    /// Opens the URLs the application was launched with, e.g. deep links of a URI scheme it
+    /// handles, which the `%u` field code of a `.desktop` file passes on the command line. They
+    /// are emitted as [`WryEvent::OpenUrl`] by the instance of the application running them.
+    ///
+    /// Returns `true` when an instance of the application built with [`Application::with_id`] is
+    /// already running and the URLs were sent to it, in which case this one can exit.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** The URLs are always opened by this instance.
+    pub fn open_urls(&self, urls: &[String]) -> bool {
+        self.inner.open_urls(urls)
+    }
+
     /// Adds a WebView window to the application. Returns its [`WindowProxy`] after created.
     ///
     /// [`Attributes`] is the configuration struct for you to customize the window.
     ///
     /// To create a default window, you could just pass `.add_window(Default::default(), None)`.
     pub fn add_window(&mut self, attributes: Attributes) -> Result<WindowProxy> {
-        let id = self.inner.create_webview(attributes, None, None)?;
+        attributes.check_storage_partition()?;
+        let id = self
+            .inner
+            .create_webview(attributes, None, None, Vec::new())?;
         Ok(self.window_proxy(id))
     }
 
@@ -303,22 +1630,168 @@ impl Application {
     ///
     /// [`Callback`] allows you to define rust function to be called on Javascript side for its window.
     ///
-    /// [`CustomProtocol`] allows you to define custom URL scheme to handle actions like loading
-    /// assets.
+    /// [`WindowCloseRequestedHandler`] decides whether the window may be closed when the user asks
+    /// for it, and returns `true` to keep it open. [`WindowEvent::CloseRequested`] is only emitted
+    /// when the window is allowed to close. It's not asked when the window is closed with
+    /// [`WindowProxy::close`]. It runs on the thread running the [`Application`], so it must not
+    /// wait for the [`WindowProxy`] it's given.
+    ///
+    /// [`CustomProtocol`]s allow you to define custom URL schemes to handle actions like loading
+    /// assets, one per scheme. Returns [`Error::DuplicateCustomProtocol`] when a scheme is given
+    /// more than once.
     ///
     /// To create a default window, you could just pass `.add_window(Default::default(), None)`.
     pub fn add_window_with_configs(
         &mut self,
         attributes: Attributes,
         handler: Option<WindowRpcHandler>,
-        custom_protocol: Option<CustomProtocol>,
+        close_requested_handler: Option<WindowCloseRequestedHandler>,
+        custom_protocols: Vec<CustomProtocol>,
     ) -> Result<WindowProxy> {
-        let id = self
-            .inner
-            .create_webview(attributes, handler, custom_protocol)?;
+        attributes.check_storage_partition()?;
+        CustomProtocol::check_unique(&custom_protocols)?;
+        let id = self.inner.create_webview(
+            attributes,
+            handler,
+            close_requested_handler,
+            custom_protocols,
+        )?;
         Ok(self.window_proxy(id))
     }
 
+    /// Adds JavaScript code to be initialized in every WebView window added after this call, like
+    /// [`Attributes::initialization_scripts`]. These scripts run before the ones of the window.
+    pub fn add_initialization_script(&mut self, js: &str) {
+        self.inner.add_initialization_script(js)
+    }
+
+    /// Sets the handler deciding whether a page may be left when its `beforeunload` handler asks
+    /// for confirmation, e.g. to guard unsaved work in web code. It's given the window and the
+    /// message of the page, and returns `true` to leave the page. This applies to WebView windows
+    /// added after this call. By default the web engine shows its own confirmation dialog.
+    ///
+    /// The handler runs on the thread running the [`Application`], so it must not wait for the
+    /// [`WindowProxy`] it's given.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn set_before_unload_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(WindowProxy, &str) -> bool + 'static,
+    {
+        self.inner.set_before_unload_handler(Rc::new(handler))
+    }
+
+    /// Sets the handler answering the dialogs opened by pages with `alert()`, `confirm()` and
+    /// `prompt()`, e.g. to show them with the look of the application. It's given the window and
+    /// the dialog, and returns the [`ScriptDialogAnswer`] of the same kind as the dialog. This
+    /// applies to WebView windows added after this call. By default the web engine shows its own
+    /// dialogs.
+    ///
+    /// The handler runs on the thread running the [`Application`], so it must not wait for the
+    /// [`WindowProxy`] it's given.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn set_script_dialog_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(WindowProxy, ScriptDialog) -> ScriptDialogAnswer + 'static,
+    {
+        self.inner.set_script_dialog_handler(Rc::new(handler))
+    }
+
+    /// Sets the handler intercepting the pastes of the user with the keyboard, e.g. to parse
+    /// tabular data or images that the web clipboard API doesn't give to pages. It's given the
+    /// window and the content of the clipboard, and returns `true` to prevent the page from
+    /// receiving the paste. This applies to WebView windows added after this call.
+    ///
+    /// The handler runs on the thread running the [`Application`], so it must not wait for the
+    /// [`WindowProxy`] it's given.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn set_paste_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(WindowProxy, ClipboardContent) -> bool + 'static,
+    {
+        self.inner.set_paste_handler(Rc::new(handler))
+    }
+
+    /// Sets the handler customizing the context menus of pages, e.g. to remove the items of the
+    /// web engine that don't fit the application, or add its own. It's given the window and the
+    /// menu, and returns the menu to show, or `None` to show no menu. Activating a custom item
+    /// emits [`WindowEvent::ContextMenuItem`]. This applies to WebView windows added after this
+    /// call.
+    ///
+    /// The handler runs on the thread running the [`Application`], so it must not wait for the
+    /// [`WindowProxy`] it's given.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn set_context_menu_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(WindowProxy, ContextMenu) -> Option<ContextMenu> + 'static,
+    {
+        self.inner.set_context_menu_handler(Rc::new(handler))
+    }
+
+    /// Sets the serializer encoding the RPC messages of WebView windows added after this call,
+    /// e.g. to send large payloads in a more compact format. The page side keeps calling
+    /// `window.rpc.call()` and `window.rpc.notify()` as usual. By default messages are JSON.
+    pub fn set_rpc_serializer<S>(&mut self, serializer: S)
+    where
+        S: RpcSerializer + 'static,
+    {
+        self.inner.set_rpc_serializer(Rc::new(serializer))
+    }
+
+    /// Limits the number of web processes shared by WebView windows added after this call.
+    ///
+    /// By default every window gets its own web process, which keeps windows isolated: a crash
+    /// or a memory leak in one page can't affect the others. With a limit, windows share a
+    /// single web context and are spread over at most `limit` processes instead. This bounds
+    /// memory usage with many windows at the cost of that isolation, and windows will also share
+    /// their cache. Each window still answers the requests to its custom protocol with its own
+    /// handler.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported. The web engine manages its own processes.
+    pub fn set_web_process_limit(&mut self, limit: u32) {
+        self.inner.set_web_process_limit(limit)
+    }
+
+    /// Configures when the web processes of WebView windows added after this call free memory,
+    /// e.g. to keep them within the budget of a device with little RAM. See
+    /// [`WryEvent::MemoryPressure`] to free memory on the application side too.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Needs WebKitGTK 2.34 and the `v2_36` feature, the settings are ignored
+    ///   without it.
+    /// - **Windows / macOS:** Unsupported. The web engine manages the memory of its processes.
+    pub fn set_memory_pressure_settings(&mut self, settings: MemoryPressureSettings) {
+        self.inner.set_memory_pressure_settings(settings)
+    }
+
+    /// Picks the input method module used for text entry, e.g. `"ibus"` or `"fcitx"`, overriding
+    /// the `GTK_IM_MODULE` environment variable. This fixes composition of CJK text in setups
+    /// where the default module doesn't talk to the running input method, like sandboxed apps.
+    ///
+    /// The WebView drives the input method itself and sets its purpose and hints from the focused
+    /// element, so use the `inputmode` and `type` HTML attributes to tune them per field.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported. The system input method is always used.
+    pub fn set_im_module(&mut self, module: &str) {
+        self.inner.set_im_module(module)
+    }
+
     /// Returns a [`ApplicationProxy`] for you to manage the application from other threads.
     pub fn application_proxy(&self) -> ApplicationProxy {
         ApplicationProxy {
@@ -338,22 +1811,81 @@ impl Application {
     pub fn run(self) {
         self.inner.run()
     }
+
+    /// Like [`Application::run`], but borrows the application instead of consuming it. This
+    /// returns once every window is closed, and can be called again afterwards, for example after
+    /// creating new windows with [`Application::add_window`].
+    pub fn run_return(&mut self) {
+        self.inner.run_return()
+    }
 }
 
+/// Finds the elements at a point of the page, returning the fields of a [`HitTestResult`].
+const HIT_TEST_SCRIPT: &str = r#"
+    (function(x, y) {
+        const element = document.elementFromPoint(x, y);
+        if (!element) {
+            return null;
+        }
+        const link = element.closest('a[href], area[href]');
+        const media = element.closest('img, video, audio');
+        return {
+            link_url: link ? link.href : null,
+            media_url: media ? (media.currentSrc || media.src || null) : null,
+            editable: element.isContentEditable || !!element.closest('input, textarea, select'),
+        };
+    })
+"#;
+
+/// Measures the page laid out at its minimum width, returning `[width, height]` in CSS pixels.
+const MIN_CONTENT_SIZE_SCRIPT: &str = r#"
+    (function() {
+        const root = document.documentElement;
+        const style = root.style.cssText;
+        root.style.width = 'min-content';
+        const rect = root.getBoundingClientRect();
+        root.style.cssText = style;
+        return [Math.ceil(rect.width), Math.ceil(rect.height)];
+    })();
+"#;
+
 trait App: Sized {
     type Proxy: AppProxy;
     type Id: Copy;
 
-    fn new() -> Result<Self>;
+    fn new(application_id: Option<&str>) -> Result<Self>;
+
+    fn open_urls(&self, urls: &[String]) -> bool;
 
     fn create_webview(
         &mut self,
         attributes: Attributes,
         rpc_handler: Option<WindowRpcHandler>,
-        custom_protocol: Option<CustomProtocol>,
+        close_requested_handler: Option<WindowCloseRequestedHandler>,
+        custom_protocols: Vec<CustomProtocol>,
     ) -> Result<Self::Id>;
 
+    fn add_initialization_script(&mut self, js: &str);
+
+    fn set_before_unload_handler(&mut self, handler: WindowBeforeUnloadHandler);
+
+    fn set_script_dialog_handler(&mut self, handler: WindowScriptDialogHandler);
+
+    fn set_paste_handler(&mut self, handler: WindowPasteHandler);
+
+    fn set_context_menu_handler(&mut self, handler: WindowContextMenuHandler);
+
+    fn set_rpc_serializer(&mut self, serializer: Rc<dyn RpcSerializer>);
+
+    fn set_web_process_limit(&mut self, limit: u32);
+
+    fn set_memory_pressure_settings(&mut self, settings: MemoryPressureSettings);
+
+    fn set_im_module(&mut self, module: &str);
+
     fn application_proxy(&self) -> Self::Proxy;
 
     fn run(self);
+
+    fn run_return(&mut self);
 }
@@ -72,6 +72,8 @@
 //! [gtk-rs]: https://crates.io/crates/gtk
 //!
 
+#[macro_use]
+extern crate bitflags;
 #[macro_use]
 extern crate serde;
 #[macro_use]
@@ -85,16 +87,25 @@ mod mimetype;
 pub mod webview;
 
 pub use application::{
-    Application, ApplicationProxy, Attributes, CustomProtocol, Icon, Message, WindowId,
-    WindowMessage, WindowProxy, WindowRpcHandler,
+    Application, ApplicationProxy, Attributes, CustomProtocol, FileDialogOptions, FileFilter,
+    Fullscreen, HitTestResult, Icon, MemoryPressureLevel, MemoryPressureSettings, Message,
+    MessageDialogButtons, MessageDialogResult, ModifiersState, ProgressBarState, RoundedRect,
+    ScriptCallback, SnapRegion, UserAttentionType, WindowButtonsPosition,
+    WindowCloseRequestedHandler, WindowEvent, WindowId, WindowMessage, WindowProxy, WindowResponse,
+    WindowRpcHandler, WindowTypeHint, WryEvent,
 };
 pub use serde_json::Value;
+pub use webview::{
+    BackForwardList, ClipboardContent, ContextMenu, ContextMenuAction, ContextMenuItem, Cookie,
+    DevtoolsAttachment, HistoryItem, LoadState, ProtocolReader, ProtocolRequest, ProtocolResponse,
+    RpcSerializer, ScriptDialog, ScriptDialogAnswer, StorageTypes,
+};
 pub(crate) use webview::{RpcHandler, RpcRequest, RpcResponse, WebView, WebViewBuilder};
 
 #[cfg(not(target_os = "linux"))]
 use winit::window::BadIcon;
 
-use std::sync::mpsc::{RecvError, SendError};
+use std::sync::mpsc::{RecvError, RecvTimeoutError, SendError};
 
 use url::ParseError;
 
@@ -122,9 +133,25 @@ pub enum Error {
     #[error(transparent)]
     ReceiverError(#[from] RecvError),
     #[error(transparent)]
+    ReceiverTimeoutError(#[from] RecvTimeoutError),
+    #[error(transparent)]
     SenderError(#[from] SendError<String>),
     #[error("Failed to send the message")]
     MessageSender,
+    #[error("The window answered with an unexpected response")]
+    UnexpectedResponse,
+    #[error("The thread running the application can't wait for its own answer")]
+    MainThreadRequest,
+    #[error("{0} is unsupported on this platform")]
+    Unsupported(&'static str),
+    #[error("The WebView can't display the MIME type: {0}")]
+    UnsupportedMimeType(String),
+    #[error("The cursor image has {0} bytes instead of 4 per pixel")]
+    InvalidCursorImage(usize),
+    #[error("The custom protocol {0} is registered more than once")]
+    DuplicateCustomProtocol(String),
+    #[error("The storage partition {0:?} isn't a valid directory name")]
+    InvalidStoragePartition(String),
     #[error(transparent)]
     Json(#[from] serde_json::Error),
     #[error(transparent)]
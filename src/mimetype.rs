@@ -70,6 +70,22 @@ impl MimeType {
 
         mime.to_string()
     }
+
+    /// split a mimeType like `text/html; charset=utf-8` into its essence and charset parameter.
+    pub fn split_charset(mime_type: &str) -> (&str, Option<&str>) {
+        let mut params = mime_type.split(';');
+        let essence = params.next().unwrap_or_default().trim();
+        let charset = params.find_map(|param| {
+            let mut pair = param.splitn(2, '=');
+            match (pair.next(), pair.next()) {
+                (Some(name), Some(value)) if name.trim().eq_ignore_ascii_case("charset") => {
+                    Some(value.trim().trim_matches('"'))
+                }
+                _ => None,
+            }
+        });
+        (essence, charset)
+    }
 }
 
 #[cfg(test)]
@@ -118,4 +134,17 @@ mod tests {
         let custom_scheme = MimeType::parse_from_uri("wry://tauri.studio").to_string();
         assert_eq!(custom_scheme, String::from("text/html"));
     }
+
+    #[test]
+    fn should_split_charset_from_mimetype() {
+        assert_eq!(MimeType::split_charset("text/html"), ("text/html", None));
+        assert_eq!(
+            MimeType::split_charset("text/html; charset=utf-8"),
+            ("text/html", Some("utf-8"))
+        );
+        assert_eq!(
+            MimeType::split_charset("text/plain;format=flowed; Charset=\"ISO-8859-1\""),
+            ("text/plain", Some("ISO-8859-1"))
+        );
+    }
 }
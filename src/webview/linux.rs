@@ -1,50 +1,107 @@
 use crate::mimetype::MimeType;
-use crate::webview::WV;
-use crate::{Error, Result, RpcHandler};
+use crate::webview::{
+    BackForwardList, ClipboardContent, ContextMenu, ContextMenuAction, ContextMenuItem, Cookie,
+    DevtoolsAttachment, EditorState, HistoryItem, LoadState, ProtocolHandler, ProtocolRequest,
+    ProtocolResponse, ScriptDialog, ScriptDialogAnswer, StorageTypes, ThreadedProtocolHandler,
+    WebViewAttributes, SELECTABLE_SCRIPT, UNSELECTABLE_SCRIPT, WV,
+};
+use crate::{Error, Result, RpcHandler, Value};
 
-use std::rc::Rc;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    ffi::{CStr, CString},
+    io::{Read, Seek, SeekFrom},
+    os::raw::{c_char, c_int, c_long},
+    ptr,
+    rc::Rc,
+    sync::{mpsc::channel, Arc, Mutex, PoisonError},
+    thread,
+    time::{Duration, SystemTime},
+};
 
-use gdk::RGBA;
-use gio::Cancellable;
-use glib::{Bytes, FileError};
-use gtk::{ApplicationWindow as Window, ContainerExt, WidgetExt};
+use gdk::{
+    keys::constants as keys, Cursor, EventType, ModifierType, WindowEdge, WindowExt, RGBA,
+    SELECTION_CLIPBOARD,
+};
+use gdk_pixbuf::{Colorspace, Pixbuf};
+use gio::{Cancellable, IOErrorEnum, InputStreamExt, SimpleAction};
+use glib::{
+    timeout_add_local, Bytes, Cast, Continue, FileError, IsA, MainContext, ObjectExt,
+    PRIORITY_DEFAULT,
+};
+use gtk::{
+    ApplicationWindow as Window, Clipboard, ContainerExt, Grid, GridExt, GtkWindowExt, Inhibit,
+    PositionType, Widget, WidgetExt,
+};
 use url::Url;
 use webkit2gtk::{
-    SecurityManagerExt, SettingsExt, URISchemeRequestExt, UserContentInjectedFrames,
-    UserContentManager, UserContentManagerExt, UserScript, UserScriptInjectionTime, WebContext,
-    WebContextExt, WebView, WebViewExt, WebViewExtManual,
+    BackForwardListExt, BackForwardListItemExt, ContextMenuAction as WebKitContextMenuAction,
+    ContextMenuExt, ContextMenuItem as WebKitContextMenuItem, ContextMenuItemExt, CookieManager,
+    EditorStateExt, EditorTypingAttributes, FindControllerExt, FindOptions, HitTestResultExt,
+    JavascriptResult, LoadEvent, NetworkError, ScriptDialogType, SecurityManagerExt, SettingsExt,
+    URISchemeRequest, URISchemeRequestExt, UserContentInjectedFrames, UserContentManager,
+    UserContentManagerExt, UserScript, UserScriptInjectionTime, WebContext, WebContextExt,
+    WebInspectorExt, WebView, WebViewExt, WebViewExtManual, WebViewSessionState,
+    WebsiteDataManagerExt, WebsiteDataTypes,
 };
 
 pub struct InnerWebView {
     webview: Rc<WebView>,
+    grid: Grid,
+    devtools_open: Rc<Cell<bool>>,
+    devtools_attachment: DevtoolsAttachment,
+    restored_user_agent: Rc<RefCell<Option<String>>>,
+    cursor: Rc<RefCell<Option<Cursor>>>,
+    // What the zoom level of WebKit is multiplied by, on top of the one of the page.
+    zoom_factor: Rc<Cell<f64>>,
+    drag_regions: Rc<Cell<bool>>,
+    // The selected match of the search and the number of matches, once counted.
+    find_matches: Rc<Cell<(u32, u32)>>,
 }
 
 impl WV for InnerWebView {
     type Window = Window;
 
-    fn new<F: 'static + Fn(&str) -> Result<Vec<u8>>>(
+    fn new(
         window: &Window,
         scripts: Vec<String>,
         url: Option<Url>,
-        transparent: bool,
-        custom_protocol: Option<(String, F)>,
+        attributes: WebViewAttributes,
+        custom_protocols: Vec<(String, ProtocolHandler)>,
         rpc_handler: Option<RpcHandler>,
+        web_context: Option<WebContext>,
     ) -> Result<Self> {
         // Webview widget
         let manager = UserContentManager::new();
-        let context = WebContext::new();
+        let context = web_context.unwrap_or_default();
+        if let Some(languages) = &attributes.accept_languages {
+            let languages: Vec<&str> = languages.iter().map(String::as_str).collect();
+            context.set_preferred_languages(&languages);
+        }
         let webview = Rc::new(WebView::new_with_context_and_user_content_manager(
             &context, &manager,
         ));
 
         // Message handler
-        let wv = Rc::clone(&webview);
-        manager.register_script_message_handler("external");
-        manager.connect_script_message_received(move |_m, msg| {
-            if let (Some(js), Some(context)) = (msg.get_value(), msg.get_global_context()) {
-                if let Some(js) = js.to_string(&context) {
-                    if let Some(rpc_handler) = rpc_handler.as_ref() {
-                        match super::rpc_proxy(js, rpc_handler) {
+        if !attributes.rpc_bridge_disabled {
+            let wv = Rc::clone(&webview);
+            let serializer = attributes.rpc_serializer.clone();
+            manager.register_script_message_handler("external");
+            // Other script message handlers post to the same signal, with their name as detail.
+            let _ =
+                manager.connect_local("script-message-received::external", false, move |values| {
+                    let msg = values
+                        .get(1)
+                        .and_then(|value| value.get::<JavascriptResult>().ok().flatten());
+                    // The values replacing the deprecated ones can't be read with these bindings.
+                    #[allow(deprecated)]
+                    let js = msg.and_then(|msg| {
+                        let context = msg.get_global_context()?;
+                        msg.get_value()?.to_string(&context)
+                    });
+                    if let (Some(js), Some(rpc_handler)) = (js, rpc_handler.as_ref()) {
+                        match super::rpc_proxy(js, rpc_handler, serializer.as_deref()) {
                             Ok(result) => {
                                 if let Some(ref script) = result {
                                     let cancellable: Option<&Cancellable> = None;
@@ -56,12 +113,19 @@ impl WV for InnerWebView {
                             }
                         }
                     }
-                }
-            }
-        });
+                    None
+                });
+        }
 
-        window.add(&*webview);
-        webview.grab_focus();
+        // Put the webview in a grid so native widgets can be laid out around it
+        let grid = Grid::new();
+        webview.set_hexpand(true);
+        webview.set_vexpand(true);
+        grid.add(&*webview);
+        window.add(&grid);
+        if !attributes.focus_disabled {
+            webview.grab_focus();
+        }
 
         // Enable webgl, webaudio, canvas features and others as default.
         if let Some(settings) = WebViewExt::get_settings(&*webview) {
@@ -77,6 +141,21 @@ impl WV for InnerWebView {
             // Enable Smooth scrooling
             settings.set_enable_smooth_scrolling(true);
 
+            settings.set_enable_back_forward_navigation_gestures(
+                attributes.back_forward_navigation_gestures,
+            );
+            settings
+                .set_allow_file_access_from_file_urls(attributes.allow_file_access_from_file_urls);
+            settings.set_allow_universal_access_from_file_urls(
+                attributes.allow_universal_access_from_file_urls,
+            );
+            if let Some(user_agent) = &attributes.user_agent {
+                settings.set_user_agent(Some(user_agent));
+            }
+            if attributes.devtools {
+                settings.set_enable_developer_extras(true);
+            }
+
             debug_assert_eq!(
                 {
                     settings.set_enable_write_console_messages_to_stdout(true);
@@ -87,55 +166,515 @@ impl WV for InnerWebView {
         }
 
         // Transparent
-        if transparent {
+        if attributes.transparent {
             webview.set_background_color(&RGBA {
                 red: 0.,
                 green: 0.,
                 blue: 0.,
                 alpha: 0.,
             });
+        } else if let Some((red, green, blue, alpha)) = attributes.background_color {
+            webview.set_background_color(&RGBA {
+                red: red as f64 / 255.,
+                green: green as f64 / 255.,
+                blue: blue as f64 / 255.,
+                alpha: alpha as f64 / 255.,
+            });
+        }
+
+        // Zoom
+        if let Some(zoom) = attributes.zoom_level {
+            webview.set_zoom_level(zoom);
+        }
+        // WebKitGTK renders at the scale factor of the monitor. The zoom level is multiplied by it
+        // to follow the monitor, or makes up the difference to the device scale factor asked for.
+        // The factor is updated when the window moves to a monitor with another scale factor.
+        let auto_dpi_zoom = attributes.auto_dpi_zoom;
+        let device_scale_factor = attributes.device_scale_factor;
+        let monitor_zoom = move |webview: &WebView| {
+            let scale_factor = monitor_scale_factor(webview) as f64;
+            let zoom = if auto_dpi_zoom { scale_factor } else { 1. };
+            zoom * device_scale_factor.map_or(1., |device| device / scale_factor)
+        };
+        let zoom_factor = Rc::new(Cell::new(1.));
+        if auto_dpi_zoom || device_scale_factor.is_some() {
+            zoom_factor.set(monitor_zoom(&webview));
+            webview.set_zoom_level(webview.get_zoom_level() * zoom_factor.get());
+            let factor = zoom_factor.clone();
+            webview.connect_property_scale_factor_notify(move |webview| {
+                let new = monitor_zoom(webview);
+                let old = factor.replace(new);
+                webview.set_zoom_level(webview.get_zoom_level() / old * new);
+            });
+        }
+
+        // Window title
+        if attributes.html_title_as_window_title {
+            let window_ = window.clone();
+            webview.connect_property_title_notify(move |webview| {
+                if let Some(title) = webview.get_title() {
+                    window_.set_title(&title);
+                }
+            });
+        }
+
+        // Resize border
+        if let Some(border) = attributes.resize_border {
+            let border = border as i32;
+            let window_ = window.clone();
+            let in_border = Cell::new(false);
+            webview.connect_motion_notify_event(move |webview, event| {
+                let edge = resize_edge(&window_, webview, event.get_position(), border);
+                if let Some(gdk_window) = webview.get_window() {
+                    match edge {
+                        Some(edge) => {
+                            let cursor =
+                                Cursor::from_name(&gdk_window.get_display(), edge_cursor(edge));
+                            gdk_window.set_cursor(cursor.as_ref());
+                            in_border.set(true);
+                            return Inhibit(true);
+                        }
+                        // Let WebKit set the cursor of the page again.
+                        None if in_border.replace(false) => gdk_window.set_cursor(None),
+                        None => {}
+                    }
+                }
+                Inhibit(false)
+            });
+            let window_ = window.clone();
+            webview.connect_button_press_event(move |webview, event| {
+                if event.get_button() == 1 && event.get_event_type() == EventType::ButtonPress {
+                    if let Some(edge) = resize_edge(&window_, webview, event.get_position(), border)
+                    {
+                        let (x_root, y_root) = event.get_root();
+                        window_.begin_resize_drag(
+                            edge,
+                            1,
+                            x_root as i32,
+                            y_root as i32,
+                            event.get_time(),
+                        );
+                        return Inhibit(true);
+                    }
+                }
+                Inhibit(false)
+            });
+        }
+
+        // Page load
+        if let Some(handler) = attributes.page_load_handler {
+            let handler = Rc::new(handler);
+            // WebKit still reports the end of a failed load, which was already reported, and of a
+            // load cancelled by another one, which never finished.
+            let failed = Rc::new(Cell::new(false));
+            let cancelled = Rc::new(Cell::new(false));
+            let handler_ = handler.clone();
+            let failed_ = failed.clone();
+            let cancelled_ = cancelled.clone();
+            webview.connect_load_changed(move |webview, event| {
+                let state = match event {
+                    LoadEvent::Started => {
+                        failed_.set(false);
+                        cancelled_.set(false);
+                        LoadState::Started
+                    }
+                    LoadEvent::Committed => LoadState::Committed,
+                    LoadEvent::Finished if !failed_.get() && !cancelled_.get() => {
+                        LoadState::Finished
+                    }
+                    _ => return,
+                };
+                let uri = webview.get_uri().map(|uri| uri.to_string());
+                handler_(uri.as_deref().unwrap_or_default(), state);
+            });
+            webview.connect_load_failed(move |_, _, uri, error| {
+                // Loads cancelled by another one aren't failures.
+                if error.kind::<NetworkError>() == Some(NetworkError::Cancelled) {
+                    cancelled.set(true);
+                } else {
+                    failed.set(true);
+                    handler(uri, LoadState::Failed);
+                }
+                false
+            });
+        }
+
+        // Error page
+        if let Some(html) = attributes.error_page {
+            webview.connect_load_failed(move |webview, _, uri, error| {
+                // Loads cancelled by another one aren't failures.
+                if error.kind::<NetworkError>() == Some(NetworkError::Cancelled) {
+                    return false;
+                }
+                let html = html
+                    .replace("{url}", &escape_html(uri))
+                    .replace("{error}", &escape_html(&error.to_string()));
+                // Keeps the failed URI as the one of the page, so reloading retries it.
+                webview.load_alternate_html(&html, uri, None);
+                true
+            });
+        }
+
+        // Paste interception
+        if let Some(handler) = attributes.paste_handler {
+            webview.connect_key_press_event(move |_, event| {
+                let state = event.get_state();
+                let key = event.get_keyval();
+                let paste = (state.contains(ModifierType::CONTROL_MASK)
+                    && (key == keys::v || key == keys::V))
+                    || (state.contains(ModifierType::SHIFT_MASK) && key == keys::Insert);
+                if !paste {
+                    return Inhibit(false);
+                }
+                let clipboard = Clipboard::get(&SELECTION_CLIPBOARD);
+                let content = ClipboardContent {
+                    text: clipboard.wait_for_text().map(String::from),
+                    image: if clipboard.wait_is_image_available() {
+                        clipboard
+                            .wait_for_image()
+                            .and_then(|image| image.save_to_bufferv("png", &[]).ok())
+                    } else {
+                        None
+                    },
+                };
+                Inhibit(handler(content))
+            });
+        }
+
+        // Context menu
+        if let Some(handler) = attributes.context_menu_handler {
+            let item_handler = attributes.context_menu_item_handler;
+            webview.connect_context_menu(move |_, menu, _, hit_test| {
+                let mut others = 0;
+                let mut builtin_items: Vec<(ContextMenuAction, WebKitContextMenuItem)> = menu
+                    .get_items()
+                    .into_iter()
+                    .filter(|item| !item.is_separator())
+                    .map(|item| (context_menu_action(&item, &mut others), item))
+                    .collect();
+                let mut builtin = builtin_items.iter();
+                let context_menu = ContextMenu {
+                    items: menu
+                        .get_items()
+                        .iter()
+                        .filter_map(|item| {
+                            if item.is_separator() {
+                                Some(ContextMenuItem::Separator)
+                            } else {
+                                let (action, _) = builtin.next()?;
+                                Some(ContextMenuItem::Builtin { action: *action })
+                            }
+                        })
+                        .collect(),
+                    link_url: hit_test.get_link_uri().map(String::from),
+                    media_url: hit_test
+                        .get_image_uri()
+                        .or_else(|| hit_test.get_media_uri())
+                        .map(String::from),
+                    editable: hit_test.context_is_editable(),
+                    selection: hit_test.context_is_selection(),
+                };
+                let context_menu = match handler(context_menu) {
+                    Some(context_menu) => context_menu,
+                    // Handling the signal prevents the menu from being shown.
+                    None => return true,
+                };
+
+                menu.remove_all();
+                for item in context_menu.items {
+                    match item {
+                        ContextMenuItem::Builtin { action } => {
+                            // The items of the web engine can only be reused, not created.
+                            if let Some(i) = builtin_items.iter().position(|(a, _)| *a == action) {
+                                menu.append(&builtin_items.remove(i).1);
+                            }
+                        }
+                        ContextMenuItem::Custom { id, label } => {
+                            let action = SimpleAction::new("wry-context-menu-item", None);
+                            if let Some(item_handler) = item_handler.clone() {
+                                action.connect_activate(move |_, _| item_handler(&id));
+                            }
+                            menu.append(&WebKitContextMenuItem::from_gaction(
+                                &action, &label, None,
+                            ));
+                        }
+                        ContextMenuItem::Separator => {
+                            menu.append(&WebKitContextMenuItem::new_separator());
+                        }
+                    }
+                }
+                false
+            });
+        }
+
+        // beforeunload confirmation
+        if let Some(handler) = attributes.before_unload_handler {
+            webview.connect_script_dialog(move |_, dialog| {
+                if dialog.get_dialog_type() != ScriptDialogType::BeforeUnloadConfirm {
+                    return false;
+                }
+                dialog.confirm_set_confirmed(handler(dialog.get_message()));
+                true
+            });
+        }
+
+        // alert(), confirm() and prompt()
+        if let Some(handler) = attributes.script_dialog_handler {
+            webview.connect_script_dialog(move |_, dialog| {
+                let message = dialog.get_message().to_string();
+                match dialog.get_dialog_type() {
+                    ScriptDialogType::Alert => {
+                        handler(ScriptDialog::Alert { message });
+                    }
+                    ScriptDialogType::Confirm => {
+                        let answer = handler(ScriptDialog::Confirm { message });
+                        dialog.confirm_set_confirmed(answer == ScriptDialogAnswer::Confirm(true));
+                    }
+                    ScriptDialogType::Prompt => {
+                        let default_text = dialog.prompt_get_default_text().to_string();
+                        // The prompt returns null unless a text is set.
+                        if let ScriptDialogAnswer::Prompt(Some(text)) =
+                            handler(ScriptDialog::Prompt {
+                                message,
+                                default_text,
+                            })
+                        {
+                            dialog.prompt_set_text(&text);
+                        }
+                    }
+                    _ => return false,
+                }
+                true
+            });
+        }
+
+        let devtools_attachment = attributes.devtools_attachment;
+
+        // Keep track of the inspector since WebKit doesn't expose whether it's shown. Attaching
+        // or detaching the open inspector emits the signals again, so only changes are reported.
+        let devtools_open = Rc::new(Cell::new(false));
+        if let Some(inspector) = webview.get_inspector() {
+            let handler = attributes.devtools_handler;
+            let set_open = move |open: &Cell<bool>, value: bool| {
+                if open.replace(value) != value {
+                    if let Some(handler) = &handler {
+                        handler(value);
+                    }
+                }
+            };
+            let set_open = Rc::new(set_open);
+            let (open, set) = (devtools_open.clone(), set_open.clone());
+            inspector.connect_open_window(move |_| {
+                set(&open, true);
+                false
+            });
+            let (open, set) = (devtools_open.clone(), set_open.clone());
+            inspector.connect_attach(move |_| {
+                set(&open, true);
+                false
+            });
+            let open = devtools_open.clone();
+            inspector.connect_closed(move |_| set_open(&open, false));
+        }
+
+        // WebKit reports the typing attributes but not the selection, a script posts whether the
+        // page has one. Both are merged into one state and only changes are reported.
+        if let (Some(handler), Some(editor)) =
+            (attributes.editor_state_handler, webview.get_editor_state())
+        {
+            let state = Rc::new(Cell::new(EditorState::default()));
+            let report = Rc::new(move |update: &dyn Fn(&mut EditorState)| {
+                let mut new = state.get();
+                update(&mut new);
+                if state.replace(new) != new {
+                    handler(new);
+                }
+            });
+            let report_ = report.clone();
+            editor.connect_property_typing_attributes_notify(move |editor| {
+                let attributes =
+                    EditorTypingAttributes::from_bits_truncate(editor.get_typing_attributes());
+                report_(&|state| {
+                    state.bold = attributes.contains(EditorTypingAttributes::BOLD);
+                    state.italic = attributes.contains(EditorTypingAttributes::ITALIC);
+                    state.underline = attributes.contains(EditorTypingAttributes::UNDERLINE);
+                });
+            });
+            manager.register_script_message_handler("wrySelection");
+            let _ = manager.connect_local(
+                "script-message-received::wrySelection",
+                false,
+                move |values| {
+                    let msg = values
+                        .get(1)
+                        .and_then(|value| value.get::<JavascriptResult>().ok().flatten());
+                    // The values replacing the deprecated ones can't be read with these bindings.
+                    #[allow(deprecated)]
+                    let selection = msg.and_then(|msg| {
+                        let context = msg.get_global_context()?;
+                        Some(msg.get_value()?.to_boolean(&context))
+                    });
+                    if let Some(selection) = selection {
+                        report(&|state| state.selection = selection);
+                    }
+                    None
+                },
+            );
+            let script = UserScript::new(
+                "document.addEventListener('selectionchange', () => { const selection = document.getSelection(); window.webkit.messageHandlers.wrySelection.postMessage(!!selection && !selection.isCollapsed); });",
+                UserContentInjectedFrames::TopFrame,
+                UserScriptInjectionTime::Start,
+                &[],
+                &[],
+            );
+            manager.add_script(&script);
+        }
+
+        // WebKit counts the matches but doesn't say which one is selected, it's tracked as the
+        // selection moves through them. The matches of a new search are reported once counted.
+        let find_matches = Rc::new(Cell::new((0, 0)));
+        if let (Some(handler), Some(controller)) = (
+            attributes.find_result_handler,
+            webview.get_find_controller(),
+        ) {
+            let handler = Rc::new(handler);
+            let (matches, handler_) = (find_matches.clone(), handler.clone());
+            controller.connect_counted_matches(move |_, total| {
+                let current = matches.get().0.max(1).min(total);
+                matches.set((current, total));
+                handler_(current, total);
+            });
+            let matches = find_matches.clone();
+            controller.connect_found_text(move |_, _| {
+                let (current, total) = matches.get();
+                if total > 0 {
+                    handler(current, total);
+                }
+            });
         }
 
+        // The script posts the presses in drag regions, the window is then moved from the press
+        // while its button is still held. Presses already released aren't dragged.
+        let drag_regions = Rc::new(Cell::new(false));
+        let press: Rc<Cell<Option<(f64, f64, u32)>>> = Default::default();
+        let press_ = press.clone();
+        webview.connect_button_press_event(move |_, event| {
+            if event.get_button() == 1 && event.get_event_type() == EventType::ButtonPress {
+                let (x_root, y_root) = event.get_root();
+                press_.set(Some((x_root, y_root, event.get_time())));
+            }
+            Inhibit(false)
+        });
+        let press_ = press.clone();
+        webview.connect_button_release_event(move |_, _| {
+            press_.set(None);
+            Inhibit(false)
+        });
+        manager.register_script_message_handler("wryDrag");
+        let (enabled, window_) = (drag_regions.clone(), window.clone());
+        let _ = manager.connect_local("script-message-received::wryDrag", false, move |_| {
+            if let Some((x_root, y_root, time)) = press.take().filter(|_| enabled.get()) {
+                window_.begin_move_drag(1, x_root as i32, y_root as i32, time);
+            }
+            None
+        });
+        manager.add_script(&UserScript::new(
+            DRAG_REGIONS_SCRIPT,
+            UserContentInjectedFrames::TopFrame,
+            UserScriptInjectionTime::Start,
+            &[],
+            &[],
+        ));
+
+        // Restore the user agent replaced for a reload once it's done, so only the reloaded
+        // document is requested with it.
+        let restored_user_agent: Rc<RefCell<Option<String>>> = Default::default();
+        let restored = restored_user_agent.clone();
+        webview.connect_load_changed(move |webview, event| {
+            if event == LoadEvent::Finished {
+                if let Some(user_agent) = restored.borrow_mut().take() {
+                    if let Some(settings) = WebViewExt::get_settings(webview) {
+                        settings.set_user_agent(Some(&user_agent));
+                    }
+                }
+            }
+        });
+
+        // WebKit sets the cursor of the page as the pointer moves over it, put the custom one back.
+        let cursor: Rc<RefCell<Option<Cursor>>> = Default::default();
+        let custom = cursor.clone();
+        webview.connect_motion_notify_event(move |webview, _| {
+            restore_cursor(webview, &custom);
+            Inhibit(false)
+        });
+        let custom = cursor.clone();
+        webview.connect_enter_notify_event(move |webview, _| {
+            restore_cursor(webview, &custom);
+            Inhibit(false)
+        });
+
         if window.get_visible() {
             window.show_all();
         }
 
-        let w = Self { webview };
+        let w = Self {
+            webview,
+            grid,
+            devtools_open,
+            devtools_attachment,
+            restored_user_agent,
+            cursor,
+            zoom_factor,
+            drag_regions,
+            find_matches,
+        };
 
         // Initialize scripts
-        w.init("window.external={invoke:function(x){window.webkit.messageHandlers.external.postMessage(x);}}")?;
+        if !attributes.rpc_bridge_disabled {
+            w.init("window.external={invoke:function(x){window.webkit.messageHandlers.external.postMessage(x);}}")?;
+        }
         for js in scripts {
             w.init(&js)?;
         }
 
-        // Custom protocol
-        if let Some((name, handler)) = custom_protocol {
-            context
-                .get_security_manager()
-                .unwrap()
-                .register_uri_scheme_as_secure(&name);
-            context.register_uri_scheme(&name.clone(), move |request| {
-                if let Some(uri) = request.get_uri() {
-                    let uri = uri.as_str();
-
-                    match handler(uri) {
-                        Ok(buffer) => {
-                            let mime = MimeType::parse(&buffer, uri);
-                            let input = gio::MemoryInputStream::from_bytes(&Bytes::from(&buffer));
-                            request.finish(&input, buffer.len() as i64, Some(&mime))
+        // Custom protocols
+        for (name, handler) in custom_protocols {
+            let security_manager = context.get_security_manager().unwrap();
+            security_manager.register_uri_scheme_as_secure(&name);
+            if attributes.custom_protocol_cors {
+                security_manager.register_uri_scheme_as_cors_enabled(&name);
+            }
+            if attributes.custom_protocol_local {
+                security_manager.register_uri_scheme_as_local(&name);
+            }
+            let respond: SchemeHandler = match handler {
+                ProtocolHandler::Local(handler) => {
+                    let handler = Rc::new(handler);
+                    Box::new(move |request| {
+                        if let Some(uri) = request.get_uri() {
+                            let handler = handler.clone();
+                            let request_ = request.clone();
+                            let uri = uri.to_string();
+                            read_protocol_request(request, &uri.clone(), move |protocol_request| {
+                                let content = handler(&protocol_request)
+                                    .and_then(|response| range(response, &protocol_request));
+                                finish_request(&request_, &uri, content);
+                            });
+                        } else {
+                            request.finish_error(&mut glib::Error::new(
+                                FileError::Exist,
+                                "Could not get uri.",
+                            ));
                         }
-                        Err(_) => request.finish_error(&mut glib::Error::new(
-                            FileError::Exist,
-                            "Could not get requested file.",
-                        )),
-                    }
-                } else {
-                    request.finish_error(&mut glib::Error::new(
-                        FileError::Exist,
-                        "Could not get uri.",
-                    ));
+                    })
                 }
-            });
+                ProtocolHandler::Threaded(handler) => threaded_scheme_handler(
+                    handler,
+                    attributes.custom_protocol_concurrency,
+                    attributes.custom_protocol_timeout,
+                ),
+            };
+            register_custom_protocol(&context, &w.webview, &name, respond);
         }
 
         // Navigation
@@ -151,9 +690,1149 @@ impl WV for InnerWebView {
         self.webview.run_javascript(js, cancellable, |_| ());
         Ok(())
     }
+
+    // The values replacing the deprecated ones can't be read with these bindings.
+    #[allow(deprecated)]
+    fn eval_with_callback(&self, js: &str, callback: Box<dyn FnOnce(Result<Value>)>) -> Result<()> {
+        // The result is passed as JSON, the JavaScriptCore values can't be converted otherwise.
+        let js = format!("JSON.stringify(eval({}))", serde_json::to_string(js)?);
+        // The callback isn't `Send`, it's called from a future running on the main thread instead
+        // of the callback of `run_javascript`.
+        let result = self.webview.run_javascript_future(&js);
+        MainContext::default().spawn_local(async move {
+            callback(result.await.map_err(Into::into).and_then(|result| {
+                match (result.get_value(), result.get_global_context()) {
+                    (Some(value), Some(context)) if value.is_string(&context) => {
+                        match value.to_string(&context) {
+                            Some(json) => Ok(serde_json::from_str(&json)?),
+                            None => Ok(Value::Null),
+                        }
+                    }
+                    // `JSON.stringify` returns `undefined` for values it can't represent.
+                    _ => Ok(Value::Null),
+                }
+            }))
+        });
+        Ok(())
+    }
+
+    fn load_url(&self, url: Url) -> Result<()> {
+        self.webview.stop_loading();
+        self.webview.load_uri(url.as_str());
+        Ok(())
+    }
+
+    fn reload(&self, bypass_cache: bool) -> Result<()> {
+        if bypass_cache {
+            self.webview.reload_bypass_cache();
+        } else {
+            self.webview.reload();
+        }
+        Ok(())
+    }
+
+    fn set_cursor_image(
+        &self,
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    ) -> Result<()> {
+        let pixbuf = Pixbuf::from_mut_slice(
+            rgba,
+            Colorspace::Rgb,
+            true,
+            8,
+            width as i32,
+            height as i32,
+            width as i32 * 4,
+        );
+        let cursor = Cursor::from_pixbuf(
+            &self.webview.get_display(),
+            &pixbuf,
+            hotspot_x as i32,
+            hotspot_y as i32,
+        );
+        *self.cursor.borrow_mut() = Some(cursor);
+        if let Some(gdk_window) = self.webview.get_window() {
+            gdk_window.set_cursor(self.cursor.borrow().as_ref());
+        }
+        Ok(())
+    }
+
+    fn reset_cursor(&self) -> Result<()> {
+        if self.cursor.borrow_mut().take().is_some() {
+            if let Some(gdk_window) = self.webview.get_window() {
+                gdk_window.set_cursor(None);
+            }
+        }
+        Ok(())
+    }
+
+    fn set_user_agent(&self, user_agent: &str) -> Result<()> {
+        // The user agent given now replaces the one a reload would restore.
+        self.restored_user_agent.borrow_mut().take();
+        if let Some(settings) = WebViewExt::get_settings(&*self.webview) {
+            settings.set_user_agent(Some(user_agent));
+        }
+        Ok(())
+    }
+
+    fn reload_with_user_agent(&self, user_agent: &str, restore: bool) -> Result<()> {
+        if let Some(settings) = WebViewExt::get_settings(&*self.webview) {
+            let previous = settings.get_user_agent().map(String::from);
+            settings.set_user_agent(Some(user_agent));
+            *self.restored_user_agent.borrow_mut() = previous.filter(|_| restore);
+        }
+        self.webview.reload();
+        Ok(())
+    }
+
+    fn set_zoom_level(&self, zoom: f64) -> Result<()> {
+        self.webview.set_zoom_level(zoom * self.zoom_factor.get());
+        Ok(())
+    }
+
+    fn zoom_level(&self) -> f64 {
+        self.webview.get_zoom_level() / self.zoom_factor.get()
+    }
+
+    fn go_back(&self) -> Result<()> {
+        self.webview.go_back();
+        Ok(())
+    }
+
+    fn go_forward(&self) -> Result<()> {
+        self.webview.go_forward();
+        Ok(())
+    }
+
+    fn can_go_back(&self) -> bool {
+        self.webview.can_go_back()
+    }
+
+    fn can_go_forward(&self) -> bool {
+        self.webview.can_go_forward()
+    }
+
+    fn url(&self) -> Option<String> {
+        self.webview.get_uri().map(|uri| uri.to_string())
+    }
+
+    fn title(&self) -> Option<String> {
+        self.webview.get_title().map(|title| title.to_string())
+    }
+
+    fn load_bytes(&self, bytes: &[u8], mime_type: &str, base_url: Option<Url>) -> Result<()> {
+        let (essence, charset) = MimeType::split_charset(mime_type);
+        if !self.webview.can_show_mime_type(essence) {
+            return Err(Error::UnsupportedMimeType(mime_type.to_string()));
+        }
+        self.webview.stop_loading();
+        self.webview.load_bytes(
+            &Bytes::from(bytes),
+            Some(essence),
+            charset,
+            base_url.as_ref().map(Url::as_str),
+        );
+        Ok(())
+    }
+
+    fn load_html(&self, html: &str, base_url: Option<Url>) -> Result<()> {
+        self.webview.stop_loading();
+        self.webview
+            .load_html(html, base_url.as_ref().map(Url::as_str));
+        Ok(())
+    }
+
+    fn set_editable(&self, editable: bool) -> Result<()> {
+        self.webview.set_editable(editable);
+        Ok(())
+    }
+
+    fn find(&self, text: &str, case_sensitive: bool) -> Result<()> {
+        if let Some(controller) = self.webview.get_find_controller() {
+            let mut options = FindOptions::WRAP_AROUND;
+            if !case_sensitive {
+                options |= FindOptions::CASE_INSENSITIVE;
+            }
+            self.find_matches.set((1, 0));
+            controller.search(text, options.bits(), u32::MAX);
+            controller.count_matches(text, options.bits(), u32::MAX);
+        }
+        Ok(())
+    }
+
+    fn find_next(&self) -> Result<()> {
+        if let Some(controller) = self.webview.get_find_controller() {
+            let (current, total) = self.find_matches.get();
+            if total > 0 {
+                self.find_matches.set((current % total + 1, total));
+            }
+            controller.search_next();
+        }
+        Ok(())
+    }
+
+    fn find_previous(&self) -> Result<()> {
+        if let Some(controller) = self.webview.get_find_controller() {
+            let (current, total) = self.find_matches.get();
+            if total > 0 {
+                let previous = if current > 1 { current - 1 } else { total };
+                self.find_matches.set((previous, total));
+            }
+            controller.search_previous();
+        }
+        Ok(())
+    }
+
+    fn stop_find(&self) -> Result<()> {
+        if let Some(controller) = self.webview.get_find_controller() {
+            controller.search_finish();
+        }
+        self.find_matches.set((0, 0));
+        Ok(())
+    }
+
+    fn set_enable_drag_region(&self, enabled: bool) -> Result<()> {
+        self.drag_regions.set(enabled);
+        Ok(())
+    }
+
+    fn set_selectable(&self, selectable: bool) -> Result<()> {
+        if let Some(settings) = WebViewExt::get_settings(&*self.webview) {
+            settings.set_javascript_can_access_clipboard(selectable);
+        }
+        self.eval(if selectable {
+            SELECTABLE_SCRIPT
+        } else {
+            UNSELECTABLE_SCRIPT
+        })
+    }
+
+    fn back_forward_list(&self) -> BackForwardList {
+        let list = match self.webview.get_back_forward_list() {
+            Some(list) => list,
+            None => return BackForwardList::default(),
+        };
+        let item = |index| {
+            list.get_nth_item(index).and_then(|item| {
+                Some(HistoryItem {
+                    url: item.get_uri()?.to_string(),
+                    title: item.get_title().map(|title| title.to_string()),
+                })
+            })
+        };
+
+        // Negative indices go back in history and positive ones forward.
+        BackForwardList {
+            back: (1..)
+                .map(|i| item(-i))
+                .take_while(Option::is_some)
+                .flatten()
+                .collect(),
+            current: item(0),
+            forward: (1..)
+                .map(item)
+                .take_while(Option::is_some)
+                .flatten()
+                .collect(),
+        }
+    }
+
+    fn clear_history(&self) -> Result<()> {
+        // WebKitGTK can't clear the back-forward list, but restoring a session replaces it. The
+        // session of a new WebView is empty, then the current page is loaded as its only item.
+        let empty = self
+            .webview
+            .get_context()
+            .and_then(|context| WebView::with_context(&context).get_session_state())
+            .ok_or(Error::Unsupported("Clearing the history"))?;
+        let uri = self.webview.get_uri();
+        self.webview.restore_session_state(&empty);
+        if let Some(uri) = uri {
+            self.webview.load_uri(&uri);
+        }
+        Ok(())
+    }
+
+    fn session_state(&self) -> Option<Vec<u8>> {
+        let state = self.webview.get_session_state()?.serialize()?;
+        Some(state.to_vec())
+    }
+
+    fn restore_session_state(&self, state: &[u8]) -> Result<()> {
+        let state = WebViewSessionState::new(&Bytes::from(state));
+        self.webview.restore_session_state(&state);
+        // Restoring only fills the back-forward list, the current item still has to be loaded.
+        if let Some(item) = self
+            .webview
+            .get_back_forward_list()
+            .and_then(|list| list.get_current_item())
+        {
+            self.webview.go_to_back_forward_list_item(&item);
+        }
+        Ok(())
+    }
+
+    fn cookies(&self, url: &str, callback: Box<dyn FnOnce(Vec<Cookie>)>) -> Result<()> {
+        match self.cookie_manager() {
+            Some(manager) => get_cookies(&manager, &CString::new(url)?, callback),
+            None => callback(Vec::new()),
+        }
+        Ok(())
+    }
+
+    fn set_cookie(&self, cookie: &Cookie) -> Result<()> {
+        if let Some(manager) = self.cookie_manager() {
+            add_cookie(&manager, cookie)?;
+        }
+        Ok(())
+    }
+
+    fn clear_all_cookies(&self) -> Result<()> {
+        // The cookie manager's own way is deprecated in favour of the website data manager.
+        self.clear_storage(StorageTypes {
+            cookies: true,
+            ..Default::default()
+        })
+    }
+
+    fn clear_cache(&self) -> Result<()> {
+        if let Some(context) = self.webview.get_context() {
+            context.clear_cache();
+        }
+        Ok(())
+    }
+
+    fn clear_storage(&self, types: StorageTypes) -> Result<()> {
+        let mut data_types = WebsiteDataTypes::empty();
+        if types.cache {
+            data_types |= WebsiteDataTypes::MEMORY_CACHE
+                | WebsiteDataTypes::DISK_CACHE
+                | WebsiteDataTypes::OFFLINE_APPLICATION_CACHE;
+        }
+        if types.local_storage {
+            data_types |= WebsiteDataTypes::LOCAL_STORAGE | WebsiteDataTypes::SESSION_STORAGE;
+        }
+        if types.indexed_db {
+            data_types |= WebsiteDataTypes::INDEXEDDB_DATABASES;
+        }
+        if types.cookies {
+            data_types |= WebsiteDataTypes::COOKIES;
+        }
+        if let Some(manager) = self
+            .webview
+            .get_context()
+            .and_then(|context| context.get_website_data_manager())
+        {
+            let cancellable: Option<&Cancellable> = None;
+            // A time span of 0 clears the data regardless of when it was modified.
+            manager.clear(data_types, 0, cancellable, |_| ());
+        }
+        Ok(())
+    }
+
+    fn is_devtools_open(&self) -> bool {
+        self.devtools_open.get()
+    }
+
+    fn open_devtools(&self, attachment: Option<DevtoolsAttachment>) -> Result<()> {
+        if let Some(inspector) = self.webview.get_inspector() {
+            inspector.show();
+            match attachment.unwrap_or(self.devtools_attachment) {
+                DevtoolsAttachment::Attached => inspector.attach(),
+                DevtoolsAttachment::Detached => inspector.detach(),
+            }
+        }
+        Ok(())
+    }
+
+    fn close_devtools(&self) -> Result<()> {
+        if let Some(inspector) = self.webview.get_inspector() {
+            inspector.close();
+        }
+        Ok(())
+    }
+
+    fn devtools_attached_height(&self) -> Option<u32> {
+        self.webview
+            .get_inspector()
+            .filter(|inspector| self.devtools_open.get() && inspector.is_attached())
+            .map(|inspector| inspector.get_attached_height())
+    }
+
+    fn focus(&self) -> Result<()> {
+        self.webview.grab_focus();
+        Ok(())
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Returns the edge of the window to resize from, when `position` in the WebView is within
+/// `border` pixels of it.
+fn resize_edge(
+    window: &Window,
+    webview: &WebView,
+    position: (f64, f64),
+    border: i32,
+) -> Option<WindowEdge> {
+    if !window.get_resizable() || window.is_maximized() {
+        return None;
+    }
+    let (x, y) = webview.translate_coordinates(window, position.0 as i32, position.1 as i32)?;
+    let (width, height) = (window.get_allocated_width(), window.get_allocated_height());
+    let (left, right) = (x < border, x >= width - border);
+    let (top, bottom) = (y < border, y >= height - border);
+    Some(match (left, right, top, bottom) {
+        (true, _, true, _) => WindowEdge::NorthWest,
+        (_, true, true, _) => WindowEdge::NorthEast,
+        (true, _, _, true) => WindowEdge::SouthWest,
+        (_, true, _, true) => WindowEdge::SouthEast,
+        (true, _, _, _) => WindowEdge::West,
+        (_, true, _, _) => WindowEdge::East,
+        (_, _, true, _) => WindowEdge::North,
+        (_, _, _, true) => WindowEdge::South,
+        _ => return None,
+    })
+}
+
+fn edge_cursor(edge: WindowEdge) -> &'static str {
+    match edge {
+        WindowEdge::NorthWest => "nw-resize",
+        WindowEdge::NorthEast => "ne-resize",
+        WindowEdge::SouthWest => "sw-resize",
+        WindowEdge::SouthEast => "se-resize",
+        WindowEdge::West => "w-resize",
+        WindowEdge::East => "e-resize",
+        WindowEdge::North => "n-resize",
+        _ => "s-resize",
+    }
+}
+
+fn restore_cursor(webview: &WebView, cursor: &RefCell<Option<Cursor>>) {
+    if let (Some(cursor), Some(gdk_window)) = (&*cursor.borrow(), webview.get_window()) {
+        if gdk_window.get_cursor().as_ref() != Some(cursor) {
+            gdk_window.set_cursor(Some(cursor));
+        }
+    }
+}
+
+// The action of an item of the web engine, numbering the items without a known one.
+fn context_menu_action(item: &WebKitContextMenuItem, others: &mut u32) -> ContextMenuAction {
+    match item.get_stock_action() {
+        WebKitContextMenuAction::OpenLink => ContextMenuAction::OpenLink,
+        WebKitContextMenuAction::OpenLinkInNewWindow => ContextMenuAction::OpenLinkInNewWindow,
+        WebKitContextMenuAction::DownloadLinkToDisk => ContextMenuAction::DownloadLinkToDisk,
+        WebKitContextMenuAction::CopyLinkToClipboard => ContextMenuAction::CopyLinkToClipboard,
+        WebKitContextMenuAction::OpenImageInNewWindow => ContextMenuAction::OpenImageInNewWindow,
+        WebKitContextMenuAction::DownloadImageToDisk => ContextMenuAction::DownloadImageToDisk,
+        WebKitContextMenuAction::CopyImageToClipboard => ContextMenuAction::CopyImageToClipboard,
+        WebKitContextMenuAction::CopyImageUrlToClipboard => {
+            ContextMenuAction::CopyImageUrlToClipboard
+        }
+        WebKitContextMenuAction::OpenFrameInNewWindow => ContextMenuAction::OpenFrameInNewWindow,
+        WebKitContextMenuAction::GoBack => ContextMenuAction::GoBack,
+        WebKitContextMenuAction::GoForward => ContextMenuAction::GoForward,
+        WebKitContextMenuAction::Stop => ContextMenuAction::Stop,
+        WebKitContextMenuAction::Reload => ContextMenuAction::Reload,
+        WebKitContextMenuAction::Copy => ContextMenuAction::Copy,
+        WebKitContextMenuAction::Cut => ContextMenuAction::Cut,
+        WebKitContextMenuAction::Paste => ContextMenuAction::Paste,
+        WebKitContextMenuAction::Delete => ContextMenuAction::Delete,
+        WebKitContextMenuAction::SelectAll => ContextMenuAction::SelectAll,
+        WebKitContextMenuAction::InputMethods => ContextMenuAction::InputMethods,
+        WebKitContextMenuAction::Unicode => ContextMenuAction::Unicode,
+        WebKitContextMenuAction::SpellingGuess => ContextMenuAction::SpellingGuess,
+        WebKitContextMenuAction::NoGuessesFound => ContextMenuAction::NoGuessesFound,
+        WebKitContextMenuAction::IgnoreSpelling => ContextMenuAction::IgnoreSpelling,
+        WebKitContextMenuAction::LearnSpelling => ContextMenuAction::LearnSpelling,
+        WebKitContextMenuAction::IgnoreGrammar => ContextMenuAction::IgnoreGrammar,
+        WebKitContextMenuAction::FontMenu => ContextMenuAction::FontMenu,
+        WebKitContextMenuAction::Bold => ContextMenuAction::Bold,
+        WebKitContextMenuAction::Italic => ContextMenuAction::Italic,
+        WebKitContextMenuAction::Underline => ContextMenuAction::Underline,
+        WebKitContextMenuAction::Outline => ContextMenuAction::Outline,
+        WebKitContextMenuAction::InspectElement => ContextMenuAction::InspectElement,
+        WebKitContextMenuAction::OpenVideoInNewWindow => ContextMenuAction::OpenVideoInNewWindow,
+        WebKitContextMenuAction::OpenAudioInNewWindow => ContextMenuAction::OpenAudioInNewWindow,
+        WebKitContextMenuAction::CopyVideoLinkToClipboard => {
+            ContextMenuAction::CopyVideoLinkToClipboard
+        }
+        WebKitContextMenuAction::CopyAudioLinkToClipboard => {
+            ContextMenuAction::CopyAudioLinkToClipboard
+        }
+        WebKitContextMenuAction::ToggleMediaControls => ContextMenuAction::ToggleMediaControls,
+        WebKitContextMenuAction::ToggleMediaLoop => ContextMenuAction::ToggleMediaLoop,
+        WebKitContextMenuAction::EnterVideoFullscreen => ContextMenuAction::EnterVideoFullscreen,
+        WebKitContextMenuAction::MediaPlay => ContextMenuAction::MediaPlay,
+        WebKitContextMenuAction::MediaPause => ContextMenuAction::MediaPause,
+        WebKitContextMenuAction::MediaMute => ContextMenuAction::MediaMute,
+        WebKitContextMenuAction::DownloadVideoToDisk => ContextMenuAction::DownloadVideoToDisk,
+        WebKitContextMenuAction::DownloadAudioToDisk => ContextMenuAction::DownloadAudioToDisk,
+        WebKitContextMenuAction::InsertEmoji => ContextMenuAction::InsertEmoji,
+        _ => {
+            *others += 1;
+            ContextMenuAction::Other(*others - 1)
+        }
+    }
+}
+
+// WebKit already renders at the scale factor of the monitor, only the text scaling of the desktop
+// is left to follow. GTK gives the resolution of fonts in 1024ths of DPI, without the scale factor.
+// Finds the element pressed with the main button in a `-webkit-app-region: drag` region, unless a
+// `no-drag` one is closer. WebKitGTK doesn't know the property and drops it from stylesheets, so
+// it's also looked for in the `style` attribute.
+const DRAG_REGIONS_SCRIPT: &str = r#"
+    document.addEventListener('mousedown', (event) => {
+        if (event.button !== 0) {
+            return;
+        }
+        for (let element = event.target; element instanceof Element; element = element.parentElement) {
+            let region = getComputedStyle(element).getPropertyValue('-webkit-app-region');
+            const inline = /-webkit-app-region\s*:\s*(no-drag|drag)/.exec(element.getAttribute('style') || '');
+            if (inline) {
+                region = inline[1];
+            }
+            if (region === 'drag') {
+                window.webkit.messageHandlers.wryDrag.postMessage(null);
+            }
+            if (region === 'drag' || region === 'no-drag') {
+                return;
+            }
+        }
+    });
+"#;
+
+// The scale factor of the monitor showing the widget, or of the primary one before it's shown.
+fn monitor_scale_factor(widget: &WebView) -> i32 {
+    let display = widget.get_display();
+    let monitor = match widget.get_window() {
+        Some(window) => display.get_monitor_at_window(&window),
+        None => display.get_primary_monitor(),
+    };
+    monitor.map_or_else(
+        || widget.get_scale_factor(),
+        |monitor| monitor.get_scale_factor(),
+    )
+}
+
+type SchemeHandler = Box<dyn Fn(&URISchemeRequest)>;
+
+const CUSTOM_PROTOCOLS_KEY: &str = "wry-custom-protocols";
+
+// WebKit only keeps the first handler registered for a scheme on a web context, which windows can
+// share. The scheme is registered once per context, and each request goes to the handler of the
+// WebView it comes from.
+fn register_custom_protocol(
+    context: &WebContext,
+    webview: &WebView,
+    name: &str,
+    handler: SchemeHandler,
+) {
+    let key = format!("wry-custom-protocol-{}", name);
+    // The WebView isn't finalized as long as signal handlers hold it, so the handler, and the
+    // worker threads waiting for its requests, are dropped once the WebView is destroyed.
+    let key_ = key.clone();
+    webview.connect_destroy(move |webview| {
+        // Safety: The key is only ever set to this type
+        unsafe { webview.steal_data::<SchemeHandler>(&key_) };
+    });
+    // Safety: The keys are only ever set to these types
+    let registered = unsafe {
+        webview.set_data(&key, handler);
+        if context
+            .get_data::<RefCell<HashSet<String>>>(CUSTOM_PROTOCOLS_KEY)
+            .is_none()
+        {
+            context.set_data(CUSTOM_PROTOCOLS_KEY, RefCell::new(HashSet::<String>::new()));
+        }
+        context.get_data::<RefCell<HashSet<String>>>(CUSTOM_PROTOCOLS_KEY)
+    };
+    if let Some(registered) = registered {
+        if !registered.borrow_mut().insert(name.to_string()) {
+            return;
+        }
+    }
+    context.register_uri_scheme(name, move |request| {
+        let webview = request.get_web_view();
+        // Safety: The key is only ever set to this type
+        let handler = webview
+            .as_ref()
+            .and_then(|webview| unsafe { webview.get_data::<SchemeHandler>(&key) });
+        match handler {
+            Some(handler) => handler(request),
+            None => request.finish_error(&mut glib::Error::new(
+                FileError::Exist,
+                "The WebView has no handler for this protocol.",
+            )),
+        }
+    });
+}
+
+type ProtocolReply = glib::Sender<Result<ProtocolResponse>>;
+
+// Requests are handled on worker threads, and their answers are sent back to the main thread
+// where the request has to be finished.
+fn threaded_scheme_handler(
+    handler: ThreadedProtocolHandler,
+    workers: Option<usize>,
+    timeout: Option<Duration>,
+) -> SchemeHandler {
+    let respond = move |request: &ProtocolRequest| {
+        handler(request).and_then(|response| range(response, request))
+    };
+    let dispatch: Rc<dyn Fn(ProtocolRequest, ProtocolReply)> = match workers {
+        Some(workers) => {
+            let (queue, requests) = channel::<(ProtocolRequest, ProtocolReply)>();
+            let requests = Arc::new(Mutex::new(requests));
+            let respond = Arc::new(respond);
+            for _ in 0..workers.max(1) {
+                let requests = requests.clone();
+                let respond = respond.clone();
+                thread::spawn(move || loop {
+                    // The lock is only held while waiting for the next request.
+                    let request = requests
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .recv();
+                    match request {
+                        Ok((request, reply)) => {
+                            let _ = reply.send(respond(&request));
+                        }
+                        Err(_) => break,
+                    }
+                });
+            }
+            Rc::new(move |request, reply| {
+                let _ = queue.send((request, reply));
+            })
+        }
+        // Without a limit, a handler that never returns only holds up its own request.
+        None => {
+            let respond = Arc::new(respond);
+            Rc::new(move |request, reply| {
+                let respond = respond.clone();
+                thread::spawn(move || {
+                    let _ = reply.send(respond(&request));
+                });
+            })
+        }
+    };
+
+    Box::new(move |request| {
+        let uri = match request.get_uri() {
+            Some(uri) => uri.to_string(),
+            None => {
+                request.finish_error(&mut glib::Error::new(
+                    FileError::Exist,
+                    "Could not get uri.",
+                ));
+                return;
+            }
+        };
+
+        // Whichever of the answer and the timeout comes first finishes it.
+        let finished = Rc::new(Cell::new(false));
+        let (reply, answer) = MainContext::channel(PRIORITY_DEFAULT);
+        let request_ = request.clone();
+        let finished_ = finished.clone();
+        let uri_ = uri.clone();
+        answer.attach(None, move |content| {
+            if !finished_.replace(true) {
+                finish_request(&request_, &uri_, content);
+            }
+            Continue(false)
+        });
+        if let Some(timeout) = timeout {
+            let request_ = request.clone();
+            timeout_add_local(timeout.as_millis() as u32, move || {
+                if !finished.replace(true) {
+                    request_.finish_error(&mut glib::Error::new(
+                        IOErrorEnum::TimedOut,
+                        "The custom protocol handler timed out.",
+                    ));
+                }
+                Continue(false)
+            });
+        }
+        let dispatch = dispatch.clone();
+        read_protocol_request(request, &uri, move |protocol_request| {
+            dispatch(protocol_request, reply)
+        });
+    })
+}
+
+fn finish_request(request: &URISchemeRequest, uri: &str, content: Result<ProtocolResponse>) {
+    match content {
+        Ok(mut response) => {
+            let encoding = match content_encoding(&mut response.headers) {
+                Ok(encoding) => encoding,
+                Err(encoding) => {
+                    return request.finish_error(&mut glib::Error::new(
+                        IOErrorEnum::NotSupported,
+                        &format!("The {} content encoding is not supported.", encoding),
+                    ))
+                }
+            };
+            let mime = match (&response.mimetype, &response.reader) {
+                (Some(mimetype), _) => mimetype.clone(),
+                (None, Some(_)) => MimeType::parse_from_uri(uri).to_string(),
+                // Encoded content can't be sniffed.
+                (None, None) if encoding.is_some() => MimeType::parse_from_uri(uri).to_string(),
+                (None, None) => MimeType::parse(&response.body, uri),
+            };
+            // WebKit pulls the content of readers from another thread as it needs it.
+            let (input, length): (gio::InputStream, i64) = match response.reader.take() {
+                Some(reader) => match response.length {
+                    // Only the known length is read, like the part of a `Range` request.
+                    Some(length) => (
+                        gio::ReadInputStream::new(reader.take(length)).upcast(),
+                        length as i64,
+                    ),
+                    None => (gio::ReadInputStream::new(reader).upcast(), -1),
+                },
+                None => (
+                    gio::MemoryInputStream::from_bytes(&Bytes::from(&response.body)).upcast(),
+                    response.body.len() as i64,
+                ),
+            };
+            // WebKit doesn't decode the content of custom protocols, it's decoded as it's read.
+            let (input, length) = match encoding {
+                Some(format) => {
+                    let decoder = gio::ZlibDecompressor::new(format);
+                    let input = gio::ConverterInputStream::new(&input, &decoder).upcast();
+                    (input, -1)
+                }
+                None => (input, length),
+            };
+            #[cfg(feature = "v2_36")]
+            {
+                if response.status != 200 || !response.headers.is_empty() {
+                    return finish_with_response(request, &input, length, &mime, &response);
+                }
+            }
+            request.finish(&input, length, Some(&mime))
+        }
+        Err(_) => request.finish_error(&mut glib::Error::new(
+            FileError::Exist,
+            "Could not get requested file.",
+        )),
+    }
+}
+
+// Removes the `Content-Encoding` header, returning how the content has to be decoded. gio only
+// has decoders for gzip and deflate, the other encodings are returned as the error.
+fn content_encoding(
+    headers: &mut HashMap<String, String>,
+) -> std::result::Result<Option<gio::ZlibCompressorFormat>, String> {
+    let name = match headers
+        .keys()
+        .find(|name| name.eq_ignore_ascii_case("content-encoding"))
+    {
+        Some(name) => name.clone(),
+        None => return Ok(None),
+    };
+    let encoding = headers[&name].trim().to_ascii_lowercase();
+    let format = match encoding.as_str() {
+        "gzip" | "x-gzip" => Some(gio::ZlibCompressorFormat::Gzip),
+        "deflate" => Some(gio::ZlibCompressorFormat::Zlib),
+        "identity" | "" => None,
+        _ => return Err(encoding),
+    };
+    headers.remove(&name);
+    Ok(format)
+}
+
+// Answers the `Range` header of the request with the part of the content it asks for, which media
+// elements need to seek. Responses with another status than 200, encoded responses and multiple
+// ranges are left alone.
+fn range(mut response: ProtocolResponse, request: &ProtocolRequest) -> Result<ProtocolResponse> {
+    let encoded = response
+        .headers
+        .keys()
+        .any(|name| name.eq_ignore_ascii_case("content-encoding"));
+    let range = match request.headers.get("range") {
+        Some(range) if response.status == 200 && !encoded => range,
+        _ => return Ok(response),
+    };
+    let total = match (&response.reader, response.length) {
+        (Some(_), Some(length)) => length,
+        (Some(_), None) => return Ok(response),
+        (None, _) => response.body.len() as u64,
+    };
+    let (first, last) = match parse_range(range) {
+        Some(bounds) => bounds,
+        None => return Ok(response),
+    };
+    let (start, end) = match (first, last) {
+        (Some(first), last) => (first, last.unwrap_or(u64::MAX).min(total.saturating_sub(1))),
+        (None, Some(suffix)) => (total.saturating_sub(suffix), total.saturating_sub(1)),
+        (None, None) => return Ok(response),
+    };
+
+    if start >= total || start > end {
+        response.status = 416;
+        response.body.clear();
+        response.reader = None;
+        response.length = None;
+        response
+            .headers
+            .insert("Content-Range".to_string(), format!("bytes */{}", total));
+        return Ok(response);
+    }
+
+    let length = end - start + 1;
+    match response.reader.take() {
+        Some(mut reader) => {
+            // The content is read up to `length` from there.
+            reader.seek(SeekFrom::Current(start as i64))?;
+            response.reader = Some(reader);
+            response.length = Some(length);
+        }
+        None => {
+            // The part of the content can't be sniffed.
+            if response.mimetype.is_none() {
+                response.mimetype = Some(MimeType::parse(&response.body, &request.uri));
+            }
+            response.body.truncate(end as usize + 1);
+            response.body.drain(..start as usize);
+        }
+    }
+    response.status = 206;
+    response.headers.insert(
+        "Content-Range".to_string(),
+        format!("bytes {}-{}/{}", start, end, total),
+    );
+    response
+        .headers
+        .insert("Content-Length".to_string(), length.to_string());
+    Ok(response)
+}
+
+// Parses a single range like `bytes=0-499`, `bytes=500-` or `bytes=-500`.
+fn parse_range(range: &str) -> Option<(Option<u64>, Option<u64>)> {
+    let range = range.trim().strip_prefix("bytes=")?;
+    if range.contains(',') {
+        return None;
+    }
+    let mut bounds = range.splitn(2, '-').map(|bound| match bound.trim() {
+        "" => Ok(None),
+        bound => bound.parse().map(Some),
+    });
+    match (bounds.next()?.ok()?, bounds.next()?.ok()?) {
+        (Some(first), Some(last)) if first > last => None,
+        bounds => Some(bounds),
+    }
+}
+
+// The bindings leave out the cookie functions, which take a `SoupCookie`. The raw ones are used
+// instead, through webkit2gtk-sys, which already links libsoup-2.4 like WebKitGTK 4.0 does.
+fn get_cookies(manager: &CookieManager, url: &CStr, callback: Box<dyn FnOnce(Vec<Cookie>)>) {
+    use glib::{glib_sys, gobject_sys, ObjectType};
+
+    unsafe extern "C" fn ready(
+        manager: *mut gobject_sys::GObject,
+        result: *mut gio_sys::GAsyncResult,
+        callback: glib_sys::gpointer,
+    ) {
+        let callback = Box::from_raw(callback as *mut Box<dyn FnOnce(Vec<Cookie>)>);
+        let list = webkit2gtk_sys::webkit_cookie_manager_get_cookies_finish(
+            manager as *mut webkit2gtk_sys::WebKitCookieManager,
+            result,
+            ptr::null_mut(),
+        );
+        let mut cookies = Vec::new();
+        let mut node = list;
+        while !node.is_null() {
+            let cookie = (*node).data as *mut soup_sys::SoupCookie;
+            cookies.push(cookie_from_soup(cookie));
+            soup_sys::soup_cookie_free(cookie);
+            node = (*node).next;
+        }
+        glib_sys::g_list_free(list);
+        callback(cookies);
+    }
+
+    // Safety: The callback is boxed once more to get a thin pointer, and freed by `ready`
+    unsafe {
+        webkit2gtk_sys::webkit_cookie_manager_get_cookies(
+            manager.as_ptr(),
+            url.as_ptr(),
+            ptr::null_mut(),
+            Some(ready),
+            Box::into_raw(Box::new(callback)) as glib_sys::gpointer,
+        );
+    }
+}
+
+unsafe fn cookie_from_soup(cookie: *mut soup_sys::SoupCookie) -> Cookie {
+    let string = |s: *const c_char| {
+        if s.is_null() {
+            String::new()
+        } else {
+            CStr::from_ptr(s).to_string_lossy().into_owned()
+        }
+    };
+    let expires = soup_sys::soup_cookie_get_expires(cookie);
+    // Session cookies have no expiry date.
+    let expiry = if expires.is_null() {
+        None
+    } else {
+        let seconds = soup_sys::soup_date_to_time_t(expires).max(0) as u64;
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+    };
+    Cookie {
+        name: string(soup_sys::soup_cookie_get_name(cookie)),
+        value: string(soup_sys::soup_cookie_get_value(cookie)),
+        domain: string(soup_sys::soup_cookie_get_domain(cookie)),
+        path: string(soup_sys::soup_cookie_get_path(cookie)),
+        secure: soup_sys::soup_cookie_get_secure(cookie) != 0,
+        http_only: soup_sys::soup_cookie_get_http_only(cookie) != 0,
+        expiry,
+    }
+}
+
+fn add_cookie(manager: &CookieManager, cookie: &Cookie) -> Result<()> {
+    use glib::ObjectType;
+
+    let name = CString::new(cookie.name.as_str())?;
+    let value = CString::new(cookie.value.as_str())?;
+    let domain = CString::new(cookie.domain.as_str())?;
+    let path = CString::new(cookie.path.as_str())?;
+
+    // Safety: The manager copies the cookie right away, so it can be freed after
+    unsafe {
+        // A negative max age makes a session cookie.
+        let soup_cookie = soup_sys::soup_cookie_new(
+            name.as_ptr(),
+            value.as_ptr(),
+            domain.as_ptr(),
+            path.as_ptr(),
+            -1,
+        );
+        soup_sys::soup_cookie_set_secure(soup_cookie, cookie.secure as c_int);
+        soup_sys::soup_cookie_set_http_only(soup_cookie, cookie.http_only as c_int);
+        if let Some(expiry) = cookie.expiry {
+            let seconds = expiry
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let date = soup_sys::soup_date_new_from_time_t(seconds as c_long);
+            soup_sys::soup_cookie_set_expires(soup_cookie, date);
+            soup_sys::soup_date_free(date);
+        }
+        webkit2gtk_sys::webkit_cookie_manager_add_cookie(
+            manager.as_ptr(),
+            soup_cookie,
+            ptr::null_mut(),
+            None,
+            ptr::null_mut(),
+        );
+        soup_sys::soup_cookie_free(soup_cookie);
+    }
+    Ok(())
+}
+
+// The bindings predate WebKitGTK 2.36, which lets the response have a status and headers.
+#[cfg(feature = "v2_36")]
+fn finish_with_response(
+    request: &URISchemeRequest,
+    input: &gio::InputStream,
+    length: i64,
+    mime: &str,
+    response: &ProtocolResponse,
+) {
+    use glib::ObjectType;
+    use std::os::raw::{c_uint, c_void};
+
+    extern "C" {
+        fn webkit_uri_scheme_response_new(stream: *mut c_void, length: i64) -> *mut c_void;
+        fn webkit_uri_scheme_response_set_status(
+            response: *mut c_void,
+            status: c_uint,
+            reason: *const c_char,
+        );
+        fn webkit_uri_scheme_response_set_content_type(
+            response: *mut c_void,
+            content_type: *const c_char,
+        );
+        fn webkit_uri_scheme_response_set_http_headers(response: *mut c_void, headers: *mut c_void);
+        fn webkit_uri_scheme_request_finish_with_response(
+            request: *mut c_void,
+            response: *mut c_void,
+        );
+        fn soup_message_headers_new(kind: c_int) -> *mut c_void;
+        fn soup_message_headers_append(
+            headers: *mut c_void,
+            name: *const c_char,
+            value: *const c_char,
+        );
+        fn g_object_unref(object: *mut c_void);
+    }
+    const SOUP_MESSAGE_HEADERS_RESPONSE: c_int = 1;
+
+    // Safety: The response takes the headers, and is released once the request holds it
+    unsafe {
+        let uri_response = webkit_uri_scheme_response_new(input.as_ptr() as *mut c_void, length);
+        webkit_uri_scheme_response_set_status(uri_response, response.status as c_uint, ptr::null());
+        if let Ok(mime) = CString::new(mime) {
+            webkit_uri_scheme_response_set_content_type(uri_response, mime.as_ptr());
+        }
+        let headers = soup_message_headers_new(SOUP_MESSAGE_HEADERS_RESPONSE);
+        for (name, value) in &response.headers {
+            if let (Ok(name), Ok(value)) =
+                (CString::new(name.as_str()), CString::new(value.as_str()))
+            {
+                soup_message_headers_append(headers, name.as_ptr(), value.as_ptr());
+            }
+        }
+        webkit_uri_scheme_response_set_http_headers(uri_response, headers);
+        webkit_uri_scheme_request_finish_with_response(
+            request.as_ptr() as *mut c_void,
+            uri_response,
+        );
+        g_object_unref(uri_response);
+    }
+}
+
+// Gives the request to `handle` once its body is read, without blocking the main thread. Without
+// the `v2_36` feature, requests are reported as `GET` requests without headers.
+fn read_protocol_request<F: FnOnce(ProtocolRequest) + 'static>(
+    request: &URISchemeRequest,
+    uri: &str,
+    handle: F,
+) {
+    let mut protocol_request = ProtocolRequest {
+        uri: uri.to_string(),
+        method: "GET".to_string(),
+        headers: HashMap::new(),
+        body: Vec::new(),
+    };
+    read_method_and_headers(request, &mut protocol_request);
+    match request_body(request) {
+        Some(body) => MainContext::default().spawn_local(async move {
+            protocol_request.body = read_stream(&body).await;
+            handle(protocol_request);
+        }),
+        None => handle(protocol_request),
+    }
+}
+
+#[cfg(not(feature = "v2_36"))]
+fn read_method_and_headers(_request: &URISchemeRequest, _protocol_request: &mut ProtocolRequest) {}
+
+// The bindings predate WebKitGTK 2.36, which exposes the method and the headers of the requests.
+#[cfg(feature = "v2_36")]
+fn read_method_and_headers(request: &URISchemeRequest, protocol_request: &mut ProtocolRequest) {
+    use glib::ObjectType;
+    use std::os::raw::c_void;
+
+    #[repr(C)]
+    struct SoupMessageHeadersIter {
+        dummy: [*mut c_void; 3],
+    }
+    extern "C" {
+        fn webkit_uri_scheme_request_get_http_method(request: *mut c_void) -> *const c_char;
+        fn webkit_uri_scheme_request_get_http_headers(request: *mut c_void) -> *mut c_void;
+        fn soup_message_headers_iter_init(iter: *mut SoupMessageHeadersIter, headers: *mut c_void);
+        fn soup_message_headers_iter_next(
+            iter: *mut SoupMessageHeadersIter,
+            name: *mut *const c_char,
+            value: *mut *const c_char,
+        ) -> c_int;
+    }
+
+    let request = request.as_ptr() as *mut c_void;
+    // Safety: The strings and the headers are owned by the request, which outlives this call
+    unsafe {
+        let method = webkit_uri_scheme_request_get_http_method(request);
+        if !method.is_null() {
+            protocol_request.method = CStr::from_ptr(method).to_string_lossy().into_owned();
+        }
+        let headers = webkit_uri_scheme_request_get_http_headers(request);
+        if !headers.is_null() {
+            let mut iter = SoupMessageHeadersIter {
+                dummy: [ptr::null_mut(); 3],
+            };
+            soup_message_headers_iter_init(&mut iter, headers);
+            let (mut name, mut value) = (ptr::null(), ptr::null());
+            while soup_message_headers_iter_next(&mut iter, &mut name, &mut value) != 0 {
+                let name = CStr::from_ptr(name).to_string_lossy().to_lowercase();
+                let value = CStr::from_ptr(value).to_string_lossy();
+                protocol_request
+                    .headers
+                    .entry(name)
+                    .and_modify(|values| {
+                        values.push_str(", ");
+                        values.push_str(&value);
+                    })
+                    .or_insert_with(|| value.into_owned());
+            }
+        }
+    }
+}
+
+// The bindings predate WebKitGTK 2.40, which exposes the body of the requests.
+#[cfg(feature = "v2_40")]
+fn request_body(request: &URISchemeRequest) -> Option<gio::InputStream> {
+    use glib::{translate::from_glib_full, ObjectType};
+    use std::os::raw::c_void;
+
+    extern "C" {
+        fn webkit_uri_scheme_request_get_http_body(
+            request: *mut c_void,
+        ) -> *mut glib::gobject_sys::GObject;
+    }
+
+    // Safety: The body is returned with a reference of its own
+    unsafe {
+        let body = webkit_uri_scheme_request_get_http_body(request.as_ptr() as *mut c_void);
+        if body.is_null() {
+            return None;
+        }
+        from_glib_full::<_, glib::Object>(body)
+            .downcast::<gio::InputStream>()
+            .ok()
+    }
+}
+
+#[cfg(not(feature = "v2_40"))]
+fn request_body(_request: &URISchemeRequest) -> Option<gio::InputStream> {
+    None
+}
+
+async fn read_stream(stream: &gio::InputStream) -> Vec<u8> {
+    let mut content = Vec::new();
+    while let Ok(bytes) = stream.read_bytes_async_future(4096, PRIORITY_DEFAULT).await {
+        if bytes.is_empty() {
+            break;
+        }
+        content.extend_from_slice(&bytes);
+    }
+    content
 }
 
 impl InnerWebView {
+    fn cookie_manager(&self) -> Option<CookieManager> {
+        self.webview.get_context()?.get_cookie_manager()
+    }
+
+    pub fn add_widget<W: IsA<Widget>>(&self, widget: &W, side: PositionType) {
+        self.grid.insert_next_to(&*self.webview, side);
+        self.grid
+            .attach_next_to(widget, Some(&*self.webview), side, 1, 1);
+        widget.show_all();
+    }
+
     fn init(&self, js: &str) -> Result<()> {
         if let Some(manager) = self.webview.get_user_content_manager() {
             let script = UserScript::new(
@@ -170,3 +1849,344 @@ impl InnerWebView {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webview::WebViewBuilder;
+
+    use once_cell::sync::Lazy;
+    use std::{
+        io,
+        panic::{self, AssertUnwindSafe},
+        sync::mpsc::Sender,
+        time::Instant,
+    };
+
+    type GtkTest = (Box<dyn FnOnce() + Send>, Sender<thread::Result<()>>);
+
+    // GTK can only be used from the thread that initialized it, so the tests using it all run on
+    // this one. They pass without running when there's no display to initialize GTK with.
+    static GTK_THREAD: Lazy<Mutex<Sender<GtkTest>>> = Lazy::new(|| {
+        let (sender, tests) = channel::<GtkTest>();
+        thread::spawn(move || {
+            let initialized = gtk::init().is_ok();
+            for (test, result) in tests {
+                let _ = result.send(if initialized {
+                    panic::catch_unwind(AssertUnwindSafe(test))
+                } else {
+                    Ok(())
+                });
+            }
+        });
+        Mutex::new(sender)
+    });
+
+    fn with_gtk<F: FnOnce() + Send + 'static>(test: F) {
+        let (sender, result) = channel();
+        GTK_THREAD
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .send((Box::new(test), sender))
+            .unwrap();
+        if let Err(panic) = result.recv().unwrap() {
+            panic::resume_unwind(panic);
+        }
+    }
+
+    fn test_window() -> Window {
+        let app = gtk::Application::new(None, Default::default()).unwrap();
+        let window = Window::new(&app);
+        window.show_all();
+        window
+    }
+
+    // Runs the main loop until `done` returns true, or fails after 10 seconds.
+    fn run_until<F: Fn() -> bool>(done: F) -> bool {
+        let start = Instant::now();
+        while !done() {
+            if start.elapsed() > Duration::from_secs(10) {
+                return false;
+            }
+            if !gtk::main_iteration_do(false) {
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+        true
+    }
+
+    #[test]
+    fn should_only_finish_the_last_of_quick_loads() {
+        with_gtk(|| {
+            let loads = Rc::new(RefCell::new(Vec::new()));
+            let loads_ = loads.clone();
+            let webview = WebViewBuilder::new(test_window())
+                .unwrap()
+                .register_request_protocol("wry".to_string(), |request| {
+                    Ok(format!("<p>{}</p>", request.uri).into_bytes().into())
+                })
+                .page_load_handler(move |url, state| {
+                    loads_.borrow_mut().push((url.to_string(), state))
+                })
+                .build()
+                .unwrap();
+            webview.load_url("wry://localhost/first").unwrap();
+            webview.load_url("wry://localhost/second").unwrap();
+
+            assert!(run_until(|| loads.borrow().iter().any(|(url, state)| url
+                .ends_with("/second")
+                && *state == LoadState::Finished)));
+            assert!(!loads
+                .borrow()
+                .iter()
+                .any(|(url, state)| url.ends_with("/first")
+                    && (*state == LoadState::Finished || *state == LoadState::Failed)));
+        });
+    }
+
+    #[test]
+    fn should_report_the_editor_state_of_the_selection() {
+        with_gtk(|| {
+            let states = Rc::new(RefCell::new(Vec::new()));
+            let states_ = states.clone();
+            let webview = WebViewBuilder::new(test_window())
+                .unwrap()
+                .register_request_protocol("wry".to_string(), |_| {
+                    Ok(r#"<div id="editor" contenteditable><b>Bold</b></div>
+                    <script>
+                        const editor = document.getElementById('editor');
+                        editor.focus();
+                        document.getSelection().selectAllChildren(editor);
+                    </script>"#
+                        .as_bytes()
+                        .to_vec()
+                        .into())
+                })
+                .editor_state_handler(move |state| states_.borrow_mut().push(state))
+                .build()
+                .unwrap();
+            webview.load_url("wry://localhost/index.html").unwrap();
+
+            assert!(run_until(|| states
+                .borrow()
+                .iter()
+                .any(|state| state.bold && state.selection)));
+        });
+    }
+
+    #[test]
+    fn should_render_gzipped_responses() {
+        with_gtk(|| {
+            // `<title>Decompressed</title>` compressed with gzip.
+            let gzipped = vec![
+                31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 179, 41, 201, 44, 201, 73, 181, 115, 73, 77, 206,
+                207, 45, 40, 74, 45, 46, 78, 77, 177, 209, 135, 136, 1, 0, 44, 61, 227, 244, 27, 0,
+                0, 0,
+            ];
+            let webview = WebViewBuilder::new(test_window())
+                .unwrap()
+                .register_request_protocol("wry".to_string(), move |_| {
+                    let mut response = ProtocolResponse::from(gzipped.clone());
+                    response.mimetype = Some("text/html".to_string());
+                    response
+                        .headers
+                        .insert("Content-Encoding".to_string(), "gzip".to_string());
+                    Ok(response)
+                })
+                .build()
+                .unwrap();
+            webview.load_url("wry://localhost/index.html").unwrap();
+
+            assert!(run_until(
+                || webview.title().as_deref() == Some("Decompressed")
+            ));
+        });
+    }
+
+    #[test]
+    fn should_only_keep_the_current_page_when_clearing_the_history() {
+        with_gtk(|| {
+            let loaded = Rc::new(Cell::new(0));
+            let loaded_ = loaded.clone();
+            let webview = WebViewBuilder::new(test_window())
+                .unwrap()
+                .register_request_protocol("wry".to_string(), |request| {
+                    Ok(format!("<p>{}</p>", request.uri).into_bytes().into())
+                })
+                .page_load_handler(move |_, state| {
+                    if state == LoadState::Finished {
+                        loaded_.set(loaded_.get() + 1);
+                    }
+                })
+                .build()
+                .unwrap();
+            webview.load_url("wry://localhost/first").unwrap();
+            assert!(run_until(|| loaded.get() == 1));
+            webview.load_url("wry://localhost/second").unwrap();
+            assert!(run_until(|| loaded.get() == 2));
+            assert!(webview.can_go_back());
+
+            webview.clear_history().unwrap();
+            assert!(run_until(|| loaded.get() == 3));
+            let list = webview.back_forward_list();
+            assert!(list.back.is_empty() && list.forward.is_empty());
+            assert!(list.current.unwrap().url.ends_with("/second"));
+        });
+    }
+
+    #[test]
+    fn should_report_the_selected_match_of_the_search() {
+        with_gtk(|| {
+            let loaded = Rc::new(Cell::new(false));
+            let loaded_ = loaded.clone();
+            let results = Rc::new(RefCell::new(Vec::new()));
+            let results_ = results.clone();
+            let webview = WebViewBuilder::new(test_window())
+                .unwrap()
+                .register_request_protocol("wry".to_string(), |_| {
+                    Ok(b"<p>Apple, apple and APPLE</p>".to_vec().into())
+                })
+                .page_load_handler(move |_, state| loaded_.set(state == LoadState::Finished))
+                .find_result_handler(move |current, total| {
+                    results_.borrow_mut().push((current, total))
+                })
+                .build()
+                .unwrap();
+            webview.load_url("wry://localhost/index.html").unwrap();
+            assert!(run_until(|| loaded.get()));
+
+            webview.find("apple", false).unwrap();
+            assert!(run_until(|| results.borrow().last() == Some(&(1, 3))));
+            webview.find_previous().unwrap();
+            assert!(run_until(|| results.borrow().last() == Some(&(3, 3))));
+            webview.find_next().unwrap();
+            assert!(run_until(|| results.borrow().last() == Some(&(1, 3))));
+            webview.find("Apple", true).unwrap();
+            assert!(run_until(|| results.borrow().last() == Some(&(1, 1))));
+        });
+    }
+
+    #[test]
+    fn should_fail_responses_with_unsupported_encodings() {
+        with_gtk(|| {
+            let loads = Rc::new(RefCell::new(Vec::new()));
+            let loads_ = loads.clone();
+            let webview = WebViewBuilder::new(test_window())
+                .unwrap()
+                .register_request_protocol("wry".to_string(), |_| {
+                    let mut response = ProtocolResponse::from(b"<p>Brotli</p>".to_vec());
+                    response.mimetype = Some("text/html".to_string());
+                    response
+                        .headers
+                        .insert("Content-Encoding".to_string(), "br".to_string());
+                    Ok(response)
+                })
+                .page_load_handler(move |_, state| loads_.borrow_mut().push(state))
+                .build()
+                .unwrap();
+            webview.load_url("wry://localhost/index.html").unwrap();
+
+            assert!(run_until(|| loads.borrow().contains(&LoadState::Failed)));
+        });
+    }
+
+    #[cfg(feature = "v2_40")]
+    #[test]
+    fn should_echo_posted_forms() {
+        with_gtk(|| {
+            let webview = WebViewBuilder::new(test_window())
+                .unwrap()
+                .register_request_protocol("wry".to_string(), |request| {
+                    let html = if request.uri.ends_with("/echo") {
+                        format!(
+                            "<title>{} {}</title>",
+                            request.method,
+                            String::from_utf8_lossy(&request.body)
+                        )
+                    } else {
+                        r#"<form method="post" action="/echo"><input name="name" value="wry"></form>
+                        <script>document.forms[0].submit()</script>"#
+                            .to_string()
+                    };
+                    Ok(html.into_bytes().into())
+                })
+                .build()
+                .unwrap();
+            webview.load_url("wry://localhost/form").unwrap();
+
+            assert!(run_until(
+                || webview.title().as_deref() == Some("POST name=wry")
+            ));
+        });
+    }
+
+    #[test]
+    fn should_read_posted_form_from_stream() {
+        let context = MainContext::new();
+        let form = b"name=wry&message=Hello%2C+world%21".to_vec();
+        let stream = gio::MemoryInputStream::from_bytes(&Bytes::from(&form));
+        assert_eq!(context.block_on(read_stream(stream.upcast_ref())), form);
+
+        // Longer than a single read
+        let form = format!("text={}", "a".repeat(10000)).into_bytes();
+        let stream = gio::MemoryInputStream::from_bytes(&Bytes::from(&form));
+        assert_eq!(context.block_on(read_stream(stream.upcast_ref())), form);
+    }
+
+    #[test]
+    fn should_answer_range_requests() {
+        let video: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        let request = |header: Option<&str>| {
+            let mut request = ProtocolRequest {
+                uri: "wry://localhost/video.mp4".to_string(),
+                ..Default::default()
+            };
+            if let Some(header) = header {
+                request
+                    .headers
+                    .insert("range".to_string(), header.to_string());
+            }
+            request
+        };
+        let answer = |response: ProtocolResponse, header: &str| {
+            range(response, &request(Some(header))).unwrap()
+        };
+
+        let response = answer(video.clone().into(), "bytes=1048576-2097151");
+        assert_eq!(response.status, 206);
+        assert_eq!(response.body, &video[1048576..2097152]);
+        assert_eq!(
+            response.headers["Content-Range"],
+            format!("bytes 1048576-2097151/{}", video.len())
+        );
+        assert_eq!(response.headers["Content-Length"], "1048576");
+
+        let response = answer(video.clone().into(), "bytes=-100");
+        assert_eq!(response.status, 206);
+        assert_eq!(response.body, &video[video.len() - 100..]);
+
+        let response = answer(
+            ProtocolResponse::from_reader(io::Cursor::new(video.clone()), Some(video.len() as u64)),
+            "bytes=3000000-",
+        );
+        assert_eq!(response.status, 206);
+        assert_eq!(response.length, Some(video.len() as u64 - 3000000));
+        let mut body = Vec::new();
+        let length = response.length.unwrap();
+        response
+            .reader
+            .unwrap()
+            .take(length)
+            .read_to_end(&mut body)
+            .unwrap();
+        assert_eq!(body, &video[3000000..]);
+
+        let response = answer(video.clone().into(), "bytes=5000000-");
+        assert_eq!(response.status, 416);
+        assert!(response.body.is_empty());
+
+        let response = range(video.clone().into(), &request(None)).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, video);
+    }
+}
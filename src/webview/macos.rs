@@ -1,16 +1,23 @@
 use crate::mimetype::MimeType;
-use crate::webview::WV;
-use crate::{Result, RpcHandler};
+use crate::webview::{
+    BackForwardList, Cookie, DevtoolsAttachment, HistoryItem, LoadState, ProtocolHandler,
+    ProtocolRequest, ProtocolResponse, RpcSerializer, StorageTypes, WebViewAttributes,
+    SELECTABLE_SCRIPT, UNSELECTABLE_SCRIPT, WV,
+};
+use crate::{Result, RpcHandler, Value};
 
 use std::{
+    collections::HashMap,
     ffi::{c_void, CStr},
+    io::{ErrorKind, Read},
     os::raw::c_char,
     ptr::null,
+    rc::Rc,
     slice, str,
 };
 
 use cocoa::appkit::{NSView, NSViewHeightSizable, NSViewWidthSizable};
-use cocoa::base::id;
+use cocoa::base::{id, nil, BOOL, YES};
 use core_graphics::geometry::{CGPoint, CGRect, CGSize};
 use objc::{
     declare::ClassDecl,
@@ -23,17 +30,18 @@ use winit::{platform::macos::WindowExtMacOS, window::Window};
 pub struct InnerWebView {
     webview: Id<Object>,
     manager: id,
+    devtools_attachment: DevtoolsAttachment,
 }
 
 impl WV for InnerWebView {
     type Window = Window;
 
-    fn new<F: 'static + Fn(&str) -> Result<Vec<u8>>>(
+    fn new(
         window: &Window,
         scripts: Vec<String>,
         url: Option<Url>,
-        transparent: bool,
-        custom_protocol: Option<(String, F)>,
+        attributes: WebViewAttributes,
+        custom_protocols: Vec<(String, ProtocolHandler)>,
         rpc_handler: Option<RpcHandler>,
     ) -> Result<Self> {
         // Callback function for message handler
@@ -41,12 +49,13 @@ impl WV for InnerWebView {
             // Safety: objc runtime calls are unsafe
             unsafe {
                 let function = this.get_ivar::<*mut c_void>("function");
-                let function: &mut RpcHandler = std::mem::transmute(*function);
+                let (function, serializer): &mut (RpcHandler, Option<Rc<dyn RpcSerializer>>) =
+                    std::mem::transmute(*function);
                 let body: id = msg_send![msg, body];
                 let utf8: *const c_char = msg_send![body, UTF8String];
                 let js = CStr::from_ptr(utf8).to_str().expect("Invalid UTF8 string");
 
-                match super::rpc_proxy(js.to_string(), function) {
+                match super::rpc_proxy(js.to_string(), function, serializer.as_deref()) {
                     Ok(result) => {
                         if let Some(ref script) = result {
                             let wv: id = msg_send![msg, webView];
@@ -61,11 +70,39 @@ impl WV for InnerWebView {
             }
         }
 
+        // Navigation delegate functions called as a page loads
+        fn page_load(this: &Object, webview: id, state: LoadState) {
+            // Safety: objc runtime calls are unsafe
+            unsafe {
+                let function = this.get_ivar::<*mut c_void>("function");
+                let function: &mut Box<dyn Fn(&str, LoadState)> = std::mem::transmute(*function);
+                let url: id = msg_send![webview, URL];
+                if url.is_null() {
+                    function("", state);
+                } else {
+                    let url: id = msg_send![url, absoluteString];
+                    function(NSString(Id::from_ptr(url)).to_str(), state);
+                }
+            }
+        }
+        extern "C" fn did_start_provisional_navigation(this: &Object, _: Sel, webview: id, _: id) {
+            page_load(this, webview, LoadState::Started);
+        }
+        extern "C" fn did_commit_navigation(this: &Object, _: Sel, webview: id, _: id) {
+            page_load(this, webview, LoadState::Committed);
+        }
+        extern "C" fn did_finish_navigation(this: &Object, _: Sel, webview: id, _: id) {
+            page_load(this, webview, LoadState::Finished);
+        }
+        extern "C" fn did_fail_navigation(this: &Object, _: Sel, webview: id, _: id, _: id) {
+            page_load(this, webview, LoadState::Failed);
+        }
+
         // Task handler for custom protocol
         extern "C" fn start_task(this: &Object, _: Sel, _webview: id, task: id) {
             unsafe {
                 let function = this.get_ivar::<*mut c_void>("function");
-                let function: &mut Box<dyn Fn(&str) -> Result<Vec<u8>>> =
+                let function: &mut Box<dyn Fn(&ProtocolRequest) -> Result<ProtocolResponse>> =
                     std::mem::transmute(*function);
 
                 // Get url request
@@ -77,32 +114,99 @@ impl WV for InnerWebView {
                 };
                 let uri = nsstring.to_str();
 
+                // Method and headers
+                let method: id = msg_send![request, HTTPMethod];
+                let mut headers = HashMap::new();
+                let fields: id = msg_send![request, allHTTPHeaderFields];
+                if fields != nil {
+                    let names: id = msg_send![fields, allKeys];
+                    let count: usize = msg_send![names, count];
+                    for i in 0..count {
+                        let name: id = msg_send![names, objectAtIndex: i];
+                        let value: id = msg_send![fields, objectForKey: name];
+                        headers.insert(
+                            NSString(Id::from_ptr(name)).to_str().to_lowercase(),
+                            NSString(Id::from_ptr(value)).to_str().to_string(),
+                        );
+                    }
+                }
+                let mut body = Vec::new();
+                let data: id = msg_send![request, HTTPBody];
+                if data != nil {
+                    let bytes: *const u8 = msg_send![data, bytes];
+                    let length: usize = msg_send![data, length];
+                    if !bytes.is_null() {
+                        body.extend_from_slice(slice::from_raw_parts(bytes, length));
+                    }
+                }
+                let protocol_request = ProtocolRequest {
+                    uri: uri.to_string(),
+                    method: NSString(Id::from_ptr(method)).to_str().to_string(),
+                    headers,
+                    body,
+                };
+
                 // Send response
-                if let Ok(content) = function(uri) {
-                    let mime = MimeType::parse(&content, uri);
-                    let nsurlresponse: id = msg_send![class!(NSURLResponse), alloc];
-                    let response: id = msg_send![nsurlresponse, initWithURL:url MIMEType:NSString::new(&mime)
-                        expectedContentLength:content.len() textEncodingName:null::<c_void>()];
+                if let Ok(ProtocolResponse {
+                    body: content,
+                    reader,
+                    length,
+                    mimetype,
+                    status,
+                    headers,
+                }) = function(&protocol_request)
+                {
+                    let mime = mimetype.unwrap_or_else(|| match &reader {
+                        Some(_) => MimeType::parse_from_uri(uri).to_string(),
+                        None => MimeType::parse(&content, uri),
+                    });
+                    let fields: id = msg_send![class!(NSMutableDictionary), new];
+                    for (name, value) in &headers {
+                        let () = msg_send![fields, setObject:NSString::new(value) forKey:NSString::new(name)];
+                    }
+                    if let Some(length) = length.filter(|_| reader.is_some()) {
+                        let () = msg_send![fields, setObject:NSString::new(&length.to_string()) forKey:NSString::new("Content-Length")];
+                    }
+                    let () = msg_send![fields, setObject:NSString::new(&mime) forKey:NSString::new("Content-Type")];
+                    let nsurlresponse: id = msg_send![class!(NSHTTPURLResponse), alloc];
+                    let response: id = msg_send![nsurlresponse, initWithURL:url statusCode:status as isize
+                        HTTPVersion:NSString::new("HTTP/1.1") headerFields:fields];
                     let () = msg_send![task, didReceiveResponse: response];
 
                     // Send data
-                    let bytes = content.as_ptr() as *mut c_void;
-                    let data: id = msg_send![class!(NSData), alloc];
-                    let data: id = msg_send![data, initWithBytes:bytes length:content.len()];
-                    let () = msg_send![task, didReceiveData: data];
+                    match reader {
+                        Some(mut reader) => {
+                            let mut chunk = vec![0; 64 * 1024];
+                            loop {
+                                match reader.read(&mut chunk) {
+                                    Ok(0) => break,
+                                    Ok(read) => send_data(task, &chunk[..read]),
+                                    Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                                    Err(_) => break,
+                                }
+                            }
+                        }
+                        None => send_data(task, &content),
+                    }
 
                     // Finish
                     let () = msg_send![task, didFinish];
                 }
             }
         }
+        unsafe fn send_data(task: id, content: &[u8]) {
+            let bytes = content.as_ptr() as *mut c_void;
+            let data: id = msg_send![class!(NSData), alloc];
+            let data: id = msg_send![data, initWithBytes:bytes length:content.len()];
+            let () = msg_send![task, didReceiveData: data];
+        }
         extern "C" fn stop_task(_: &Object, _: Sel, _webview: id, _task: id) {}
 
         // Safety: objc runtime calls are unsafe
         unsafe {
             // Config and custom protocol
             let config: id = msg_send![class!(WKWebViewConfiguration), new];
-            if let Some((name, function)) = custom_protocol {
+            for (name, function) in custom_protocols {
                 let cls = ClassDecl::new("CustomURLSchemeHandler", class!(NSObject));
                 let cls = match cls {
                     Some(mut cls) => {
@@ -120,8 +224,10 @@ impl WV for InnerWebView {
                     None => class!(CustomURLSchemeHandler),
                 };
                 let handler: id = msg_send![cls, new];
-                let function: Box<Box<dyn Fn(&str) -> Result<Vec<u8>>>> =
-                    Box::new(Box::new(function));
+                let function: Box<Box<dyn Fn(&ProtocolRequest) -> Result<ProtocolResponse>>> =
+                    Box::new(Box::new(move |request: &ProtocolRequest| {
+                        function.call(request)
+                    }));
 
                 (*handler).set_ivar("function", Box::into_raw(function) as *mut _ as *mut c_void);
                 let () = msg_send![config, setURLSchemeHandler:handler forURLScheme:NSString::new(&name)];
@@ -143,8 +249,26 @@ impl WV for InnerWebView {
                 },
                 ()
             );
+            if attributes.devtools {
+                let dev = NSString::new("developerExtrasEnabled");
+                let _: id = msg_send![preference, setValue:yes forKey:dev];
+            }
+
+            if attributes.allow_file_access_from_file_urls {
+                // Equivalent Obj-C:
+                // [[config preferences] setValue:@YES forKey:@"allowFileAccessFromFileURLs"];
+                let key = NSString::new("allowFileAccessFromFileURLs");
+                let _: id = msg_send![preference, setValue:yes forKey:key];
+            }
 
-            if transparent {
+            if attributes.allow_universal_access_from_file_urls {
+                // Equivalent Obj-C:
+                // [config setValue:@YES forKey:@"allowUniversalAccessFromFileURLs"];
+                let key = NSString::new("allowUniversalAccessFromFileURLs");
+                let _: id = msg_send![config, setValue:yes forKey:key];
+            }
+
+            if attributes.transparent || attributes.background_color.is_some() {
                 // Equivalent Obj-C:
                 // [config setValue:@NO forKey:@"drawsBackground"];
                 let _: id = msg_send![config, setValue:no forKey:NSString::new("drawsBackground")];
@@ -156,8 +280,24 @@ impl WV for InnerWebView {
             let _: () = msg_send![webview, initWithFrame:rect configuration:config];
             webview.setAutoresizingMask_(NSViewHeightSizable | NSViewWidthSizable);
 
+            if let Some(zoom) = attributes.zoom_level {
+                let _: () = msg_send![webview, setPageZoom: zoom];
+            }
+
+            if let Some(scale_factor) = attributes.device_scale_factor {
+                let _: () = msg_send![webview, _setOverrideDeviceScaleFactor: scale_factor];
+            }
+
+            if let Some(user_agent) = &attributes.user_agent {
+                let _: () = msg_send![webview, setCustomUserAgent: NSString::new(user_agent)];
+            }
+
+            if attributes.back_forward_navigation_gestures {
+                let _: () = msg_send![webview, setAllowsBackForwardNavigationGestures: YES];
+            }
+
             // Message handler
-            if let Some(rpc_handler) = rpc_handler {
+            if let Some(rpc_handler) = rpc_handler.filter(|_| !attributes.rpc_bridge_disabled) {
                 let cls = ClassDecl::new("WebViewDelegate", class!(NSObject));
                 let cls = match cls {
                     Some(mut cls) => {
@@ -171,27 +311,68 @@ impl WV for InnerWebView {
                     None => class!(WebViewDelegate),
                 };
                 let handler: id = msg_send![cls, new];
-                let function: Box<RpcHandler> = Box::new(rpc_handler);
+                let function = Box::new((rpc_handler, attributes.rpc_serializer.clone()));
 
                 (*handler).set_ivar("function", Box::into_raw(function) as *mut _ as *mut c_void);
                 let external = NSString::new("external");
                 let _: () = msg_send![manager, addScriptMessageHandler:handler name:external];
             }
 
+            // Page load
+            if let Some(handler) = attributes.page_load_handler {
+                let cls = ClassDecl::new("NavigationDelegate", class!(NSObject));
+                let cls = match cls {
+                    Some(mut cls) => {
+                        cls.add_ivar::<*mut c_void>("function");
+                        cls.add_method(
+                            sel!(webView:didStartProvisionalNavigation:),
+                            did_start_provisional_navigation as extern "C" fn(&Object, Sel, id, id),
+                        );
+                        cls.add_method(
+                            sel!(webView:didCommitNavigation:),
+                            did_commit_navigation as extern "C" fn(&Object, Sel, id, id),
+                        );
+                        cls.add_method(
+                            sel!(webView:didFinishNavigation:),
+                            did_finish_navigation as extern "C" fn(&Object, Sel, id, id),
+                        );
+                        cls.add_method(
+                            sel!(webView:didFailNavigation:withError:),
+                            did_fail_navigation as extern "C" fn(&Object, Sel, id, id, id),
+                        );
+                        cls.add_method(
+                            sel!(webView:didFailProvisionalNavigation:withError:),
+                            did_fail_navigation as extern "C" fn(&Object, Sel, id, id, id),
+                        );
+                        cls.register()
+                    }
+                    None => class!(NavigationDelegate),
+                };
+                let delegate: id = msg_send![cls, new];
+                let function: Box<Box<dyn Fn(&str, LoadState)>> = Box::new(handler);
+
+                (*delegate).set_ivar("function", Box::into_raw(function) as *mut _ as *mut c_void);
+                let _: () = msg_send![webview, setNavigationDelegate: delegate];
+            }
+
             let w = Self {
                 webview: Id::from_ptr(webview),
                 manager,
+                devtools_attachment: attributes.devtools_attachment,
             };
 
             // Initialize scripts
+            if !attributes.rpc_bridge_disabled {
+                w.init(
+                    r#"window.external = {
+                        invoke: function(s) {
+                            window.webkit.messageHandlers.external.postMessage(s);
+                        },
+                    };"#,
+                );
+            }
             w.init(
-                r#"window.external = {
-                    invoke: function(s) {
-                        window.webkit.messageHandlers.external.postMessage(s);
-                    },
-                };
-
-                window.addEventListener("keydown", function(e) {
+                r#"window.addEventListener("keydown", function(e) {
                     if (e.defaultPrevented) {
                         return;
                     }
@@ -222,15 +403,7 @@ impl WV for InnerWebView {
 
             // Navigation
             if let Some(url) = url {
-                if url.cannot_be_a_base() {
-                    let s = url.as_str();
-                    if let Some(pos) = s.find(',') {
-                        let (_, path) = s.split_at(pos + 1);
-                        w.navigate_to_string(path);
-                    }
-                } else {
-                    w.navigate(url.as_str());
-                }
+                w.navigate_to_url(&url);
             }
 
             let view = window.ns_view() as id;
@@ -247,6 +420,351 @@ impl WV for InnerWebView {
         }
         Ok(())
     }
+
+    fn eval_with_callback(&self, js: &str, callback: Box<dyn FnOnce(Result<Value>)>) -> Result<()> {
+        self.eval(js)?;
+        callback(Ok(Value::Null));
+        Ok(())
+    }
+
+    fn load_url(&self, url: Url) -> Result<()> {
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            let () = msg_send![self.webview, stopLoading];
+        }
+        self.navigate_to_url(&url);
+        Ok(())
+    }
+
+    fn reload(&self, bypass_cache: bool) -> Result<()> {
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            if bypass_cache {
+                let _: id = msg_send![self.webview, reloadFromOrigin];
+            } else {
+                let _: id = msg_send![self.webview, reload];
+            }
+        }
+        Ok(())
+    }
+
+    fn set_cursor_image(
+        &self,
+        _rgba: Vec<u8>,
+        _width: u32,
+        _height: u32,
+        _hotspot_x: u32,
+        _hotspot_y: u32,
+    ) -> Result<()> {
+        // winit has no custom cursors.
+        Ok(())
+    }
+
+    fn reset_cursor(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_user_agent(&self, user_agent: &str) -> Result<()> {
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            let _: () = msg_send![self.webview, setCustomUserAgent: NSString::new(user_agent)];
+        }
+        Ok(())
+    }
+
+    fn reload_with_user_agent(&self, user_agent: &str, _restore: bool) -> Result<()> {
+        self.set_user_agent(user_agent)?;
+        self.reload(false)
+    }
+
+    fn set_zoom_level(&self, zoom: f64) -> Result<()> {
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            let _: () = msg_send![self.webview, setPageZoom: zoom];
+        }
+        Ok(())
+    }
+
+    fn zoom_level(&self) -> f64 {
+        // Safety: objc runtime calls are unsafe
+        unsafe { msg_send![self.webview, pageZoom] }
+    }
+
+    fn go_back(&self) -> Result<()> {
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            let _: id = msg_send![self.webview, goBack];
+        }
+        Ok(())
+    }
+
+    fn go_forward(&self) -> Result<()> {
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            let _: id = msg_send![self.webview, goForward];
+        }
+        Ok(())
+    }
+
+    fn can_go_back(&self) -> bool {
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            let can: BOOL = msg_send![self.webview, canGoBack];
+            can == YES
+        }
+    }
+
+    fn can_go_forward(&self) -> bool {
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            let can: BOOL = msg_send![self.webview, canGoForward];
+            can == YES
+        }
+    }
+
+    fn url(&self) -> Option<String> {
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            let url: id = msg_send![self.webview, URL];
+            if url.is_null() {
+                return None;
+            }
+            let url: id = msg_send![url, absoluteString];
+            Some(NSString(Id::from_ptr(url)).to_str().to_string())
+        }
+    }
+
+    fn title(&self) -> Option<String> {
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            let title: id = msg_send![self.webview, title];
+            if title.is_null() {
+                return None;
+            }
+            Some(NSString(Id::from_ptr(title)).to_str().to_string()).filter(|t| !t.is_empty())
+        }
+    }
+
+    fn load_bytes(&self, bytes: &[u8], mime_type: &str, base_url: Option<Url>) -> Result<()> {
+        let (essence, charset) = MimeType::split_charset(mime_type);
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            let () = msg_send![self.webview, stopLoading];
+            let data: id =
+                msg_send![class!(NSData), dataWithBytes:bytes.as_ptr() length:bytes.len()];
+            // WKWebView requires a base URL, `about:blank` keeps relative URLs unresolved.
+            let base_url = base_url.as_ref().map_or("about:blank", Url::as_str);
+            let base_url: id = msg_send![class!(NSURL), URLWithString: NSString::new(base_url)];
+            let charset = NSString::new(charset.unwrap_or("UTF-8"));
+            let _: id = msg_send![self.webview, loadData:data MIMEType:NSString::new(essence) characterEncodingName:charset baseURL:base_url];
+        }
+        Ok(())
+    }
+
+    fn load_html(&self, html: &str, base_url: Option<Url>) -> Result<()> {
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            let () = msg_send![self.webview, stopLoading];
+            let base_url: id = match base_url {
+                Some(url) => msg_send![class!(NSURL), URLWithString: NSString::new(url.as_str())],
+                None => nil,
+            };
+            let _: id =
+                msg_send![self.webview, loadHTMLString:NSString::new(html) baseURL:base_url];
+        }
+        Ok(())
+    }
+
+    fn set_editable(&self, editable: bool) -> Result<()> {
+        self.eval(if editable {
+            "document.designMode = 'on'"
+        } else {
+            "document.designMode = 'off'"
+        })
+    }
+
+    fn find(&self, _text: &str, _case_sensitive: bool) -> Result<()> {
+        // WKWebView has no find-in-page API to drive.
+        Ok(())
+    }
+
+    fn find_next(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn find_previous(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn stop_find(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_enable_drag_region(&self, _enabled: bool) -> Result<()> {
+        // The window can't be moved from a press in the page with WKWebView.
+        Ok(())
+    }
+
+    fn set_selectable(&self, selectable: bool) -> Result<()> {
+        self.eval(if selectable {
+            SELECTABLE_SCRIPT
+        } else {
+            UNSELECTABLE_SCRIPT
+        })
+    }
+
+    fn back_forward_list(&self) -> BackForwardList {
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            let list: id = msg_send![self.webview, backForwardList];
+            let item = |index: isize| {
+                let item: id = msg_send![list, itemAtIndex: index];
+                history_item(item)
+            };
+
+            // Negative indices go back in history and positive ones forward.
+            BackForwardList {
+                back: (1..)
+                    .map(|i| item(-i))
+                    .take_while(Option::is_some)
+                    .flatten()
+                    .collect(),
+                current: item(0),
+                forward: (1..)
+                    .map(item)
+                    .take_while(Option::is_some)
+                    .flatten()
+                    .collect(),
+            }
+        }
+    }
+
+    fn clear_history(&self) -> Result<()> {
+        // Safety: objc runtime calls are unsafe
+        // Equivalent Obj-C:
+        // [[webview backForwardList] _clear]
+        unsafe {
+            let list: id = msg_send![self.webview, backForwardList];
+            let () = msg_send![list, _clear];
+        }
+        Ok(())
+    }
+
+    fn session_state(&self) -> Option<Vec<u8>> {
+        // Safety: objc runtime calls are unsafe
+        // Equivalent Obj-C:
+        // NSData *state = [webview interactionState];
+        unsafe {
+            let supported: BOOL =
+                msg_send![self.webview, respondsToSelector: sel!(interactionState)];
+            if supported != YES {
+                return None;
+            }
+            let state: id = msg_send![self.webview, interactionState];
+            if state.is_null() {
+                return None;
+            }
+            let bytes: *const u8 = msg_send![state, bytes];
+            let length: usize = msg_send![state, length];
+            Some(slice::from_raw_parts(bytes, length).to_vec())
+        }
+    }
+
+    fn restore_session_state(&self, state: &[u8]) -> Result<()> {
+        // Safety: objc runtime calls are unsafe
+        // Equivalent Obj-C:
+        // [webview setInteractionState:[NSData dataWithBytes:state length:length]];
+        unsafe {
+            let supported: BOOL =
+                msg_send![self.webview, respondsToSelector: sel!(setInteractionState:)];
+            if supported == YES {
+                let state: id =
+                    msg_send![class!(NSData), dataWithBytes:state.as_ptr() length:state.len()];
+                let () = msg_send![self.webview, setInteractionState: state];
+            }
+        }
+        Ok(())
+    }
+
+    fn cookies(&self, _url: &str, callback: Box<dyn FnOnce(Vec<Cookie>)>) -> Result<()> {
+        // WKHTTPCookieStore only answers through blocks, which can't be made without the block
+        // crate.
+        callback(Vec::new());
+        Ok(())
+    }
+
+    fn set_cookie(&self, _cookie: &Cookie) -> Result<()> {
+        Ok(())
+    }
+
+    fn clear_all_cookies(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn clear_cache(&self) -> Result<()> {
+        // WKWebsiteDataStore also needs a completion block to remove data.
+        Ok(())
+    }
+
+    fn clear_storage(&self, _types: StorageTypes) -> Result<()> {
+        Ok(())
+    }
+
+    fn is_devtools_open(&self) -> bool {
+        // Safety: objc runtime calls are unsafe
+        // Equivalent Obj-C:
+        // [[webview _inspector] isVisible]
+        unsafe {
+            let inspector: id = msg_send![self.webview, _inspector];
+            let visible: BOOL = msg_send![inspector, isVisible];
+            visible == YES
+        }
+    }
+
+    fn open_devtools(&self, attachment: Option<DevtoolsAttachment>) -> Result<()> {
+        // Safety: objc runtime calls are unsafe
+        // Equivalent Obj-C:
+        // [[webview _inspector] show]
+        // [[webview _inspector] attach]
+        unsafe {
+            let inspector: id = msg_send![self.webview, _inspector];
+            let () = msg_send![inspector, show];
+            match attachment.unwrap_or(self.devtools_attachment) {
+                DevtoolsAttachment::Attached => {
+                    let () = msg_send![inspector, attach];
+                }
+                DevtoolsAttachment::Detached => {
+                    let () = msg_send![inspector, detach];
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn close_devtools(&self) -> Result<()> {
+        // Safety: objc runtime calls are unsafe
+        // Equivalent Obj-C:
+        // [[webview _inspector] close]
+        unsafe {
+            let inspector: id = msg_send![self.webview, _inspector];
+            let () = msg_send![inspector, close];
+        }
+        Ok(())
+    }
+
+    fn devtools_attached_height(&self) -> Option<u32> {
+        // WKWebView doesn't expose the size of its inspector.
+        None
+    }
+
+    fn focus(&self) -> Result<()> {
+        // Safety: objc runtime calls are unsafe
+        unsafe {
+            let window: id = msg_send![self.webview, window];
+            let _: BOOL = msg_send![window, makeFirstResponder: self.webview];
+        }
+        Ok(())
+    }
 }
 
 impl InnerWebView {
@@ -261,6 +779,18 @@ impl InnerWebView {
         }
     }
 
+    fn navigate_to_url(&self, url: &Url) {
+        if url.cannot_be_a_base() {
+            let s = url.as_str();
+            if let Some(pos) = s.find(',') {
+                let (_, path) = s.split_at(pos + 1);
+                self.navigate_to_string(path);
+            }
+        } else {
+            self.navigate(url.as_str());
+        }
+    }
+
     fn navigate(&self, url: &str) {
         // Safety: objc runtime calls are unsafe
         unsafe {
@@ -281,6 +811,24 @@ impl InnerWebView {
 
 const UTF8_ENCODING: usize = 4;
 
+// Safety: `item` must be nil or a WKBackForwardListItem.
+unsafe fn history_item(item: id) -> Option<HistoryItem> {
+    if item.is_null() {
+        return None;
+    }
+    let url: id = msg_send![item, URL];
+    let url: id = msg_send![url, absoluteString];
+    let title: id = msg_send![item, title];
+    Some(HistoryItem {
+        url: NSString(Id::from_ptr(url)).to_str().to_string(),
+        title: if title.is_null() {
+            None
+        } else {
+            Some(NSString(Id::from_ptr(title)).to_str().to_string())
+        },
+    })
+}
+
 struct NSString(Id<Object>);
 
 impl NSString {
@@ -15,13 +15,28 @@ use win::*;
 
 use crate::{Error, Result};
 
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    io::{Read, Seek},
+    rc::Rc,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
 
 use serde_json::Value;
 use url::Url;
 
 #[cfg(target_os = "linux")]
-use gtk::ApplicationWindow as Window;
+use glib::IsA;
+#[cfg(target_os = "linux")]
+use gtk::{ApplicationWindow as Window, PositionType, Widget};
+#[cfg(target_os = "linux")]
+use webkit2gtk::WebContext;
 #[cfg(target_os = "windows")]
 use winit::platform::windows::WindowExtWindows;
 #[cfg(not(target_os = "linux"))]
@@ -29,23 +44,393 @@ use winit::window::Window;
 
 pub type RpcHandler = Box<dyn Fn(RpcRequest) -> Option<RpcResponse> + Send>;
 
+/// Encodes the RPC messages exchanged with the page in another format than JSON, e.g. a more
+/// compact one for large payloads. Messages are passed as strings, so binary formats have to be
+/// encoded in text, e.g. in base64.
+pub trait RpcSerializer {
+    /// A JavaScript function encoding a request object into the message sent to the handler,
+    /// like `JSON.stringify`.
+    fn js_encoder(&self) -> &str;
+
+    /// A JavaScript function decoding a message from [`RpcSerializer::encode`] into a value,
+    /// like `JSON.parse`.
+    fn js_decoder(&self) -> &str;
+
+    /// Decodes a request encoded by the [`RpcSerializer::js_encoder`] function.
+    fn decode_request(&self, message: &str) -> Result<RpcRequest>;
+
+    /// Encodes the result or error of a response, decoded by the [`RpcSerializer::js_decoder`]
+    /// function.
+    fn encode(&self, value: &Value) -> Result<String>;
+}
+
+/// Decides whether the page may be left when it asks for confirmation with a `beforeunload`
+/// handler. It's given the message of the page and returns `true` to leave it.
+pub type BeforeUnloadHandler = Box<dyn Fn(&str) -> bool>;
+
+/// Answers the dialogs opened by the page with `alert()`, `confirm()` and `prompt()`.
+pub type ScriptDialogHandler = Box<dyn Fn(ScriptDialog) -> ScriptDialogAnswer>;
+
+/// Intercepts the pastes of the user in the page. It's given the content of the clipboard and
+/// returns `true` to prevent the page from receiving the paste.
+pub type PasteHandler = Box<dyn Fn(ClipboardContent) -> bool>;
+
+/// The content of the clipboard when the user pastes in the page, read natively so it isn't
+/// restricted like the clipboard of the web API.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClipboardContent {
+    /// The text in the clipboard, if any.
+    pub text: Option<String>,
+    /// The image in the clipboard encoded in PNG, if any.
+    pub image: Option<Vec<u8>>,
+}
+
+/// A request to a custom protocol, given to the handler registered with
+/// [`WebViewBuilder::register_request_protocol`].
+///
+/// ## Platform-specific
+///
+/// - **Linux:** The method and the headers need WebKitGTK 2.36 and the `v2_36` feature. Without
+///   it, every request is a `GET` without headers, so `Range` requests aren't answered either. The
+///   body needs WebKitGTK 2.40 and the `v2_40` feature.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProtocolRequest {
+    /// The URI of the request, including the scheme of the protocol.
+    pub uri: String,
+    /// The HTTP method of the request, like `GET` or `POST`.
+    pub method: String,
+    /// The HTTP headers of the request, by lowercase name. Repeated headers are joined with `, `.
+    pub headers: HashMap<String, String>,
+    /// The body of the request, like the fields of a form submitted with `method="POST"`.
+    pub body: Vec<u8>,
+}
+
+/// The answer of a custom protocol handler.
+pub struct ProtocolResponse {
+    /// The content of the response.
+    pub body: Vec<u8>,
+    /// The content of the response read as it's needed, instead of `body`. See
+    /// [`ProtocolResponse::from_reader`].
+    pub reader: Option<Box<dyn ProtocolReader>>,
+    /// The length of the content of `reader`, if it's known.
+    pub length: Option<u64>,
+    /// The MIME type of the content, guessed from the content and the URI when it's `None`, or
+    /// only from the URI for a `reader`.
+    pub mimetype: Option<String>,
+    /// The HTTP status code of the response, `200` by default.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Needs WebKitGTK 2.36 and the `v2_36` feature, otherwise it's always `200`.
+    pub status: u16,
+    /// Additional HTTP headers of the response. The `Content-Type` is set from `mimetype`.
+    ///
+    /// A `Content-Encoding` header lets the content be served compressed, like pre-compressed
+    /// assets, and decompressed by the webview.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Only the `gzip` and `deflate` encodings are supported, the requests of
+    ///   responses with another encoding, like `br`, fail. The other headers need WebKitGTK 2.36
+    ///   and the `v2_36` feature, and are dropped without it.
+    pub headers: HashMap<String, String>,
+}
+
+impl Default for ProtocolResponse {
+    fn default() -> Self {
+        Self {
+            body: Vec::new(),
+            reader: None,
+            length: None,
+            mimetype: None,
+            status: 200,
+            headers: HashMap::new(),
+        }
+    }
+}
+
+impl ProtocolResponse {
+    /// Creates a response whose content is read from `reader` in chunks as the page needs it,
+    /// like a large video, instead of being loaded in memory at once. `length` is the length of
+    /// the content, if it's known. `reader` is seeked to the part of the content `Range` requests
+    /// ask for.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The content is read entirely before the response is sent.
+    pub fn from_reader<R: Read + Seek + Send + 'static>(reader: R, length: Option<u64>) -> Self {
+        Self {
+            reader: Some(Box::new(reader)),
+            length,
+            ..Default::default()
+        }
+    }
+}
+
+/// The content of a [`ProtocolResponse`] read as it's needed, which can be seeked to answer
+/// `Range` requests without reading what comes before. It's implemented for every [`Read`] and
+/// [`Seek`] type, like [`File`](std::fs::File).
+pub trait ProtocolReader: Read + Seek + Send {}
+
+impl<R: Read + Seek + Send> ProtocolReader for R {}
+
+impl fmt::Debug for ProtocolResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProtocolResponse")
+            .field("body", &self.body)
+            .field("reader", &self.reader.as_ref().map(|_| "Read"))
+            .field("length", &self.length)
+            .field("mimetype", &self.mimetype)
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+impl From<Vec<u8>> for ProtocolResponse {
+    fn from(body: Vec<u8>) -> Self {
+        Self {
+            body,
+            ..Default::default()
+        }
+    }
+}
+
+pub(crate) type LocalProtocolHandler = Box<dyn Fn(&ProtocolRequest) -> Result<ProtocolResponse>>;
+
+pub(crate) type ThreadedProtocolHandler =
+    Arc<dyn Fn(&ProtocolRequest) -> Result<ProtocolResponse> + Send + Sync>;
+
+/// A custom protocol handler, as registered on [`WebViewBuilder`].
+pub(crate) enum ProtocolHandler {
+    /// Always called on the main thread.
+    Local(LocalProtocolHandler),
+    /// Called on worker threads where the platform supports it.
+    Threaded(ThreadedProtocolHandler),
+}
+
+#[cfg(not(target_os = "linux"))]
+impl ProtocolHandler {
+    pub fn call(&self, request: &ProtocolRequest) -> Result<ProtocolResponse> {
+        match self {
+            ProtocolHandler::Local(handler) => handler(request),
+            ProtocolHandler::Threaded(handler) => handler(request),
+        }
+    }
+}
+
+/// The progress of a page load, reported by [`WebViewBuilder::page_load_handler`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoadState {
+    /// The navigation started, the request is being sent.
+    Started,
+    /// The first data of the page was received and the previous page is gone.
+    Committed,
+    /// The page and its resources finished loading.
+    Finished,
+    /// The navigation failed. No [`LoadState::Finished`] follows.
+    Failed,
+}
+
+/// Where the developer tools are shown when they're opened.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DevtoolsAttachment {
+    /// Docked inside the window of the WebView.
+    #[default]
+    Attached,
+    /// In a window of their own.
+    Detached,
+}
+
+/// The formatting at the caret of editable content and whether some of it is selected, reported
+/// by [`WebViewBuilder::editor_state_handler`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EditorState {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    /// Whether some content of the page is selected.
+    pub selection: bool,
+}
+
+/// Customizes the context menu of the page before it's shown. It's given the menu and returns
+/// the one to show, or `None` to show no menu.
+pub type ContextMenuHandler = Box<dyn Fn(ContextMenu) -> Option<ContextMenu>>;
+
+/// The context menu the WebView is about to show, with what's under the cursor.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContextMenu {
+    /// The items of the menu, in order.
+    pub items: Vec<ContextMenuItem>,
+    /// The URL of the link under the cursor, if any.
+    pub link_url: Option<String>,
+    /// The URL of the image or the media under the cursor, if any.
+    pub media_url: Option<String>,
+    /// Whether the cursor is over editable content.
+    pub editable: bool,
+    /// Whether the cursor is over selected content.
+    pub selection: bool,
+}
+
+/// An item of a [`ContextMenu`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContextMenuItem {
+    /// An item of the web engine, identified by its action. These items can be removed and
+    /// reordered, but not added. Items with the same action, like spelling guesses, keep their
+    /// order.
+    Builtin {
+        action: ContextMenuAction,
+    },
+    /// An item of the application, reported with its `id` when it's activated.
+    Custom {
+        id: String,
+        label: String,
+    },
+    Separator,
+}
+
+/// The action of a [`ContextMenuItem::Builtin`] item, named after the items of WebKit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContextMenuAction {
+    OpenLink,
+    OpenLinkInNewWindow,
+    DownloadLinkToDisk,
+    CopyLinkToClipboard,
+    OpenImageInNewWindow,
+    DownloadImageToDisk,
+    CopyImageToClipboard,
+    CopyImageUrlToClipboard,
+    OpenFrameInNewWindow,
+    GoBack,
+    GoForward,
+    Stop,
+    Reload,
+    Copy,
+    Cut,
+    Paste,
+    Delete,
+    SelectAll,
+    InputMethods,
+    Unicode,
+    SpellingGuess,
+    NoGuessesFound,
+    IgnoreSpelling,
+    LearnSpelling,
+    IgnoreGrammar,
+    FontMenu,
+    Bold,
+    Italic,
+    Underline,
+    Outline,
+    InspectElement,
+    OpenVideoInNewWindow,
+    OpenAudioInNewWindow,
+    CopyVideoLinkToClipboard,
+    CopyAudioLinkToClipboard,
+    ToggleMediaControls,
+    ToggleMediaLoop,
+    EnterVideoFullscreen,
+    MediaPlay,
+    MediaPause,
+    MediaMute,
+    DownloadVideoToDisk,
+    DownloadAudioToDisk,
+    InsertEmoji,
+    /// An item without a known action, like the items added by extensions, numbered in the
+    /// order they appear in the menu.
+    Other(u32),
+}
+
+/// A dialog opened by JavaScript in the page.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptDialog {
+    /// Opened by `alert()`.
+    Alert { message: String },
+    /// Opened by `confirm()`.
+    Confirm { message: String },
+    /// Opened by `prompt()`, with the text to show in its text entry.
+    Prompt {
+        message: String,
+        default_text: String,
+    },
+}
+
+/// The answer to a [`ScriptDialog`]. An answer of another kind than the dialog cancels it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptDialogAnswer {
+    /// Closes an alert.
+    Alert,
+    /// Whether a confirmation is confirmed.
+    Confirm(bool),
+    /// The text entered in a prompt, or `None` to cancel it.
+    Prompt(Option<String>),
+}
+
+// Keeps the zoom level in a range where pages stay usable.
+fn clamp_zoom(zoom: f64) -> f64 {
+    zoom.clamp(0.25, 5.0)
+}
+
+type LoadWaiters = Rc<RefCell<Vec<Box<dyn FnOnce()>>>>;
+
+// Chains the page load handler with the load finished handler and the callbacks waiting for the
+// next page load, so the platforms only have a single handler to call.
+fn load_waiters(attributes: &mut WebViewAttributes) -> LoadWaiters {
+    let waiters = LoadWaiters::default();
+    let pending = Rc::clone(&waiters);
+    let page_load_handler = attributes.page_load_handler.take();
+    let load_finished_handler = attributes.load_finished_handler.take();
+    attributes.page_load_handler = Some(Box::new(move |url, state| {
+        if let Some(handler) = &page_load_handler {
+            handler(url, state);
+        }
+        if state == LoadState::Finished || state == LoadState::Failed {
+            if let Some(handler) = &load_finished_handler {
+                handler();
+            }
+            // Taken first as the callbacks may wait for another load.
+            for callback in pending.replace(Vec::new()) {
+                callback();
+            }
+        }
+    }));
+    waiters
+}
+
 // Helper so all platforms handle RPC messages consistently.
-fn rpc_proxy(js: String, handler: &RpcHandler) -> Result<Option<String>> {
-    let req = serde_json::from_str::<RpcRequest>(&js)
-        .map_err(|e| Error::RpcScriptError(e.to_string(), js))?;
+fn rpc_proxy(
+    js: String,
+    handler: &RpcHandler,
+    serializer: Option<&dyn RpcSerializer>,
+) -> Result<Option<String>> {
+    let req = match serializer {
+        Some(serializer) => serializer.decode_request(&js),
+        None => serde_json::from_str::<RpcRequest>(&js).map_err(Into::into),
+    }
+    .map_err(|e| Error::RpcScriptError(e.to_string(), js))?;
 
     let mut response = (handler)(req);
     // Got a synchronous response so convert it to a script to be evaluated
     if let Some(mut response) = response.take() {
         if let Some(id) = response.id {
-            let js = if let Some(error) = response.error.take() {
-                RpcResponse::into_error_script(id, error)?
+            let (function, value) = if let Some(error) = response.error.take() {
+                ("_error", error)
             } else if let Some(result) = response.result.take() {
-                RpcResponse::into_result_script(id, result)?
+                ("_result", result)
             } else {
                 // No error or result, assume a positive response
                 // with empty result (ACK)
-                RpcResponse::into_result_script(id, Value::Null)?
+                ("_result", Value::Null)
+            };
+            let js = match serializer {
+                Some(serializer) => format!(
+                    "window.external.rpc.{}({}, window.external.rpc._decode({}))",
+                    function,
+                    id,
+                    serde_json::to_string(&serializer.encode(&value)?)?
+                ),
+                None if function == "_error" => RpcResponse::into_error_script(id, value)?,
+                None => RpcResponse::into_result_script(id, value)?,
             };
             Ok(Some(js))
         } else {
@@ -62,14 +447,16 @@ fn rpc_proxy(js: String, handler: &RpcHandler) -> Result<Option<String>> {
 /// scripts for those who prefer to control fine grained window creation and event handling.
 /// [`WebViewBuilder`] privides ability to setup initialization before web engine starts.
 pub struct WebViewBuilder {
-    transparent: bool,
+    attributes: WebViewAttributes,
     tx: Sender<String>,
     rx: Receiver<String>,
     initialization_scripts: Vec<String>,
     window: Window,
     url: Option<Url>,
-    custom_protocol: Option<(String, Box<dyn Fn(&str) -> Result<Vec<u8>>>)>,
+    custom_protocols: Vec<(String, ProtocolHandler)>,
     rpc_handler: Option<RpcHandler>,
+    #[cfg(target_os = "linux")]
+    web_context: Option<WebContext>,
 }
 
 impl WebViewBuilder {
@@ -83,16 +470,359 @@ impl WebViewBuilder {
             initialization_scripts: vec![],
             window,
             url: None,
-            transparent: false,
-            custom_protocol: None,
+            attributes: Default::default(),
+            custom_protocols: vec![],
             rpc_handler: None,
+            #[cfg(target_os = "linux")]
+            web_context: None,
         })
     }
 
     /// Whether the WebView window should be transparent. If this is true, writing colors
     /// with alpha values different than `1.0` will produce a transparent window.
     pub fn transparent(mut self, transparent: bool) -> Self {
-        self.transparent = transparent;
+        self.attributes.transparent = transparent;
+        self
+    }
+
+    /// The color of the WebView until the page paints its own background, as
+    /// `(red, green, blue, alpha)`. It's ignored when the WebView is transparent.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported.
+    /// - **macOS:** The WebView doesn't draw a background, the one of the window shows instead.
+    pub fn background_color(mut self, color: (u8, u8, u8, u8)) -> Self {
+        self.attributes.background_color = Some(color);
+        self
+    }
+
+    /// Whether the WebView takes the keyboard focus when it's created. The default is `true`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.attributes.focus_disabled = !focused;
+        self
+    }
+
+    /// Whether the window title follows the title of the page, like a browser tab does.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Unsupported.
+    pub fn html_title_as_window_title(mut self, enabled: bool) -> Self {
+        self.attributes.html_title_as_window_title = enabled;
+        self
+    }
+
+    /// Add an invisible border of `width` pixels along the edges of the WebView, which resizes
+    /// the window when dragged. This is meant for windows without decorations, which have no
+    /// resize border of their own.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn resize_border(mut self, width: u32) -> Self {
+        self.attributes.resize_border = Some(width);
+        self
+    }
+
+    /// Whether swiping with two fingers on a touchpad navigates back and forward in history.
+    /// This is disabled by default, as app-like content rarely expects it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported.
+    pub fn back_forward_navigation_gestures(mut self, enabled: bool) -> Self {
+        self.attributes.back_forward_navigation_gestures = enabled;
+        self
+    }
+
+    /// Whether the page bounces (rubber-bands) when scrolled past its edges on a touchpad. This
+    /// is enabled by default; disabling it also stops scroll chaining out of the page.
+    pub fn overscroll(mut self, enabled: bool) -> Self {
+        self.attributes.overscroll_disabled = !enabled;
+        self
+    }
+
+    /// Whether the zoom level is multiplied by the scale factor of the monitor, and updated when the
+    /// window moves to a monitor with another scale factor. [`WebView::zoom_level`] still returns
+    /// the zoom level of the page.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn auto_dpi_zoom(mut self, enabled: bool) -> Self {
+        self.attributes.auto_dpi_zoom = enabled;
+        self
+    }
+
+    /// The initial zoom level, where `1.0` is 100%. See [`WebView::set_zoom_level`] for the range.
+    pub fn zoom_level(mut self, zoom: f64) -> Self {
+        self.attributes.zoom_level = Some(clamp_zoom(zoom));
+        self
+    }
+
+    /// Override the device scale factor the WebView renders at, regardless of the monitor.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Applied through the zoom level, which is multiplied by the scale factor over
+    ///   the one of the monitor. [`WebView::zoom_level`] still returns the zoom level of the page.
+    /// - **Windows:** Unsupported.
+    pub fn device_scale_factor(mut self, scale_factor: f64) -> Self {
+        self.attributes.device_scale_factor = Some(scale_factor);
+        self
+    }
+
+    /// Whether JavaScript running in a `file://` page can read other `file://` URLs. This is
+    /// disabled by default, as it exposes every local file whose path is known to the page.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported.
+    pub fn allow_file_access_from_file_urls(mut self, allow: bool) -> Self {
+        self.attributes.allow_file_access_from_file_urls = allow;
+        self
+    }
+
+    /// Whether JavaScript running in a `file://` page can access content from any origin. This
+    /// is disabled by default, as it lets the page send local files anywhere.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported.
+    pub fn allow_universal_access_from_file_urls(mut self, allow: bool) -> Self {
+        self.attributes.allow_universal_access_from_file_urls = allow;
+        self
+    }
+
+    /// Fail the requests that the handlers registered with
+    /// [`WebViewBuilder::register_threaded_protocol`] don't answer within `timeout`. The handler
+    /// itself keeps running until it returns, its answer is then dropped. Handlers registered
+    /// with [`WebViewBuilder::register_request_protocol`] run on the main thread and can't time
+    /// out.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported. The handler always runs on the main thread.
+    pub fn custom_protocol_timeout(mut self, timeout: Duration) -> Self {
+        self.attributes.custom_protocol_timeout = Some(timeout);
+        self
+    }
+
+    /// Handle the requests of the protocols registered with
+    /// [`WebViewBuilder::register_threaded_protocol`] on `threads` worker threads, so that at
+    /// most that many run at once and the others wait in a queue. By default each request gets
+    /// a thread of its own.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported. The handler always runs on the main thread.
+    pub fn custom_protocol_concurrency(mut self, threads: usize) -> Self {
+        self.attributes.custom_protocol_concurrency = Some(threads);
+        self
+    }
+
+    /// Let pages from other origins, e.g. a remote page loaded over `https://`, `fetch` from the
+    /// custom protocol. This is disabled by default, so only pages served by the custom protocol
+    /// itself can read its responses.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn custom_protocol_cors(mut self, enabled: bool) -> Self {
+        self.attributes.custom_protocol_cors = enabled;
+        self
+    }
+
+    /// Treat the custom protocol like `file://`, so that only pages from local schemes can load
+    /// its URLs.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn custom_protocol_local(mut self, local: bool) -> Self {
+        self.attributes.custom_protocol_local = local;
+        self
+    }
+
+    /// Set the languages the WebView asks content in, most preferred first. They are sent in the
+    /// `Accept-Language` header and exposed to JavaScript as `navigator.languages`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** The languages are set on the web context, so they apply to every WebView
+    ///   sharing it, and the last WebView built with languages decides them for all of them.
+    /// - **Windows / macOS:** Unsupported.
+    pub fn accept_languages(mut self, languages: Vec<String>) -> Self {
+        self.attributes.accept_languages = Some(languages);
+        self
+    }
+
+    /// Set the user agent the WebView identifies itself with.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported.
+    pub fn user_agent(mut self, user_agent: String) -> Self {
+        self.attributes.user_agent = Some(user_agent);
+        self
+    }
+
+    /// Whether the developer tools can be opened, from the context menu or with
+    /// [`WebView::open_devtools`]. They are always enabled in debug builds.
+    pub fn devtools(mut self, enabled: bool) -> Self {
+        self.attributes.devtools = enabled;
+        self
+    }
+
+    /// Set where [`WebView::open_devtools`] shows the developer tools. The default is
+    /// [`DevtoolsAttachment::Attached`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported, the developer tools are always detached.
+    pub fn devtools_attachment(mut self, attachment: DevtoolsAttachment) -> Self {
+        self.attributes.devtools_attachment = attachment;
+        self
+    }
+
+    /// Set the HTML shown when a page fails to load, instead of the default error page of the
+    /// web engine. The `{url}` and `{error}` placeholders are replaced with the URL of the page
+    /// and the error message.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn error_page(mut self, html: String) -> Self {
+        self.attributes.error_page = Some(html);
+        self
+    }
+
+    /// Set the handler deciding whether the page may be left when its `beforeunload` handler asks
+    /// for confirmation, e.g. to guard unsaved work. By default the web engine shows its own
+    /// confirmation dialog.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn before_unload_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&str) -> bool + 'static,
+    {
+        self.attributes.before_unload_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the handler intercepting the pastes of the user with the keyboard, e.g. to parse
+    /// tabular data or images that the web clipboard API doesn't give to the page.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn paste_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(ClipboardContent) -> bool + 'static,
+    {
+        self.attributes.paste_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the handler customizing the context menu of the page, e.g. to remove items of the web
+    /// engine or add items of the application. `item_handler` is called with the id of the
+    /// custom items activated by the user.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn context_menu_handler<F, I>(mut self, handler: F, item_handler: I) -> Self
+    where
+        F: Fn(ContextMenu) -> Option<ContextMenu> + 'static,
+        I: Fn(&str) + 'static,
+    {
+        self.attributes.context_menu_handler = Some(Box::new(handler));
+        self.attributes.context_menu_item_handler = Some(Rc::new(item_handler));
+        self
+    }
+
+    /// Set a handler following the loads of the main frame, given the URL of the page and how far
+    /// its load got. Every load ends with either [`LoadState::Finished`] or [`LoadState::Failed`].
+    pub fn page_load_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&str, LoadState) + 'static,
+    {
+        self.attributes.page_load_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Set a handler called with `true` when the developer tools open and `false` when they
+    /// close, e.g. to keep a toggle button in sync without polling
+    /// [`WebView::is_devtools_open`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn devtools_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(bool) + 'static,
+    {
+        self.attributes.devtools_handler = Some(Rc::new(handler));
+        self
+    }
+
+    /// Set a handler called when the formatting at the caret or the selection of the page changes,
+    /// e.g. to keep the buttons of an editor toolbar in sync.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn editor_state_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(EditorState) + 'static,
+    {
+        self.attributes.editor_state_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Set a handler called with the selected match and the number of matches of
+    /// [`WebView::find`], once they are counted and when the selection moves. The first match is
+    /// `1`, both are `0` when the text isn't found.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn find_result_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(u32, u32) + 'static,
+    {
+        self.attributes.find_result_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Set a handler called every time a page finished loading, successfully or not.
+    pub(crate) fn load_finished_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn() + 'static,
+    {
+        self.attributes.load_finished_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the handler answering the dialogs opened by the page with `alert()`, `confirm()` and
+    /// `prompt()`, e.g. to show them with the look of the application. By default the web engine
+    /// shows its own dialogs.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn script_dialog_handler<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(ScriptDialog) -> ScriptDialogAnswer + 'static,
+    {
+        self.attributes.script_dialog_handler = Some(Box::new(handler));
         self
     }
 
@@ -111,12 +841,71 @@ impl WebViewBuilder {
         Dispatcher(self.tx.clone())
     }
 
-    /// Register custom file loading protocol
-    pub fn register_protocol<F>(mut self, name: String, handler: F) -> Self
+    /// Register custom file loading protocol, whose handler is given the URI of the requests.
+    /// Use [`WebViewBuilder::register_request_protocol`] to also get their method and headers.
+    pub fn register_protocol<F>(self, name: String, handler: F) -> Self
     where
         F: Fn(&str) -> Result<Vec<u8>> + 'static,
     {
-        self.custom_protocol = Some((name, Box::new(handler)));
+        self.register_request_protocol(name, move |request| handler(&request.uri).map(Into::into))
+    }
+
+    /// Register custom protocol whose handler is given the whole [`ProtocolRequest`]. It can be
+    /// called several times to register protocols with different names,
+    /// [`WebViewBuilder::build`] fails if a name is repeated.
+    ///
+    /// The handler is called on the main thread, so it can use the window, but a slow handler
+    /// freezes the whole window until it returns. Use
+    /// [`WebViewBuilder::register_threaded_protocol`] for handlers that may take time.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** The method and the headers of the request, and the status and the headers of
+    ///   the response require WebKitGTK 2.36 and the `v2_36` feature, the body of the request 2.40
+    ///   and the `v2_40` feature.
+    /// - **macOS:** The body is empty before macOS 10.15.
+    pub fn register_request_protocol<F>(self, name: String, handler: F) -> Self
+    where
+        F: Fn(&ProtocolRequest) -> Result<ProtocolResponse> + 'static,
+    {
+        self.register_protocol_handler(name, ProtocolHandler::Local(Box::new(handler)))
+    }
+
+    /// Register custom protocol like [`WebViewBuilder::register_request_protocol`], but whose
+    /// handler is called on worker threads, so that slow handlers don't freeze the window. The
+    /// number of threads is set with [`WebViewBuilder::custom_protocol_concurrency`] and
+    /// unanswered requests can be failed with [`WebViewBuilder::custom_protocol_timeout`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** The handler is called on the main thread.
+    pub fn register_threaded_protocol<F>(self, name: String, handler: F) -> Self
+    where
+        F: Fn(&ProtocolRequest) -> Result<ProtocolResponse> + Send + Sync + 'static,
+    {
+        self.register_protocol_handler(name, ProtocolHandler::Threaded(Arc::new(handler)))
+    }
+
+    pub(crate) fn register_protocol_handler(
+        mut self,
+        name: String,
+        handler: ProtocolHandler,
+    ) -> Self {
+        self.custom_protocols.push((name, handler));
+        self
+    }
+
+    /// Whether to define `window.external` and listen to its messages, which the RPC handler
+    /// relies on. This is enabled by default; disable it for pages that define their own
+    /// `window.external`, the RPC handler is then never called.
+    pub fn inject_rpc_bridge(mut self, inject: bool) -> Self {
+        self.attributes.rpc_bridge_disabled = !inject;
+        self
+    }
+
+    /// Encode the RPC messages with `serializer` instead of JSON.
+    pub fn rpc_serializer(mut self, serializer: Rc<dyn RpcSerializer>) -> Self {
+        self.attributes.rpc_serializer = Some(serializer);
         self
     }
 
@@ -127,6 +916,8 @@ impl WebViewBuilder {
                 function Rpc() {
                     const self = this;
                     this._promises = {};
+                    this._encode = JSON.stringify;
+                    this._decode = JSON.parse;
 
                     // Private internal function called on error
                     this._error = (id, error) => {
@@ -152,7 +943,7 @@ impl WebViewBuilder {
                         const promise = new Promise((resolve, reject) => {
                             self._promises[id] = {resolve, reject};
                         });
-                        window.external.invoke(JSON.stringify(payload));
+                        window.external.invoke(self._encode(payload));
                         return promise;
                     }
 
@@ -160,7 +951,7 @@ impl WebViewBuilder {
                     this.notify = function(method) {
                         const params = Array.prototype.slice.call(arguments, 1);
                         const payload = {jsonrpc: "2.0", method, params};
-                        window.external.invoke(JSON.stringify(payload));
+                        window.external.invoke(self._encode(payload));
                         return Promise.resolve();
                     }
                 }
@@ -175,6 +966,14 @@ impl WebViewBuilder {
         self
     }
 
+    /// Use the provided [`WebContext`] instead of creating a new one for this WebView. WebViews
+    /// sharing a context also share its web processes, cache and custom protocols.
+    #[cfg(target_os = "linux")]
+    pub fn web_context(mut self, context: WebContext) -> Self {
+        self.web_context = Some(context);
+        self
+    }
+
     /// Load the provided URL when the builder calling [`WebViewBuilder::build`] to create the
     /// [`WebView`]. The provided URL must be valid.
     pub fn load_url(mut self, url: &str) -> Result<Self> {
@@ -183,20 +982,44 @@ impl WebViewBuilder {
     }
 
     /// Consume the builder and create the [`WebView`].
-    pub fn build(self) -> Result<WebView> {
+    pub fn build(mut self) -> Result<WebView> {
+        // The RPC script defines the JSON functions, override them after it.
+        if let Some(serializer) = &self.attributes.rpc_serializer {
+            self.initialization_scripts.push(format!(
+                "if (window.external && window.external.rpc) {{ window.external.rpc._encode = {}; window.external.rpc._decode = {}; }}",
+                serializer.js_encoder(),
+                serializer.js_decoder()
+            ));
+        }
+        if self.attributes.overscroll_disabled {
+            self.initialization_scripts
+                .push(NO_OVERSCROLL_SCRIPT.to_string());
+        }
+        for (i, (name, _)) in self.custom_protocols.iter().enumerate() {
+            if self.custom_protocols[..i]
+                .iter()
+                .any(|(other, _)| other == name)
+            {
+                return Err(Error::DuplicateCustomProtocol(name.clone()));
+            }
+        }
+        let load_waiters = load_waiters(&mut self.attributes);
         let webview = InnerWebView::new(
             &self.window,
             self.initialization_scripts,
             self.url,
-            self.transparent,
-            self.custom_protocol,
+            self.attributes,
+            self.custom_protocols,
             self.rpc_handler,
+            #[cfg(target_os = "linux")]
+            self.web_context,
         )?;
         Ok(WebView {
             window: self.window,
             webview,
             tx: self.tx,
             rx: self.rx,
+            load_waiters,
         })
     }
 }
@@ -212,6 +1035,7 @@ pub struct WebView {
     webview: InnerWebView,
     tx: Sender<String>,
     rx: Receiver<String>,
+    load_waiters: LoadWaiters,
 }
 
 impl WebView {
@@ -227,14 +1051,28 @@ impl WebView {
     /// many more before starting WebView. To benefit from above features, create a
     /// [`WebViewBuilder`] instead.
     pub fn new_with_configs(window: Window, transparent: bool) -> Result<Self> {
-        let picky_none: Option<(String, Box<dyn Fn(&str) -> Result<Vec<u8>>>)> = None;
-        let webview = InnerWebView::new(&window, vec![], None, transparent, picky_none, None)?;
+        let mut attributes = WebViewAttributes {
+            transparent,
+            ..Default::default()
+        };
+        let load_waiters = load_waiters(&mut attributes);
+        let webview = InnerWebView::new(
+            &window,
+            vec![],
+            None,
+            attributes,
+            vec![],
+            None,
+            #[cfg(target_os = "linux")]
+            None,
+        )?;
         let (tx, rx) = channel();
         Ok(Self {
             window,
             webview,
             tx,
             rx,
+            load_waiters,
         })
     }
     /// Dispatch javascript code to be evaluated later. Note this will not actually run the
@@ -257,6 +1095,11 @@ impl WebView {
         &self.window
     }
 
+    /// Call `f` once the page being loaded, or the next one, finished loading.
+    pub(crate) fn on_next_load<F: FnOnce() + 'static>(&self, f: F) {
+        self.load_waiters.borrow_mut().push(Box::new(f));
+    }
+
     /// Evaluate the scripts sent from [`Dispatcher`]s.
     pub fn evaluate_script(&self) -> Result<()> {
         while let Ok(js) = self.rx.try_recv() {
@@ -266,6 +1109,347 @@ impl WebView {
         Ok(())
     }
 
+    /// Evaluate `js` right away and call `callback` with its result, i.e. the value of its last
+    /// statement, once it completed. Values that can't be represented in JSON become `null`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Unsupported, the callback always receives `null`.
+    pub fn evaluate_script_with_callback<F>(&self, js: &str, callback: F) -> Result<()>
+    where
+        F: FnOnce(Result<Value>) + 'static,
+    {
+        self.webview.eval_with_callback(js, Box::new(callback))
+    }
+
+    /// Navigate to the provided URL. A navigation still in progress is cancelled first, so
+    /// only the latest URL ends up being loaded. The provided URL must be valid.
+    pub fn load_url(&self, url: &str) -> Result<()> {
+        self.webview.load_url(Url::parse(url)?)
+    }
+
+    /// Display `bytes` as content of the given MIME type, e.g. `application/pdf` or
+    /// `text/html; charset=utf-8`. Relative URLs in the content are resolved against `base_url`.
+    ///
+    /// Returns [`Error::UnsupportedMimeType`] if the WebView can't display the MIME type.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Only HTML content is supported and `base_url` is ignored.
+    /// - **macOS:** Unsupported MIME types aren't detected, the content fails to display instead.
+    pub fn load_bytes(&self, bytes: &[u8], mime_type: &str, base_url: Option<&str>) -> Result<()> {
+        let base_url = base_url.map(Url::parse).transpose()?;
+        self.webview.load_bytes(bytes, mime_type, base_url)
+    }
+
+    /// Display the `html` string. Relative URLs in it, including the ones of custom protocols,
+    /// are resolved against `base_url`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** `base_url` is ignored.
+    pub fn load_html(&self, html: &str, base_url: Option<&str>) -> Result<()> {
+        let base_url = base_url.map(Url::parse).transpose()?;
+        self.webview.load_html(html, base_url)
+    }
+
+    /// Reload the current page. With `bypass_cache`, resources are loaded again instead of taken
+    /// from the cache, including the ones of custom protocols.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** `bypass_cache` is unsupported, resources may still come from the cache.
+    pub fn reload(&self, bypass_cache: bool) -> Result<()> {
+        self.webview.reload(bypass_cache)
+    }
+
+    /// Set the user agent the WebView identifies itself with, from the next request on.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported.
+    pub fn set_user_agent(&self, user_agent: &str) -> Result<()> {
+        self.webview.set_user_agent(user_agent)
+    }
+
+    /// Replace the cursor over the WebView with a custom image, given as RGBA pixels row by row,
+    /// until [`WebView::reset_cursor`] is called. The hotspot is the pixel of the image pointing
+    /// at the cursor position.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn set_cursor_image(
+        &self,
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    ) -> Result<()> {
+        self.webview
+            .set_cursor_image(rgba, width, height, hotspot_x, hotspot_y)
+    }
+
+    /// Give the cursor back to the page after [`WebView::set_cursor_image`].
+    pub fn reset_cursor(&self) -> Result<()> {
+        self.webview.reset_cursor()
+    }
+
+    /// Reload the current page with another user agent, e.g. to request the desktop version of
+    /// a site. With `restore`, the previous user agent is used again once the page is reloaded,
+    /// otherwise the new one is kept for the next navigations.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported, the page is reloaded with the same user agent.
+    /// - **macOS:** `restore` is unsupported, the new user agent is kept.
+    pub fn reload_with_user_agent(&self, user_agent: &str, restore: bool) -> Result<()> {
+        self.webview.reload_with_user_agent(user_agent, restore)
+    }
+
+    /// Set the zoom level of the page, where `1.0` is 100%. It's clamped between `0.25` and
+    /// `5.0`.
+    pub fn set_zoom_level(&self, zoom: f64) -> Result<()> {
+        self.webview.set_zoom_level(clamp_zoom(zoom))
+    }
+
+    /// Returns the zoom level of the page, where `1.0` is 100%.
+    pub fn zoom_level(&self) -> f64 {
+        self.webview.zoom_level()
+    }
+
+    /// Navigate to the previous page of the history, if any.
+    pub fn go_back(&self) -> Result<()> {
+        self.webview.go_back()
+    }
+
+    /// Navigate to the next page of the history, if any.
+    pub fn go_forward(&self) -> Result<()> {
+        self.webview.go_forward()
+    }
+
+    /// Returns whether there's a previous page in the history to go back to.
+    pub fn can_go_back(&self) -> bool {
+        self.webview.can_go_back()
+    }
+
+    /// Returns whether there's a next page in the history to go forward to.
+    pub fn can_go_forward(&self) -> bool {
+        self.webview.can_go_forward()
+    }
+
+    /// Returns the URL of the current page, after redirects and client-side navigation, or `None`
+    /// if nothing has been loaded yet.
+    pub fn url(&self) -> Option<String> {
+        self.webview.url()
+    }
+
+    /// Returns the title of the current page, or `None` if it hasn't loaded yet.
+    pub fn title(&self) -> Option<String> {
+        self.webview.title()
+    }
+
+    /// Set whether the whole document can be edited by the user.
+    pub fn set_editable(&self, editable: bool) -> Result<()> {
+        self.webview.set_editable(editable)
+    }
+
+    /// Set whether pressing the elements styled with `-webkit-app-region: drag` moves the window,
+    /// like the title bar would, e.g. for windows without decorations. Elements styled with
+    /// `-webkit-app-region: no-drag` inside them, like buttons, can still be clicked.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** WebKitGTK drops the property from stylesheets, it only works in the `style`
+    ///   attribute of the elements.
+    /// - **Windows / macOS:** Unsupported.
+    pub fn set_enable_drag_region(&self, enabled: bool) -> Result<()> {
+        self.webview.set_enable_drag_region(enabled)
+    }
+
+    /// Highlight the occurrences of `text` in the page and select the first one, wrapping around
+    /// at the end. The matches are reported to [`WebViewBuilder::find_result_handler`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn find(&self, text: &str, case_sensitive: bool) -> Result<()> {
+        self.webview.find(text, case_sensitive)
+    }
+
+    /// Select the next match of [`WebView::find`].
+    pub fn find_next(&self) -> Result<()> {
+        self.webview.find_next()
+    }
+
+    /// Select the previous match of [`WebView::find`].
+    pub fn find_previous(&self) -> Result<()> {
+        self.webview.find_previous()
+    }
+
+    /// Remove the highlights of [`WebView::find`].
+    pub fn stop_find(&self) -> Result<()> {
+        self.webview.stop_find()
+    }
+
+    /// Set whether the user can select text in the current page and copy it. This only applies to
+    /// the current page, it has to be set again after navigating.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** Making the page unselectable also stops JavaScript from accessing the
+    ///   clipboard, for every page.
+    pub fn set_selectable(&self, selectable: bool) -> Result<()> {
+        self.webview.set_selectable(selectable)
+    }
+
+    /// Returns the back-forward history of the WebView.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported, always returns an empty list.
+    pub fn back_forward_list(&self) -> BackForwardList {
+        self.webview.back_forward_list()
+    }
+
+    /// Clears the back-forward history of the WebView, except for the current page.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Linux:** The current page is loaded again, losing its state like the scroll position.
+    /// - **Windows:** Unsupported, returns [`Error::Unsupported`]. The web engine has no way to
+    ///   clear it.
+    pub fn clear_history(&self) -> Result<()> {
+        self.webview.clear_history()
+    }
+
+    /// Returns the navigation session of the WebView, including its back-forward history and
+    /// scroll positions, as an opaque blob that can be stored and given back to
+    /// [`WebView::restore_session_state`] later on.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported, always returns `None`.
+    /// - **macOS:** Requires macOS 12, returns `None` on older versions.
+    pub fn session_state(&self) -> Option<Vec<u8>> {
+        self.webview.session_state()
+    }
+
+    /// Restores a navigation session returned by [`WebView::session_state`], reloading its
+    /// current page. The state must come from the same platform and web engine version.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported.
+    /// - **macOS:** Requires macOS 12, does nothing on older versions.
+    pub fn restore_session_state(&self, state: &[u8]) -> Result<()> {
+        self.webview.restore_session_state(state)
+    }
+
+    /// Calls `callback` with the cookies that would be sent to `url`, once the web engine read
+    /// them.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported, the callback always receives no cookies.
+    pub fn cookies<F: FnOnce(Vec<Cookie>) + 'static>(&self, url: &str, callback: F) -> Result<()> {
+        self.webview.cookies(url, Box::new(callback))
+    }
+
+    /// Stores `cookie`, replacing the one with the same name, domain and path if any.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn set_cookie(&self, cookie: &Cookie) -> Result<()> {
+        self.webview.set_cookie(cookie)
+    }
+
+    /// Deletes every cookie stored by the web engine, including the ones of other WebViews
+    /// sharing its storage.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported.
+    pub fn clear_all_cookies(&self) -> Result<()> {
+        self.webview.clear_all_cookies()
+    }
+
+    /// Clears the cache of the resources loaded by the pages, so they're loaded again from their
+    /// source, e.g. the assets of an app served through a custom protocol.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Unsupported.
+    pub fn clear_cache(&self) -> Result<()> {
+        self.webview.clear_cache()
+    }
+
+    /// Deletes the website data of `types`, for every website, including the data of other
+    /// WebViews sharing its storage.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** `localStorage`, `sessionStorage` and IndexedDB are only cleared for the
+    ///   origin of the current page.
+    /// - **macOS:** Unsupported.
+    pub fn clear_storage(&self, types: StorageTypes) -> Result<()> {
+        self.webview.clear_storage(types)
+    }
+
+    /// Returns whether the developer tools are currently open.
+    pub fn is_devtools_open(&self) -> bool {
+        self.webview.is_devtools_open()
+    }
+
+    /// Open the developer tools, if they're enabled with [`WebViewBuilder::devtools`] or in a
+    /// debug build.
+    pub fn open_devtools(&self) -> Result<()> {
+        self.webview.open_devtools(None)
+    }
+
+    /// Open the developer tools like [`WebView::open_devtools`], attached or detached regardless
+    /// of [`WebViewBuilder::devtools_attachment`]. If they're already open, they're moved.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The developer tools are always detached.
+    pub fn open_devtools_with(&self, attachment: DevtoolsAttachment) -> Result<()> {
+        self.webview.open_devtools(Some(attachment))
+    }
+
+    /// Close the developer tools.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Unsupported.
+    pub fn close_devtools(&self) -> Result<()> {
+        self.webview.close_devtools()
+    }
+
+    /// Returns the height of the developer tools when they're attached to the window, or `None`
+    /// if they're closed or detached.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** Unsupported, always returns `None`.
+    pub fn devtools_attached_height(&self) -> Option<u32> {
+        self.webview.devtools_attached_height()
+    }
+
+    /// Moves the keyboard focus to the WebView.
+    pub fn focus(&self) -> Result<()> {
+        self.webview.focus()
+    }
+
+    /// Add a native widget to the window, on the given side of the WebView. Widgets added later
+    /// on the same side are placed closer to the WebView.
+    #[cfg(target_os = "linux")]
+    pub fn add_widget<W: IsA<Widget>>(&self, widget: &W, side: PositionType) {
+        self.webview.add_widget(widget, side)
+    }
+
     /// Resize the WebView manually. This is required on Windows because its WebView API doesn't
     /// provide a way to resize automatically.
     pub fn resize(&self) -> Result<()> {
@@ -291,23 +1475,240 @@ impl Dispatcher {
     }
 }
 
+/// The back-forward history of a [`WebView`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BackForwardList {
+    /// The pages before the current one, starting with the previous page.
+    pub back: Vec<HistoryItem>,
+    /// The page currently shown, if any.
+    pub current: Option<HistoryItem>,
+    /// The pages after the current one, starting with the next page.
+    pub forward: Vec<HistoryItem>,
+}
+
+/// A page of the back-forward history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryItem {
+    pub url: String,
+    pub title: Option<String>,
+}
+
+/// A cookie stored by the web engine, see [`WebView::cookies`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    /// The domain the cookie is sent to, a leading `.` also matches its subdomains.
+    pub domain: String,
+    pub path: String,
+    /// Whether the cookie is only sent over HTTPS.
+    pub secure: bool,
+    /// Whether the cookie is hidden from `document.cookie`.
+    pub http_only: bool,
+    /// When the cookie expires, `None` for a session cookie.
+    pub expiry: Option<SystemTime>,
+}
+
+/// The kinds of website data deleted by [`WebView::clear_storage`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StorageTypes {
+    /// The memory and disk caches of the resources loaded by the pages.
+    pub cache: bool,
+    /// `localStorage` and `sessionStorage`.
+    pub local_storage: bool,
+    pub indexed_db: bool,
+    pub cookies: bool,
+}
+
+impl StorageTypes {
+    /// Every kind of website data, e.g. to reset an app to its first run.
+    pub fn all() -> Self {
+        StorageTypes {
+            cache: true,
+            local_storage: true,
+            indexed_db: true,
+            cookies: true,
+        }
+    }
+}
+
+type PageLoadHandler = Box<dyn Fn(&str, LoadState)>;
+
+type ContextMenuItemHandler = Rc<dyn Fn(&str)>;
+
+/// Settings of the web engine collected by [`WebViewBuilder`].
+#[derive(Default)]
+pub(crate) struct WebViewAttributes {
+    pub focus_disabled: bool,
+    pub transparent: bool,
+    pub background_color: Option<(u8, u8, u8, u8)>,
+    pub html_title_as_window_title: bool,
+    pub resize_border: Option<u32>,
+    pub back_forward_navigation_gestures: bool,
+    pub overscroll_disabled: bool,
+    pub auto_dpi_zoom: bool,
+    pub zoom_level: Option<f64>,
+    pub device_scale_factor: Option<f64>,
+    pub allow_file_access_from_file_urls: bool,
+    pub allow_universal_access_from_file_urls: bool,
+    pub custom_protocol_timeout: Option<Duration>,
+    pub custom_protocol_concurrency: Option<usize>,
+    pub custom_protocol_cors: bool,
+    pub custom_protocol_local: bool,
+    pub accept_languages: Option<Vec<String>>,
+    pub user_agent: Option<String>,
+    pub devtools: bool,
+    pub devtools_attachment: DevtoolsAttachment,
+    pub error_page: Option<String>,
+    pub rpc_bridge_disabled: bool,
+    pub rpc_serializer: Option<Rc<dyn RpcSerializer>>,
+    pub before_unload_handler: Option<BeforeUnloadHandler>,
+    pub page_load_handler: Option<PageLoadHandler>,
+    pub load_finished_handler: Option<Box<dyn Fn()>>,
+    pub devtools_handler: Option<Rc<dyn Fn(bool)>>,
+    pub editor_state_handler: Option<Box<dyn Fn(EditorState)>>,
+    pub find_result_handler: Option<Box<dyn Fn(u32, u32)>>,
+    pub paste_handler: Option<PasteHandler>,
+    pub context_menu_handler: Option<ContextMenuHandler>,
+    pub context_menu_item_handler: Option<ContextMenuItemHandler>,
+    pub script_dialog_handler: Option<ScriptDialogHandler>,
+}
+
 pub(crate) trait WV: Sized {
     type Window;
 
-    fn new<F: 'static + Fn(&str) -> Result<Vec<u8>>>(
+    fn new(
         window: &Self::Window,
         scripts: Vec<String>,
         url: Option<Url>,
-        transparent: bool,
-        custom_protocol: Option<(String, F)>,
+        attributes: WebViewAttributes,
+        custom_protocols: Vec<(String, ProtocolHandler)>,
         rpc_handler: Option<RpcHandler>,
+        #[cfg(target_os = "linux")] web_context: Option<WebContext>,
     ) -> Result<Self>;
 
     fn eval(&self, js: &str) -> Result<()>;
+
+    fn eval_with_callback(&self, js: &str, callback: Box<dyn FnOnce(Result<Value>)>) -> Result<()>;
+
+    fn load_url(&self, url: Url) -> Result<()>;
+
+    fn reload(&self, bypass_cache: bool) -> Result<()>;
+
+    fn set_user_agent(&self, user_agent: &str) -> Result<()>;
+
+    fn set_cursor_image(
+        &self,
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    ) -> Result<()>;
+
+    fn reset_cursor(&self) -> Result<()>;
+
+    fn reload_with_user_agent(&self, user_agent: &str, restore: bool) -> Result<()>;
+
+    fn set_zoom_level(&self, zoom: f64) -> Result<()>;
+
+    fn zoom_level(&self) -> f64;
+
+    fn go_back(&self) -> Result<()>;
+
+    fn go_forward(&self) -> Result<()>;
+
+    fn can_go_back(&self) -> bool;
+
+    fn can_go_forward(&self) -> bool;
+
+    fn url(&self) -> Option<String>;
+
+    fn title(&self) -> Option<String>;
+
+    fn load_bytes(&self, bytes: &[u8], mime_type: &str, base_url: Option<Url>) -> Result<()>;
+
+    fn load_html(&self, html: &str, base_url: Option<Url>) -> Result<()>;
+
+    fn set_editable(&self, editable: bool) -> Result<()>;
+
+    fn set_enable_drag_region(&self, enabled: bool) -> Result<()>;
+
+    fn find(&self, text: &str, case_sensitive: bool) -> Result<()>;
+
+    fn find_next(&self) -> Result<()>;
+
+    fn find_previous(&self) -> Result<()>;
+
+    fn stop_find(&self) -> Result<()>;
+
+    fn set_selectable(&self, selectable: bool) -> Result<()>;
+
+    fn back_forward_list(&self) -> BackForwardList;
+
+    fn clear_history(&self) -> Result<()>;
+
+    fn session_state(&self) -> Option<Vec<u8>>;
+
+    fn restore_session_state(&self, state: &[u8]) -> Result<()>;
+
+    fn cookies(&self, url: &str, callback: Box<dyn FnOnce(Vec<Cookie>)>) -> Result<()>;
+
+    fn set_cookie(&self, cookie: &Cookie) -> Result<()>;
+
+    fn clear_all_cookies(&self) -> Result<()>;
+
+    fn clear_cache(&self) -> Result<()>;
+
+    fn clear_storage(&self, types: StorageTypes) -> Result<()>;
+
+    fn is_devtools_open(&self) -> bool;
+
+    fn open_devtools(&self, attachment: Option<DevtoolsAttachment>) -> Result<()>;
+
+    fn close_devtools(&self) -> Result<()>;
+
+    fn devtools_attached_height(&self) -> Option<u32>;
+
+    fn focus(&self) -> Result<()>;
 }
 
 const RPC_VERSION: &str = "2.0";
 
+/// Stops the user from selecting text and copying or cutting it from the current page.
+const UNSELECTABLE_SCRIPT: &str = r#"
+    (function() {
+        if (document.getElementById('__wry_unselectable')) return;
+        const style = document.createElement('style');
+        style.id = '__wry_unselectable';
+        style.textContent = '* { -webkit-user-select: none !important; user-select: none !important; }';
+        (document.head || document.documentElement).appendChild(style);
+        window.__wry_prevent_copy = function(e) { e.preventDefault(); };
+        document.addEventListener('copy', window.__wry_prevent_copy, true);
+        document.addEventListener('cut', window.__wry_prevent_copy, true);
+    })();
+"#;
+
+/// Stops the page from bouncing when scrolled past its edges.
+const NO_OVERSCROLL_SCRIPT: &str = r#"
+    (function() {
+        const style = document.createElement('style');
+        style.textContent = 'html, body { overscroll-behavior: none; }';
+        (document.head || document.documentElement).appendChild(style);
+    })();
+"#;
+
+/// Undoes [`UNSELECTABLE_SCRIPT`].
+const SELECTABLE_SCRIPT: &str = r#"
+    (function() {
+        const style = document.getElementById('__wry_unselectable');
+        if (!style) return;
+        style.remove();
+        document.removeEventListener('copy', window.__wry_prevent_copy, true);
+        document.removeEventListener('cut', window.__wry_prevent_copy, true);
+    })();
+"#;
+
 /// RPC request message.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RpcRequest {
@@ -350,20 +1751,12 @@ impl RpcResponse {
     /// Get a script that resolves the promise with a result.
     pub fn into_result_script(id: Value, result: Value) -> Result<String> {
         let retval = serde_json::to_string(&result)?;
-        Ok(format!(
-            "window.external.rpc._result({}, {})",
-            id.to_string(),
-            retval
-        ))
+        Ok(format!("window.external.rpc._result({}, {})", id, retval))
     }
 
     /// Get a script that rejects the promise with an error.
     pub fn into_error_script(id: Value, result: Value) -> Result<String> {
         let retval = serde_json::to_string(&result)?;
-        Ok(format!(
-            "window.external.rpc._error({}, {})",
-            id.to_string(),
-            retval
-        ))
+        Ok(format!("window.external.rpc._error({}, {})", id, retval))
     }
 }
@@ -1,35 +1,54 @@
 use crate::mimetype::MimeType;
-use crate::webview::WV;
-use crate::{Result, RpcHandler};
+use crate::webview::{
+    BackForwardList, Cookie, DevtoolsAttachment, LoadState, ProtocolHandler, ProtocolRequest,
+    ProtocolResponse, StorageTypes, WebViewAttributes, SELECTABLE_SCRIPT, UNSELECTABLE_SCRIPT, WV,
+};
+use crate::{Error, Result, RpcHandler, Value};
 
-use std::{os::raw::c_void, rc::Rc};
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    io::Read,
+    iter::once,
+    os::{raw::c_void, windows::ffi::OsStrExt},
+    rc::Rc,
+};
 
 use once_cell::unsync::OnceCell;
 use url::Url;
-use webview2::{Controller, PermissionKind, PermissionState};
-use winapi::{shared::windef::HWND, um::winuser::GetClientRect};
+use webview2::{Controller, MoveFocusReason, PermissionKind, PermissionState};
+use winapi::{
+    shared::windef::HWND,
+    um::winuser::{GetClientRect, SetWindowTextW},
+};
 use winit::{platform::windows::WindowExtWindows, window::Window};
 
 pub struct InnerWebView {
     controller: Rc<OnceCell<Controller>>,
+    custom_protocol_names: Vec<String>,
 }
 
 impl WV for InnerWebView {
     type Window = Window;
 
-    fn new<F: 'static + Fn(&str) -> Result<Vec<u8>>>(
+    fn new(
         window: &Window,
         scripts: Vec<String>,
         url: Option<Url>,
         // TODO default background color option just adds to webview2 recently and it requires
-        // canary build. Implement this once it's in official release.
-        transparent: bool,
-        custom_protocol: Option<(String, F)>,
+        // canary build. Implement transparency once it's in official release.
+        attributes: WebViewAttributes,
+        custom_protocols: Vec<(String, ProtocolHandler)>,
         rpc_handler: Option<RpcHandler>,
     ) -> Result<Self> {
         let controller: Rc<OnceCell<Controller>> = Rc::new(OnceCell::new());
         let hwnd = window.hwnd() as HWND;
         let controller_clone = controller.clone();
+        let custom_protocol_names: Vec<String> = custom_protocols
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect();
+        let custom_protocol_names_ = custom_protocol_names.clone();
 
         // Webview controller
         webview2::EnvironmentBuilder::new().build(move |env| {
@@ -44,7 +63,7 @@ impl WV for InnerWebView {
                 settings.put_is_status_bar_enabled(false)?;
                 settings.put_are_default_context_menus_enabled(true)?;
                 settings.put_is_zoom_control_enabled(false)?;
-                settings.put_are_dev_tools_enabled(false)?;
+                settings.put_are_dev_tools_enabled(attributes.devtools)?;
                 debug_assert_eq!(settings.put_are_dev_tools_enabled(true)?, ());
 
                 // Safety: System calls are unsafe
@@ -53,60 +72,117 @@ impl WV for InnerWebView {
                     GetClientRect(hwnd, &mut rect);
                     controller.put_bounds(rect)?;
                 }
+                if let Some(zoom) = attributes.zoom_level {
+                    controller.put_zoom_factor(zoom)?;
+                }
 
                 // Initialize scripts
-                w.add_script_to_execute_on_document_created(
-                    "window.external={invoke:s=>window.chrome.webview.postMessage(s)}",
-                    |_| (Ok(())),
-                )?;
+                if !attributes.rpc_bridge_disabled {
+                    w.add_script_to_execute_on_document_created(
+                        "window.external={invoke:s=>window.chrome.webview.postMessage(s)}",
+                        |_| (Ok(())),
+                    )?;
+                }
                 for js in scripts {
                     w.add_script_to_execute_on_document_created(&js, |_| (Ok(())))?;
                 }
 
                 // Message handler
-                w.add_web_message_received(move |webview, args| {
-                    let js = args.try_get_web_message_as_string()?;
-                    if let Some(rpc_handler) = rpc_handler.as_ref() {
-                        match super::rpc_proxy(js, rpc_handler) {
-                            Ok(result) => {
-                                if let Some(ref script) = result {
-                                    webview.execute_script(script, |_| (Ok(())))?;
+                if !attributes.rpc_bridge_disabled {
+                    let serializer = attributes.rpc_serializer.clone();
+                    w.add_web_message_received(move |webview, args| {
+                        let js = args.try_get_web_message_as_string()?;
+                        if let Some(rpc_handler) = rpc_handler.as_ref() {
+                            match super::rpc_proxy(js, rpc_handler, serializer.as_deref()) {
+                                Ok(result) => {
+                                    if let Some(ref script) = result {
+                                        webview.execute_script(script, |_| (Ok(())))?;
+                                    }
+                                }
+                                Err(e) => {
+                                    eprintln!("{}", e);
                                 }
-                            }
-                            Err(e) => {
-                                eprintln!("{}", e);
                             }
                         }
-                    }
-                    Ok(())
-                })?;
+                        Ok(())
+                    })?;
+                }
 
-                let mut custom_protocol_name = None;
-                if let Some((name, function)) = custom_protocol {
+                if !custom_protocols.is_empty() {
                     // WebView2 doesn't support non-standard protocols yet, so we have to use this workaround
                     // See https://github.com/MicrosoftEdge/WebView2Feedback/issues/73
-                    custom_protocol_name = Some(name.clone());
-                    w.add_web_resource_requested_filter(
-                        &format!("file://custom-protocol-{}*", name),
-                        webview2::WebResourceContext::All,
-                    )?;
+                    for (name, _) in &custom_protocols {
+                        w.add_web_resource_requested_filter(
+                            &format!("file://custom-protocol-{}*", name),
+                            webview2::WebResourceContext::All,
+                        )?;
+                    }
                     w.add_web_resource_requested(move |_, args| {
-                        let uri = args.get_request()?.get_uri()?;
+                        let request = args.get_request()?;
+                        let uri = request.get_uri()?;
+                        // The requests of every protocol come here, the longest matching name wins
+                        // since the workaround doesn't keep a separator after it.
+                        let protocol = custom_protocols
+                            .iter()
+                            .filter(|(name, _)| {
+                                uri.starts_with(&format!("file://custom-protocol-{}", name))
+                            })
+                            .max_by_key(|(name, _)| name.len());
+                        let (name, function) = match protocol {
+                            Some(protocol) => protocol,
+                            None => return Ok(()),
+                        };
                         // Undo the protocol workaround when giving path to resolver
-                        let path = &uri.replace(
+                        let path = uri.replace(
                             &format!("file://custom-protocol-{}", name),
                             &format!("{}://", name),
                         );
+                        let mut headers = HashMap::new();
+                        for (name, value) in request.get_headers()?.get_iterator()? {
+                            headers
+                                .entry(name.to_lowercase())
+                                .and_modify(|values: &mut String| {
+                                    values.push_str(", ");
+                                    values.push_str(&value);
+                                })
+                                .or_insert(value);
+                        }
+                        let mut body = Vec::new();
+                        if let Ok(mut content) = request.get_content() {
+                            let _ = content.read_to_end(&mut body);
+                        }
+                        let protocol_request = ProtocolRequest {
+                            uri: path,
+                            method: request.get_method()?,
+                            headers,
+                            body,
+                        };
 
-                        match function(path) {
-                            Ok(content) => {
-                                let mime = MimeType::parse(&content, &uri);
-                                let stream = webview2::Stream::from_bytes(&content);
+                        match function.call(&protocol_request) {
+                            Ok(ProtocolResponse {
+                                mut body,
+                                reader,
+                                mimetype,
+                                status,
+                                headers,
+                                ..
+                            }) => {
+                                // WebView2 only takes streams of memory.
+                                if let Some(mut reader) = reader {
+                                    body.clear();
+                                    reader.read_to_end(&mut body)?;
+                                }
+                                let mime = mimetype.unwrap_or_else(|| MimeType::parse(&body, &uri));
+                                let headers = headers
+                                    .iter()
+                                    .map(|(name, value)| format!("\r\n{}: {}", name, value))
+                                    .collect::<String>();
+                                let stream = webview2::Stream::from_bytes(&body);
                                 let response = env_.create_web_resource_response(
                                     stream,
-                                    200,
-                                    "OK",
-                                    &format!("Content-Type: {}", mime),
+                                    status as i32,
+                                    if status == 200 { "OK" } else { "" },
+                                    &format!("Content-Type: {}{}", mime, headers),
                                 )?;
                                 args.put_response(response)?;
                                 Ok(())
@@ -119,6 +195,43 @@ impl WV for InnerWebView {
                     })?;
                 }
 
+                // Window title
+                if attributes.html_title_as_window_title {
+                    w.add_document_title_changed(move |w| {
+                        let title: Vec<u16> = OsStr::new(&w.get_document_title()?)
+                            .encode_wide()
+                            .chain(once(0))
+                            .collect();
+                        // Safety: System calls are unsafe
+                        unsafe { SetWindowTextW(hwnd, title.as_ptr()) };
+                        Ok(())
+                    })?;
+                }
+
+                // Page load
+                if let Some(handler) = attributes.page_load_handler {
+                    let handler = Rc::new(handler);
+                    let handler_ = handler.clone();
+                    w.add_navigation_starting(move |_, args| {
+                        handler_(&args.get_uri()?, LoadState::Started);
+                        Ok(())
+                    })?;
+                    let handler_ = handler.clone();
+                    w.add_content_loading(move |w, _| {
+                        handler_(&w.get_source()?, LoadState::Committed);
+                        Ok(())
+                    })?;
+                    w.add_navigation_completed(move |w, args| {
+                        let state = if args.get_is_success()? {
+                            LoadState::Finished
+                        } else {
+                            LoadState::Failed
+                        };
+                        handler(&w.get_source()?, state);
+                        Ok(())
+                    })?;
+                }
+
                 // Enable clipboard
                 w.add_permission_requested(|_, args| {
                     let kind = args.get_permission_kind()?;
@@ -130,22 +243,7 @@ impl WV for InnerWebView {
 
                 // Navigation
                 if let Some(url) = url {
-                    if url.cannot_be_a_base() {
-                        w.navigate_to_string(url.as_str())?;
-                    } else {
-                        let mut url_string = String::from(url.as_str());
-                        if let Some(name) = custom_protocol_name {
-                            if name == url.scheme() {
-                                // WebView2 doesn't support non-standard protocols yet, so we have to use this workaround
-                                // See https://github.com/MicrosoftEdge/WebView2Feedback/issues/73
-                                url_string = url.as_str().replace(
-                                    &format!("{}://", name),
-                                    &format!("file://custom-protocol-{}", name),
-                                )
-                            }
-                        }
-                        w.navigate(&url_string)?;
-                    }
+                    navigate(&w, &url, &custom_protocol_names_)?;
                 }
 
                 let _ = controller_clone.set(controller);
@@ -153,7 +251,10 @@ impl WV for InnerWebView {
             })
         })?;
 
-        Ok(Self { controller })
+        Ok(Self {
+            controller,
+            custom_protocol_names,
+        })
     }
 
     fn eval(&self, js: &str) -> Result<()> {
@@ -163,6 +264,283 @@ impl WV for InnerWebView {
         }
         Ok(())
     }
+
+    fn eval_with_callback(&self, js: &str, callback: Box<dyn FnOnce(Result<Value>)>) -> Result<()> {
+        if let Some(c) = self.controller.get() {
+            let webview = c.get_webview()?;
+            // WebView2 already passes the result as JSON.
+            webview.execute_script(js, move |result| {
+                callback(serde_json::from_str(&result).map_err(Into::into));
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+
+    fn load_url(&self, url: Url) -> Result<()> {
+        if let Some(c) = self.controller.get() {
+            let webview = c.get_webview()?;
+            webview.stop()?;
+            navigate(&webview, &url, &self.custom_protocol_names)?;
+        }
+        Ok(())
+    }
+
+    fn reload(&self, _bypass_cache: bool) -> Result<()> {
+        // WebView2 has no way to reload without the cache.
+        if let Some(c) = self.controller.get() {
+            c.get_webview()?.reload()?;
+        }
+        Ok(())
+    }
+
+    fn set_cursor_image(
+        &self,
+        _rgba: Vec<u8>,
+        _width: u32,
+        _height: u32,
+        _hotspot_x: u32,
+        _hotspot_y: u32,
+    ) -> Result<()> {
+        // winit and WebView2 have no custom cursors.
+        Ok(())
+    }
+
+    fn reset_cursor(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_user_agent(&self, _user_agent: &str) -> Result<()> {
+        // The user agent can't be changed with WebView2 yet.
+        Ok(())
+    }
+
+    fn reload_with_user_agent(&self, _user_agent: &str, _restore: bool) -> Result<()> {
+        // The user agent can't be changed with WebView2 yet, only reload.
+        self.reload(false)
+    }
+
+    fn set_zoom_level(&self, zoom: f64) -> Result<()> {
+        if let Some(c) = self.controller.get() {
+            c.put_zoom_factor(zoom)?;
+        }
+        Ok(())
+    }
+
+    fn zoom_level(&self) -> f64 {
+        self.controller
+            .get()
+            .and_then(|c| c.get_zoom_factor().ok())
+            .unwrap_or(1.0)
+    }
+
+    fn go_back(&self) -> Result<()> {
+        if let Some(c) = self.controller.get() {
+            let webview = c.get_webview()?;
+            if webview.get_can_go_back()? {
+                webview.go_back()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn go_forward(&self) -> Result<()> {
+        if let Some(c) = self.controller.get() {
+            let webview = c.get_webview()?;
+            if webview.get_can_go_forward()? {
+                webview.go_forward()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn can_go_back(&self) -> bool {
+        self.controller
+            .get()
+            .and_then(|c| c.get_webview().ok())
+            .and_then(|w| w.get_can_go_back().ok())
+            .unwrap_or(false)
+    }
+
+    fn can_go_forward(&self) -> bool {
+        self.controller
+            .get()
+            .and_then(|c| c.get_webview().ok())
+            .and_then(|w| w.get_can_go_forward().ok())
+            .unwrap_or(false)
+    }
+
+    fn url(&self) -> Option<String> {
+        self.controller
+            .get()
+            .and_then(|c| c.get_webview().ok())
+            .and_then(|w| w.get_source().ok())
+            .filter(|url| !url.is_empty())
+    }
+
+    fn title(&self) -> Option<String> {
+        self.controller
+            .get()
+            .and_then(|c| c.get_webview().ok())
+            .and_then(|w| w.get_document_title().ok())
+            .filter(|title| !title.is_empty())
+    }
+
+    fn load_bytes(&self, bytes: &[u8], mime_type: &str, _base_url: Option<Url>) -> Result<()> {
+        // WebView2 can only navigate to HTML strings, there's no way to hand it other content.
+        let (essence, _) = MimeType::split_charset(mime_type);
+        if !essence.eq_ignore_ascii_case("text/html") {
+            return Err(Error::UnsupportedMimeType(mime_type.to_string()));
+        }
+        if let Some(c) = self.controller.get() {
+            let webview = c.get_webview()?;
+            webview.stop()?;
+            webview.navigate_to_string(&String::from_utf8_lossy(bytes))?;
+        }
+        Ok(())
+    }
+
+    fn load_html(&self, html: &str, _base_url: Option<Url>) -> Result<()> {
+        if let Some(c) = self.controller.get() {
+            let webview = c.get_webview()?;
+            webview.stop()?;
+            webview.navigate_to_string(html)?;
+        }
+        Ok(())
+    }
+
+    fn set_editable(&self, editable: bool) -> Result<()> {
+        self.eval(if editable {
+            "document.designMode = 'on'"
+        } else {
+            "document.designMode = 'off'"
+        })
+    }
+
+    fn find(&self, _text: &str, _case_sensitive: bool) -> Result<()> {
+        // WebView2 has no find-in-page API to drive.
+        Ok(())
+    }
+
+    fn find_next(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn find_previous(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn stop_find(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_enable_drag_region(&self, _enabled: bool) -> Result<()> {
+        // The window can't be moved from a press in the page with WebView2.
+        Ok(())
+    }
+
+    fn set_selectable(&self, selectable: bool) -> Result<()> {
+        self.eval(if selectable {
+            SELECTABLE_SCRIPT
+        } else {
+            UNSELECTABLE_SCRIPT
+        })
+    }
+
+    fn back_forward_list(&self) -> BackForwardList {
+        // WebView2 only tells whether it can go back or forward.
+        BackForwardList::default()
+    }
+
+    fn clear_history(&self) -> Result<()> {
+        // WebView2 doesn't expose a way to clear the back-forward list.
+        Err(Error::Unsupported("Clearing the history"))
+    }
+
+    fn session_state(&self) -> Option<Vec<u8>> {
+        // WebView2 doesn't expose its navigation session.
+        None
+    }
+
+    fn restore_session_state(&self, _state: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    fn cookies(&self, _url: &str, callback: Box<dyn FnOnce(Vec<Cookie>)>) -> Result<()> {
+        // The cookie manager of WebView2 isn't part of the bindings.
+        callback(Vec::new());
+        Ok(())
+    }
+
+    fn set_cookie(&self, _cookie: &Cookie) -> Result<()> {
+        Ok(())
+    }
+
+    fn clear_all_cookies(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn clear_cache(&self) -> Result<()> {
+        self.call_devtools_method("Network.clearBrowserCache", "{}")
+    }
+
+    fn clear_storage(&self, types: StorageTypes) -> Result<()> {
+        if types.cache {
+            self.clear_cache()?;
+        }
+        if types.cookies {
+            self.call_devtools_method("Network.clearBrowserCookies", "{}")?;
+        }
+        // The DevTools protocol only clears the storage of one origin at a time.
+        let storage_types = [
+            (types.local_storage, "local_storage"),
+            (types.indexed_db, "indexeddb"),
+        ]
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>()
+        .join(",");
+        let origin = self
+            .url()
+            .filter(|_| !storage_types.is_empty())
+            .and_then(|url| Url::parse(&url).ok())
+            .map(|url| url.origin().ascii_serialization());
+        if let Some(origin) = origin {
+            let params = serde_json::json!({ "origin": origin, "storageTypes": storage_types });
+            self.call_devtools_method("Storage.clearDataForOrigin", &params.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn is_devtools_open(&self) -> bool {
+        // WebView2 doesn't expose the state of its dev tools window.
+        false
+    }
+
+    fn open_devtools(&self, _attachment: Option<DevtoolsAttachment>) -> Result<()> {
+        if let Some(c) = self.controller.get() {
+            c.get_webview()?.open_dev_tools_window()?;
+        }
+        Ok(())
+    }
+
+    fn close_devtools(&self) -> Result<()> {
+        // WebView2 can't close its dev tools window.
+        Ok(())
+    }
+
+    fn devtools_attached_height(&self) -> Option<u32> {
+        // The dev tools of WebView2 are always in a window of their own.
+        None
+    }
+
+    fn focus(&self) -> Result<()> {
+        if let Some(c) = self.controller.get() {
+            c.move_focus(MoveFocusReason::Programmatic)?;
+        }
+        Ok(())
+    }
 }
 
 impl InnerWebView {
@@ -180,4 +558,35 @@ impl InnerWebView {
 
         Ok(())
     }
+
+    // WebView2 has no API of its own to clear the website data.
+    fn call_devtools_method(&self, method: &str, params: &str) -> Result<()> {
+        if let Some(c) = self.controller.get() {
+            c.get_webview()?
+                .call_dev_tools_protocol_method(method, params, |_| Ok(()))?;
+        }
+        Ok(())
+    }
+}
+
+fn navigate(
+    webview: &webview2::WebView,
+    url: &Url,
+    custom_protocol_names: &[String],
+) -> webview2::Result<()> {
+    if url.cannot_be_a_base() {
+        webview.navigate_to_string(url.as_str())
+    } else {
+        let mut url_string = String::from(url.as_str());
+        let name = url.scheme();
+        if custom_protocol_names.iter().any(|other| other == name) {
+            // WebView2 doesn't support non-standard protocols yet, so we have to use this workaround
+            // See https://github.com/MicrosoftEdge/WebView2Feedback/issues/73
+            url_string = url.as_str().replace(
+                &format!("{}://", name),
+                &format!("file://custom-protocol-{}", name),
+            )
+        }
+        webview.navigate(&url_string)
+    }
 }